@@ -23,19 +23,21 @@ _start:
 abs:
         subi    $sp, $sp, 8
         sw      $ra, 0($sp)
-        sw      $a0, 4($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
 
         lw      $t0, 4($sp)
-        li      $t1, 0
-        bge     $t0, $t1, if_0
+        bge     $t0, $zero, if_0
 
         lw      $t0, 4($sp)
-        li      $t1, -1
-        mulo    $t0, $t0, $t1
+        li      $t9, -1
+        mulo    $t0, $t0, $t9
         sw      $t0, 4($sp)
 
 if_0:
-        lw      $v0, 4($sp)
+        lw      $t0, 4($sp)
+        move    $v0, $t0
         lw      $ra, 0($sp)
         addi    $sp, $sp, 8
         jr      $ra
@@ -43,43 +45,49 @@ if_0:
 getGuess:
         subi    $sp, $sp, 16
         sw      $ra, 0($sp)
-        sw      $a0, 4($sp)
-        sw      $a1, 8($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
 
         lw      $t0, 4($sp)
         lw      $t1, 8($sp)
-        add     $t2, $t0, $t1
-        sw      $t2, 12($sp)
+        add     $t0, $t0, $t1
+        sw      $t0, 12($sp)
 
         lw      $t0, 12($sp)
-        li      $t1, 0
-        bge     $t0, $t1, if_1
+        bge     $t0, $zero, if_1
 
-        lw      $a0, 12($sp)
+        lw      $t0, 12($sp)
+        move    $a0, $t0
         jal     abs
-        sw      $v0, 12($sp)
-
+        move    $t0, $v0
+        sw      $t0, 12($sp)
 
         lw      $t0, 12($sp)
-        srl     $t0, $t0, 1
+        sra     $t0, $t0, 1
         sw      $t0, 12($sp)
 
-        lw      $t1, 12($sp)
-        li      $t2, -1
-        mulo    $t1, $t1, $t2
-        sw      $t1, 12($sp)
+        lw      $t0, 12($sp)
+        li      $t9, -1
+        mulo    $t0, $t0, $t9
+        sw      $t0, 12($sp)
 
-        lw      $v0, 12($sp)
+        lw      $t0, 12($sp)
+        move    $v0, $t0
         lw      $ra, 0($sp)
         addi    $sp, $sp, 16
         jr      $ra
 
 if_1:
-        lw      $t1, 12($sp)
-        srl     $t1, $t1, 1
-        sw      $t1, 12($sp)
+        lw      $t0, 12($sp)
+        sra     $t0, $t0, 1
+        sw      $t0, 12($sp)
 
-        lw      $v0, 12($sp)
+        lw      $t0, 12($sp)
+        move    $v0, $t0
         lw      $ra, 0($sp)
         addi    $sp, $sp, 16
         jr      $ra
@@ -87,25 +95,32 @@ if_1:
 askQuestion:
         subi    $sp, $sp, 12
         sw      $ra, 0($sp)
-        sw      $a0, 4($sp)
 
-        la      $a0, str_0
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        la      $t0, str_0
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        lw      $a0, 4($sp)
+        lw      $t0, 4($sp)
+        move    $a0, $t0
         li      $v0, 1
         syscall
 
-        la      $a0, str_1
+        la      $t0, str_1
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
         li      $v0, 5
         syscall
-        sw      $v0, 8($sp)
+        move    $t0, $v0
+        sw      $t0, 8($sp)
 
-        lw      $v0, 8($sp)
+        lw      $t0, 8($sp)
+        move    $v0, $t0
         lw      $ra, 0($sp)
         addi    $sp, $sp, 12
         jr      $ra
@@ -114,15 +129,18 @@ promptMin:
         subi    $sp, $sp, 8
         sw      $ra, 0($sp)
 
-        la      $a0, str_2
+        la      $t0, str_2
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
         li      $v0, 5
         syscall
-        sw      $v0, 4($sp)
+        move    $t0, $v0
+        sw      $t0, 4($sp)
 
-        lw      $v0, 4($sp)
+        lw      $t0, 4($sp)
+        move    $v0, $t0
         lw      $ra, 0($sp)
         addi    $sp, $sp, 8
         jr      $ra
@@ -131,15 +149,18 @@ promptMax:
         subi    $sp, $sp, 8
         sw      $ra, 0($sp)
 
-        la      $a0, str_3
+        la      $t0, str_3
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
         li      $v0, 5
         syscall
-        sw      $v0, 4($sp)
+        move    $t0, $v0
+        sw      $t0, 4($sp)
 
-        lw      $v0, 4($sp)
+        lw      $t0, 4($sp)
+        move    $v0, $t0
         lw      $ra, 0($sp)
         addi    $sp, $sp, 8
         jr      $ra
@@ -149,140 +170,153 @@ main:
         sw      $ra, 0($sp)
 
         jal     promptMin
-        sw      $v0, 4($sp)
+        move    $t0, $v0
+        sw      $t0, 4($sp)
 
         jal     promptMax
-        sw      $v0, 8($sp)
+        move    $t0, $v0
+        sw      $t0, 8($sp)
 
-        li      $t2, 1
-        sw      $t2, 12($sp)
+        li      $t8, 1
+        sw      $t8, 12($sp)
 
-        la      $a0, str_4
+        la      $t0, str_4
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
         j       while_1_end
 
 while_1_body:
-        lw      $t2, 4($sp)
-        sw      $t2, 16($sp)
+        lw      $t0, 4($sp)
+        sw      $t0, 16($sp)
 
-        lw      $t2, 8($sp)
-        sw      $t2, 20($sp)
+        lw      $t0, 8($sp)
+        sw      $t0, 20($sp)
 
-        li      $t2, 0
-        sw      $t2, 24($sp)
+        sw      $zero, 24($sp)
 
-        la      $a0, str_5
+        la      $t0, str_5
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        lw      $a0, 16($sp)
+        lw      $t0, 16($sp)
+        move    $a0, $t0
         li      $v0, 1
         syscall
 
-        la      $a0, str_6
+        la      $t0, str_6
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        lw      $a0, 20($sp)
+        lw      $t0, 20($sp)
+        move    $a0, $t0
         li      $v0, 1
         syscall
 
-        la      $a0, str_4
+        la      $t0, str_4
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        li      $t2, 1
-        sw      $t2, 28($sp)
+        li      $t8, 1
+        sw      $t8, 28($sp)
 
-        lw      $a0, 16($sp)
-        lw      $a1, 20($sp)
+        lw      $t0, 16($sp)
+        lw      $t1, 20($sp)
+        move    $a0, $t0
+        move    $a1, $t1
         jal     getGuess
-        sw      $v0, 32($sp)
+        move    $t0, $v0
+        sw      $t0, 32($sp)
 
         j       while_0_end
 
 while_0_body:
-        lw      $a0, 32($sp)
+        lw      $t0, 32($sp)
+        move    $a0, $t0
         jal     askQuestion
-        sw      $v0, 28($sp)
+        move    $t0, $v0
+        sw      $t0, 28($sp)
 
+        lw      $t0, 28($sp)
+        li      $t9, 1
+        bne     $t0, $t9, if_2
 
-        lw      $t3, 28($sp)
-        li      $t4, 1
-        bne     $t3, $t4, if_2
-
-        lw      $t3, 32($sp)
-        li      $t4, 1
-        sub     $t3, $t3, $t4
-        sw      $t3, 20($sp)
+        lw      $t0, 32($sp)
+        subi    $t0, $t0, 1
+        sw      $t0, 20($sp)
 
 if_2:
-        lw      $t3, 28($sp)
-        li      $t4, 2
-        bne     $t3, $t4, if_3
+        lw      $t0, 28($sp)
+        li      $t9, 2
+        bne     $t0, $t9, if_3
 
-        lw      $t3, 32($sp)
-        li      $t4, 1
-        add     $t3, $t3, $t4
-        sw      $t3, 16($sp)
+        lw      $t0, 32($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 16($sp)
 
 if_3:
-        lw      $a0, 16($sp)
-        lw      $a1, 20($sp)
+        lw      $t0, 16($sp)
+        lw      $t1, 20($sp)
+        move    $a0, $t0
+        move    $a1, $t1
         jal     getGuess
-        sw      $v0, 32($sp)
-
+        move    $t0, $v0
+        sw      $t0, 32($sp)
 
-        lw      $t3, 24($sp)
-        li      $t4, 1
-        add     $t3, $t3, $t4
-        sw      $t3, 24($sp)
+        lw      $t0, 24($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 24($sp)
 
 while_0_end:
-        lw      $t3, 28($sp)
-        li      $t4, 0
-        bne     $t3, $t4, while_0_body
+        lw      $t0, 28($sp)
+        bne     $t0, $zero, while_0_body
 
-        la      $a0, str_7
+        la      $t0, str_7
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        la      $a0, str_8
+        la      $t0, str_8
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        lw      $a0, 24($sp)
+        lw      $t0, 24($sp)
+        move    $a0, $t0
         li      $v0, 1
         syscall
 
-        la      $a0, str_9
+        la      $t0, str_9
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
         li      $v0, 5
         syscall
-        sw      $v0, 36($sp)
+        move    $t0, $v0
+        sw      $t0, 36($sp)
 
-        li      $t4, 1
-        sw      $t4, 12($sp)
+        li      $t8, 1
+        sw      $t8, 12($sp)
 
-        lw      $t4, 36($sp)
-        li      $t5, 0
-        bne     $t4, $t5, if_4
+        lw      $t0, 36($sp)
+        bne     $t0, $zero, if_4
 
-        li      $t4, 0
-        sw      $t4, 12($sp)
+        sw      $zero, 12($sp)
 
 if_4:
-        la      $a0, str_4
+        la      $t0, str_4
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
 while_1_end:
-        lw      $t4, 12($sp)
-        bne     $t4, $zero, while_1_body
+        lw      $t0, 12($sp)
+        bne     $t0, $zero, while_1_body
 
         lw      $ra, 0($sp)
         addi    $sp, $sp, 40
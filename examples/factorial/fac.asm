@@ -11,109 +11,125 @@
 _start:
         jal     main
         li      $v0, 10
-        syscall 
+        syscall
+
 mult:
-        addi    $sp, $sp, -20
-        sw      $ra, 16($sp)
+        subi    $sp, $sp, 20
+        sw      $ra, 0($sp)
 
-        sw      $a0, 12($sp)
-        sw      $a1, 8($sp)
-        li      $t0, 0
+        move    $t0, $a0
         sw      $t0, 4($sp)
 
-        li      $t0, 0
-        sw      $t0, 0($sp)
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        sw      $zero, 12($sp)
+
+        sw      $zero, 16($sp)
 
         j       for_0_end
 
 for_0_body:
-        lw      $t0, 4($sp)
-        lw      $t1, 12($sp)
+        lw      $t0, 12($sp)
+        lw      $t1, 4($sp)
         add     $t0, $t0, $t1
-        sw      $t0, 4($sp)
+        sw      $t0, 12($sp)
 
-        lw      $t0, 0($sp)
-        li      $t1, 1
-        add     $t0, $t0, $t1
-        sw      $t0, 0($sp)
+        lw      $t0, 16($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 16($sp)
 
 for_0_end:
-        lw      $t0, 0($sp)
+        lw      $t0, 16($sp)
         lw      $t1, 8($sp)
         blt     $t0, $t1, for_0_body
 
-        lw      $v0, 4($sp)
-        lw      $ra, 16($sp)
+        lw      $t0, 12($sp)
+        move    $v0, $t0
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 20
         jr      $ra
 
 fac:
-        addi    $sp, $sp, -16
-        sw      $ra, 12($sp)
+        subi    $sp, $sp, 16
+        sw      $ra, 0($sp)
 
-        sw      $a0, 8($sp)
-        li      $t0, 1
+        move    $t0, $a0
         sw      $t0, 4($sp)
 
-        li      $t0, 1
-        sw      $t0, 0($sp)
+        li      $t8, 1
+        sw      $t8, 8($sp)
+
+        li      $t8, 1
+        sw      $t8, 12($sp)
 
         j       for_1_end
 
 for_1_body:
-        lw      $a0, 4($sp)
-        lw      $a1, 0($sp)
+        lw      $t0, 8($sp)
+        lw      $t1, 12($sp)
+        move    $a0, $t0
+        move    $a1, $t1
         jal     mult
-        sw      $v0, 4($sp)
+        move    $t0, $v0
+        sw      $t0, 8($sp)
 
-        lw      $t0, 0($sp)
-        li      $t1, 1
-        add     $t0, $t0, $t1
-        sw      $t0, 0($sp)
+        lw      $t0, 12($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 12($sp)
 
 for_1_end:
-        lw      $t0, 0($sp)
-        lw      $t1, 8($sp)
+        lw      $t0, 12($sp)
+        lw      $t1, 4($sp)
         ble     $t0, $t1, for_1_body
 
-        lw      $v0, 4($sp)
-        lw      $ra, 12($sp)
+        lw      $t0, 8($sp)
+        move    $v0, $t0
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 16
         jr      $ra
 
 main:
-        addi    $sp, $sp, -12
-        sw      $ra, 8($sp)
+        subi    $sp, $sp, 12
+        sw      $ra, 0($sp)
 
-        la      $a0, str_0
+        la      $t0, str_0
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
         li      $v0, 5
-        syscall 
-        sw      $v0, 4($sp)
+        syscall
+        move    $t0, $v0
+        sw      $t0, 4($sp)
 
-        lw      $a0, 4($sp)
+        lw      $t0, 4($sp)
+        move    $a0, $t0
         jal     fac
-        sw      $v0, 0($sp)
+        move    $t0, $v0
+        sw      $t0, 8($sp)
 
-        la      $a0, str_1
+        la      $t0, str_1
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
-        lw      $a0, 4($sp)
+        lw      $t0, 4($sp)
+        move    $a0, $t0
         li      $v0, 1
-        syscall 
+        syscall
 
-        la      $a0, str_2
+        la      $t0, str_2
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
-        lw      $a0, 0($sp)
+        lw      $t0, 8($sp)
+        move    $a0, $t0
         li      $v0, 1
-        syscall 
+        syscall
 
-        lw      $ra, 8($sp)
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 12
         jr      $ra
 
@@ -20,155 +20,170 @@ _start:
         syscall
 
 printHint:
-        addi    $sp, $sp, -12
-        sw      $ra, 8($sp)
+        subi    $sp, $sp, 12
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
 
-        sw      $a0, 4($sp)
-        sw      $a1, 0($sp)
         lw      $t0, 4($sp)
-        lw      $t1, 0($sp)
+        lw      $t1, 8($sp)
         bge     $t0, $t1, if_0
 
-        la      $a0, str_0
+        la      $t0, str_0
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
         li      $t0, 1
         move    $v0, $t0
-        lw      $ra, 8($sp)
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 12
         jr      $ra
 
 if_0:
-        lw      $t1, 4($sp)
-        lw      $t2, 0($sp)
-        ble     $t1, $t2, if_1
+        lw      $t0, 4($sp)
+        lw      $t1, 8($sp)
+        ble     $t0, $t1, if_1
 
-        la      $a0, str_1
+        la      $t0, str_1
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        li      $t1, 1
-        move    $v0, $t1
-        lw      $ra, 8($sp)
+        li      $t0, 1
+        move    $v0, $t0
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 12
         jr      $ra
 
 if_1:
-        li      $t2, 0
-        move    $v0, $t2
-        lw      $ra, 8($sp)
+        li      $t0, 0
+        move    $v0, $t0
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 12
         jr      $ra
 
 main:
-        addi    $sp, $sp, -28
-        sw      $ra, 24($sp)
+        subi    $sp, $sp, 28
+        sw      $ra, 0($sp)
 
-        li      $t0, 1
-        sw      $t0, 20($sp)
+        li      $t8, 1
+        sw      $t8, 4($sp)
 
-        li      $t0, 10000
-        sw      $t0, 16($sp)
+        li      $t8, 10000
+        sw      $t8, 8($sp)
 
         j       while_1_end
 
 while_1_body:
-        li      $t0, 0
-        sw      $t0, 12($sp)
+        sw      $zero, 12($sp)
 
-        li      $t0, 1
-        sw      $t0, 8($sp)
+        li      $t8, 1
+        sw      $t8, 16($sp)
 
-        li      $a0, 40
-        lw      $a1, 16($sp)
+        li      $t0, 40
+        lw      $t1, 8($sp)
+        move    $a0, $t0
+        move    $a1, $t1
         li      $v0, 42
         syscall
-        sw      $a0, 4($sp)
+        move    $t0, $a0
+        sw      $t0, 20($sp)
 
-        lw      $t1, 4($sp)
-        li      $t2, 1
-        add     $t1, $t1, $t2
-        sw      $t1, 4($sp)
+        lw      $t0, 20($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 20($sp)
 
-        li      $t1, 0
-        sw      $t1, 0($sp)
+        sw      $zero, 24($sp)
 
         j       while_0_end
 
 while_0_body:
-        la      $a0, str_2
+        la      $t0, str_2
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        lw      $a0, 16($sp)
+        lw      $t0, 8($sp)
+        move    $a0, $t0
         li      $v0, 1
         syscall
 
-        la      $a0, str_3
+        la      $t0, str_3
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
         li      $v0, 5
         syscall
-        sw      $v0, 0($sp)
-
+        move    $t0, $v0
+        sw      $t0, 24($sp)
 
-        lw      $a0, 0($sp)
-        lw      $a1, 4($sp)
+        lw      $t0, 24($sp)
+        lw      $t1, 20($sp)
+        move    $a0, $t0
+        move    $a1, $t1
         jal     printHint
-        sw      $v0, 8($sp)
-
+        move    $t0, $v0
+        sw      $t0, 16($sp)
 
-        la      $a0, str_4
+        la      $t0, str_4
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        lw      $t1, 12($sp)
-        li      $t2, 1
-        add     $t1, $t1, $t2
-        sw      $t1, 12($sp)
+        lw      $t0, 12($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 12($sp)
 
 while_0_end:
-        lw      $t1, 8($sp)
-        li      $t2, 0
-        bne     $t1, $t2, while_0_body
+        lw      $t0, 16($sp)
+        bne     $t0, $zero, while_0_body
 
-        la      $a0, str_5
+        la      $t0, str_5
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        lw      $a0, 4($sp)
+        lw      $t0, 20($sp)
+        move    $a0, $t0
         li      $v0, 1
         syscall
 
-        la      $a0, str_6
+        la      $t0, str_6
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        lw      $a0, 12($sp)
+        lw      $t0, 12($sp)
+        move    $a0, $t0
         li      $v0, 1
         syscall
 
-        la      $a0, str_7
+        la      $t0, str_7
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
-        la      $a0, str_8
+        la      $t0, str_8
+        move    $a0, $t0
         li      $v0, 4
         syscall
 
         li      $v0, 5
         syscall
-        sw      $v0, 20($sp)
-
+        move    $t0, $v0
+        sw      $t0, 4($sp)
 
 while_1_end:
-        lw      $t1, 20($sp)
-        li      $t2, 0
-        bgt     $t1, $t2, while_1_body
+        lw      $t0, 4($sp)
+        bgt     $t0, $zero, while_1_body
 
-        lw      $ra, 24($sp)
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 28
         jr      $ra
 
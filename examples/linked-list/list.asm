@@ -0,0 +1,389 @@
+# Assembly generated by ecc (Evil C Compiler)
+
+.data
+	global_allocations: .word 0
+	str_0: .asciiz " "
+	str_1: .asciiz "\n"
+	str_2: .asciiz "sum: "
+	str_3: .asciiz "second: "
+	str_4: .asciiz "x: "
+	str_5: .asciiz "allocations: "
+
+.text
+.globl _start
+
+_start:
+        jal     main
+        li      $v0, 10
+        syscall
+
+make_node:
+        subi    $sp, $sp, 16
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        li      $t0, 8
+        move    $a0, $t0
+        li      $v0, 9
+        syscall
+        move    $t0, $v0
+        sw      $t0, 12($sp)
+
+        lw      $t0, global_allocations
+        addi    $t0, $t0, 1
+        sw      $t0, global_allocations
+
+        lw      $t0, 4($sp)
+        lw      $t1, 12($sp)
+        sw      $t0, 0($t1)
+
+        lw      $t0, 8($sp)
+        lw      $t1, 12($sp)
+        sw      $t0, 4($t1)
+
+        lw      $t0, 12($sp)
+        move    $v0, $t0
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 16
+        jr      $ra
+
+insert:
+        subi    $sp, $sp, 20
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        lw      $t0, 4($sp)
+        lw      $t0, 4($t0)
+        beq     $t0, $zero, if_0_then
+
+        lw      $t0, 8($sp)
+        lw      $t1, 4($sp)
+        lw      $t1, 0($t1)
+        lw      $t1, 0($t1)
+        bge     $t0, $t1, if_0
+
+if_0_then:
+        lw      $t0, 8($sp)
+        lw      $t1, 4($sp)
+        lw      $t1, 0($t1)
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     make_node
+        move    $t0, $v0
+        lw      $t1, 4($sp)
+        sw      $t0, 0($t1)
+
+        lw      $t0, 4($sp)
+        lw      $t0, 4($t0)
+        addi    $t0, $t0, 1
+        lw      $t1, 4($sp)
+        sw      $t0, 4($t1)
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 20
+        jr      $ra
+
+if_0:
+        lw      $t0, 4($sp)
+        lw      $t0, 0($t0)
+        sw      $t0, 12($sp)
+
+        lw      $t0, 4($sp)
+        lw      $t0, 4($t0)
+        subi    $t0, $t0, 1
+        sw      $t0, 16($sp)
+
+        j       while_0_end
+
+while_0_body:
+        lw      $t0, 12($sp)
+        lw      $t0, 4($t0)
+        sw      $t0, 12($sp)
+
+        lw      $t0, 16($sp)
+        subi    $t0, $t0, 1
+        sw      $t0, 16($sp)
+
+while_0_end:
+        lw      $t0, 16($sp)
+        ble     $t0, $zero, while_0_end_exit
+
+        lw      $t0, 12($sp)
+        lw      $t0, 4($t0)
+        lw      $t0, 0($t0)
+        lw      $t1, 8($sp)
+        blt     $t0, $t1, while_0_body
+
+while_0_end_exit:
+        lw      $t0, 8($sp)
+        lw      $t1, 12($sp)
+        lw      $t1, 4($t1)
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     make_node
+        move    $t0, $v0
+        lw      $t1, 12($sp)
+        sw      $t0, 4($t1)
+
+        lw      $t0, 4($sp)
+        lw      $t0, 4($t0)
+        addi    $t0, $t0, 1
+        lw      $t1, 4($sp)
+        sw      $t0, 4($t1)
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 20
+        jr      $ra
+
+print_list:
+        subi    $sp, $sp, 16
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        lw      $t0, 4($sp)
+        lw      $t0, 0($t0)
+        sw      $t0, 8($sp)
+
+        sw      $zero, 12($sp)
+
+        j       for_0_end
+
+for_0_body:
+        lw      $t0, 8($sp)
+        lw      $t0, 0($t0)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_0
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 8($sp)
+        lw      $t0, 4($t0)
+        sw      $t0, 8($sp)
+
+        lw      $t0, 12($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 12($sp)
+
+for_0_end:
+        lw      $t0, 12($sp)
+        lw      $t1, 4($sp)
+        lw      $t1, 4($t1)
+        blt     $t0, $t1, for_0_body
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 16
+        jr      $ra
+
+sum:
+        subi    $sp, $sp, 20
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        sw      $zero, 12($sp)
+
+        lw      $t0, 4($sp)
+        lw      $t1, 8($sp)
+        sll     $t1, $t1, 2
+        add     $t0, $t0, $t1
+        sw      $t0, 16($sp)
+
+        j       while_1_end
+
+while_1_body:
+        lw      $t0, 12($sp)
+        lw      $t1, 4($sp)
+        lw      $t1, 0($t1)
+        add     $t0, $t0, $t1
+        sw      $t0, 12($sp)
+
+        lw      $t0, 4($sp)
+        addi    $t0, $t0, 4
+        sw      $t0, 4($sp)
+
+while_1_end:
+        lw      $t0, 4($sp)
+        lw      $t1, 16($sp)
+        bne     $t0, $t1, while_1_body
+
+        lw      $t0, 12($sp)
+        move    $v0, $t0
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 20
+        jr      $ra
+
+main:
+        subi    $sp, $sp, 40
+        sw      $ra, 0($sp)
+
+        sw      $zero, 4($sp)
+
+        sw      $zero, 8($sp)
+
+        li      $v0, 5
+        syscall
+        move    $t0, $v0
+        sw      $t0, 12($sp)
+
+        lw      $t0, 12($sp)
+        li      $t9, 4
+        mulo    $t0, $t0, $t9
+        move    $a0, $t0
+        li      $v0, 9
+        syscall
+        move    $t0, $v0
+        sw      $t0, 16($sp)
+
+        sw      $zero, 20($sp)
+
+        j       for_1_end
+
+for_1_body:
+        li      $v0, 5
+        syscall
+        move    $t0, $v0
+        lw      $t1, 16($sp)
+        lw      $t2, 20($sp)
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        sw      $t0, 0($t1)
+
+        addi    $t0, $sp, 4
+        lw      $t1, 16($sp)
+        lw      $t2, 20($sp)
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        lw      $t1, 0($t1)
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     insert
+
+        lw      $t0, 20($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 20($sp)
+
+for_1_end:
+        lw      $t0, 20($sp)
+        lw      $t1, 12($sp)
+        blt     $t0, $t1, for_1_body
+
+        addi    $t0, $sp, 4
+        move    $a0, $t0
+        jal     print_list
+
+        la      $t0, str_2
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 16($sp)
+        lw      $t1, 12($sp)
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     sum
+        move    $t0, $v0
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        addi    $t0, $sp, 4
+        sw      $t0, 24($sp)
+
+        lw      $t0, 24($sp)
+        lw      $t0, 0($t0)
+        lw      $t0, 4($t0)
+        sw      $t0, 28($sp)
+
+        la      $t0, str_3
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 28($sp)
+        lw      $t0, 0($t0)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        li      $t8, 10
+        sw      $t8, 32($sp)
+
+        addi    $t0, $sp, 32
+        sw      $t0, 36($sp)
+
+        lw      $t0, 36($sp)
+        lw      $t0, 0($t0)
+        li      $t9, 2
+        mulo    $t0, $t0, $t9
+        lw      $t1, 36($sp)
+        sw      $t0, 0($t1)
+
+        la      $t0, str_4
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 32($sp)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        la      $t0, str_5
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, global_allocations
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 40
+        jr      $ra
+
@@ -0,0 +1,464 @@
+# Assembly generated by ecc (Evil C Compiler)
+
+.data
+	.align 2
+	global_identity: .space 36
+	global_weights: .word global_weights_data
+	global_weights_data: .word 1, 10, 100
+	str_0: .asciiz " "
+	str_1: .asciiz "\n"
+	str_2: .asciiz "\nweighted rows: "
+	str_3: .asciiz "\ntotal: "
+
+.text
+.globl _start
+
+_start:
+        jal     main
+        li      $v0, 10
+        syscall
+
+fill:
+        subi    $sp, $sp, 20
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        sw      $zero, 12($sp)
+
+        j       for_1_end
+
+for_1_body:
+        sw      $zero, 16($sp)
+
+        j       for_0_end
+
+for_0_body:
+        lw      $t0, 8($sp)
+        lw      $t1, 12($sp)
+        li      $t9, 3
+        mulo    $t1, $t1, $t9
+        add     $t0, $t0, $t1
+        lw      $t1, 16($sp)
+        add     $t0, $t0, $t1
+        lw      $t1, 4($sp)
+        lw      $t2, 12($sp)
+        li      $t9, 12
+        mul     $t2, $t2, $t9
+        add     $t1, $t1, $t2
+        lw      $t2, 16($sp)
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        sw      $t0, 0($t1)
+
+        lw      $t0, 16($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 16($sp)
+
+for_0_end:
+        lw      $t0, 16($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_0_body
+
+        lw      $t0, 12($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 12($sp)
+
+for_1_end:
+        lw      $t0, 12($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_1_body
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 20
+        jr      $ra
+
+multiply:
+        subi    $sp, $sp, 32
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        move    $t0, $a2
+        sw      $t0, 12($sp)
+
+        sw      $zero, 16($sp)
+
+        j       for_4_end
+
+for_4_body:
+        sw      $zero, 20($sp)
+
+        j       for_3_end
+
+for_3_body:
+        sw      $zero, 24($sp)
+
+        sw      $zero, 28($sp)
+
+        j       for_2_end
+
+for_2_body:
+        lw      $t0, 24($sp)
+        lw      $t1, 4($sp)
+        lw      $t2, 16($sp)
+        li      $t9, 12
+        mul     $t2, $t2, $t9
+        add     $t1, $t1, $t2
+        lw      $t2, 28($sp)
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        lw      $t1, 0($t1)
+        lw      $t2, 8($sp)
+        lw      $t3, 28($sp)
+        li      $t9, 12
+        mul     $t3, $t3, $t9
+        add     $t2, $t2, $t3
+        lw      $t3, 20($sp)
+        sll     $t3, $t3, 2
+        add     $t2, $t2, $t3
+        lw      $t2, 0($t2)
+        mulo    $t1, $t1, $t2
+        add     $t0, $t0, $t1
+        sw      $t0, 24($sp)
+
+        lw      $t0, 28($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 28($sp)
+
+for_2_end:
+        lw      $t0, 28($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_2_body
+
+        lw      $t0, 24($sp)
+        lw      $t1, 12($sp)
+        lw      $t2, 16($sp)
+        li      $t9, 12
+        mul     $t2, $t2, $t9
+        add     $t1, $t1, $t2
+        lw      $t2, 20($sp)
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        sw      $t0, 0($t1)
+
+        lw      $t0, 20($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 20($sp)
+
+for_3_end:
+        lw      $t0, 20($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_3_body
+
+        lw      $t0, 16($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 16($sp)
+
+for_4_end:
+        lw      $t0, 16($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_4_body
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 32
+        jr      $ra
+
+dot:
+        subi    $sp, $sp, 20
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        sw      $zero, 12($sp)
+
+        sw      $zero, 16($sp)
+
+        j       for_5_end
+
+for_5_body:
+        lw      $t0, 12($sp)
+        lw      $t1, 4($sp)
+        lw      $t2, 16($sp)
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        lw      $t1, 0($t1)
+        lw      $t2, 8($sp)
+        lw      $t3, 16($sp)
+        sll     $t3, $t3, 2
+        add     $t2, $t2, $t3
+        lw      $t2, 0($t2)
+        mulo    $t1, $t1, $t2
+        add     $t0, $t0, $t1
+        sw      $t0, 12($sp)
+
+        lw      $t0, 16($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 16($sp)
+
+for_5_end:
+        lw      $t0, 16($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_5_body
+
+        lw      $t0, 12($sp)
+        move    $v0, $t0
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 20
+        jr      $ra
+
+print_matrix:
+        subi    $sp, $sp, 16
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        sw      $zero, 8($sp)
+
+        j       for_7_end
+
+for_7_body:
+        sw      $zero, 12($sp)
+
+        j       for_6_end
+
+for_6_body:
+        lw      $t0, 4($sp)
+        lw      $t1, 8($sp)
+        li      $t9, 12
+        mul     $t1, $t1, $t9
+        add     $t0, $t0, $t1
+        lw      $t1, 12($sp)
+        sll     $t1, $t1, 2
+        add     $t0, $t0, $t1
+        lw      $t0, 0($t0)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_0
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 12($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 12($sp)
+
+for_6_end:
+        lw      $t0, 12($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_6_body
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 8($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 8($sp)
+
+for_7_end:
+        lw      $t0, 8($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_7_body
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 16
+        jr      $ra
+
+main:
+        subi    $sp, $sp, 100
+        sw      $ra, 0($sp)
+
+        sw      $zero, 4($sp)
+
+        j       for_8_end
+
+for_8_body:
+        la      $t0, global_identity
+        lw      $t1, 4($sp)
+        li      $t9, 12
+        mul     $t1, $t1, $t9
+        add     $t0, $t0, $t1
+        lw      $t1, 4($sp)
+        sll     $t1, $t1, 2
+        add     $t0, $t0, $t1
+        li      $t8, 1
+        sw      $t8, 0($t0)
+
+        lw      $t0, 4($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 4($sp)
+
+for_8_end:
+        lw      $t0, 4($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_8_body
+
+        addi    $t0, $sp, 8
+        addi    $t1, $sp, 44
+zero_4:
+        sw      $zero, 0($t0)
+
+        addi    $t0, $t0, 4
+        blt     $t0, $t1, zero_4
+
+        addi    $t0, $sp, 44
+        addi    $t1, $sp, 80
+zero_5:
+        sw      $zero, 0($t0)
+
+        addi    $t0, $t0, 4
+        blt     $t0, $t1, zero_5
+
+        addi    $t0, $sp, 8
+        li      $v0, 5
+        syscall
+        move    $t1, $v0
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     fill
+
+        addi    $t0, $sp, 8
+        la      $t1, global_identity
+        addi    $t2, $sp, 44
+        move    $a0, $t0
+        move    $a1, $t1
+        move    $a2, $t2
+        jal     multiply
+
+        addi    $t0, $sp, 44
+        move    $a0, $t0
+        jal     print_matrix
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        addi    $t0, $sp, 8
+        addi    $t1, $sp, 8
+        addi    $t2, $sp, 44
+        move    $a0, $t0
+        move    $a1, $t1
+        move    $a2, $t2
+        jal     multiply
+
+        addi    $t0, $sp, 44
+        move    $a0, $t0
+        jal     print_matrix
+
+        sw      $zero, 80($sp)
+
+        sw      $zero, 84($sp)
+
+        sw      $zero, 88($sp)
+
+        sw      $zero, 92($sp)
+
+        sw      $zero, 96($sp)
+
+        j       for_9_end
+
+for_9_body:
+        addi    $t0, $sp, 44
+        lw      $t1, 96($sp)
+        li      $t9, 12
+        mul     $t1, $t1, $t9
+        add     $t0, $t0, $t1
+        lw      $t1, global_weights
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     dot
+        move    $t0, $v0
+        addi    $t1, $sp, 80
+        lw      $t2, 96($sp)
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        sw      $t0, 0($t1)
+
+        lw      $t0, 92($sp)
+        addi    $t1, $sp, 80
+        lw      $t2, 96($sp)
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        lw      $t1, 0($t1)
+        add     $t0, $t0, $t1
+        sw      $t0, 92($sp)
+
+        lw      $t0, 96($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 96($sp)
+
+for_9_end:
+        lw      $t0, 96($sp)
+        li      $t9, 3
+        blt     $t0, $t9, for_9_body
+
+        la      $t0, str_2
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        addi    $t0, $sp, 80
+        lw      $t0, 0($t0)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_0
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        addi    $t0, $sp, 80
+        lw      $t0, 4($t0)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_0
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        addi    $t0, $sp, 80
+        lw      $t0, 8($t0)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_3
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 92($sp)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 100
+        jr      $ra
+
@@ -0,0 +1,493 @@
+# Assembly generated by ecc (Evil C Compiler)
+
+.data
+	global_calls: .word 0
+	str_0: .asciiz "negative"
+	str_1: .asciiz "zero"
+	str_2: .asciiz "even"
+	str_3: .asciiz "odd"
+	str_4: .asciiz "\n"
+	str_5: .asciiz " = "
+	str_6: .asciiz "a + b"
+	str_7: .asciiz "a - b"
+	str_8: .asciiz "a * b"
+	str_9: .asciiz "a / b"
+	str_10: .asciiz "a % b"
+	str_11: .asciiz "-a / b"
+	str_12: .asciiz "-a % b"
+	str_13: .asciiz "a & b"
+	str_14: .asciiz "a | b"
+	str_15: .asciiz "a ^ b"
+	str_16: .asciiz "~a"
+	str_17: .asciiz "a << 3"
+	str_18: .asciiz "-a >> 2"
+	str_19: .asciiz "1 << b"
+	str_20: .asciiz "2 + 3 * 4 - 10 / 3"
+	str_21: .asciiz "(2 + 3) * (4 - 10) / 3"
+	str_22: .asciiz "a > b > 0\n"
+	str_23: .asciiz "this shouldn't print\n"
+	str_24: .asciiz "a isn't below b\n"
+	str_25: .asciiz "only either is true\n"
+	str_26: .asciiz "calls"
+	str_27: .asciiz "FizzBuzz"
+	str_28: .asciiz "Fizz"
+	str_29: .asciiz "Buzz"
+	str_30: .asciiz " "
+
+.text
+.globl _start
+
+_start:
+        jal     main
+        li      $v0, 10
+        syscall
+
+noisy:
+        subi    $sp, $sp, 8
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        lw      $t0, global_calls
+        addi    $t0, $t0, 1
+        sw      $t0, global_calls
+
+        lw      $t0, 4($sp)
+        move    $v0, $t0
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 8
+        jr      $ra
+
+classify:
+        subi    $sp, $sp, 8
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        lw      $t0, 4($sp)
+        bge     $t0, $zero, if_0
+
+        la      $t0, str_0
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        j       if_0_end
+
+if_0:
+        lw      $t0, 4($sp)
+        bne     $t0, $zero, if_1
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        j       if_1_end
+
+if_1:
+        lw      $t0, 4($sp)
+        li      $t9, 2
+        rem     $t0, $t0, $t9
+        bne     $t0, $zero, if_2
+
+        la      $t0, str_2
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        j       if_2_end
+
+if_2:
+        la      $t0, str_3
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+if_2_end:
+if_1_end:
+if_0_end:
+        la      $t0, str_4
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 8
+        jr      $ra
+
+show:
+        subi    $sp, $sp, 12
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        lw      $t0, 4($sp)
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        la      $t0, str_5
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 8($sp)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_4
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 12
+        jr      $ra
+
+main:
+        subi    $sp, $sp, 28
+        sw      $ra, 0($sp)
+        sw      $s0, 4($sp)
+
+        li      $v0, 5
+        syscall
+        move    $t0, $v0
+        sw      $t0, 8($sp)
+
+        li      $v0, 5
+        syscall
+        move    $t0, $v0
+        sw      $t0, 12($sp)
+
+        la      $t0, str_6
+        lw      $t1, 8($sp)
+        lw      $t2, 12($sp)
+        add     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_7
+        lw      $t1, 8($sp)
+        lw      $t2, 12($sp)
+        sub     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_8
+        lw      $t1, 8($sp)
+        lw      $t2, 12($sp)
+        mulo    $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_9
+        lw      $t1, 8($sp)
+        lw      $t2, 12($sp)
+        div     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_10
+        lw      $t1, 8($sp)
+        lw      $t2, 12($sp)
+        rem     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_11
+        lw      $t1, 8($sp)
+        sub     $t1, $zero, $t1
+        lw      $t2, 12($sp)
+        div     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_12
+        lw      $t1, 8($sp)
+        sub     $t1, $zero, $t1
+        lw      $t2, 12($sp)
+        rem     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_13
+        lw      $t1, 8($sp)
+        lw      $t2, 12($sp)
+        and     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_14
+        lw      $t1, 8($sp)
+        lw      $t2, 12($sp)
+        or      $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_15
+        lw      $t1, 8($sp)
+        lw      $t2, 12($sp)
+        xor     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_16
+        lw      $t1, 8($sp)
+        nor     $t1, $t1, $zero
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_17
+        lw      $t1, 8($sp)
+        sll     $t1, $t1, 3
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_18
+        lw      $t1, 8($sp)
+        sub     $t1, $zero, $t1
+        sra     $t1, $t1, 2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_19
+        li      $t1, 1
+        lw      $t2, 12($sp)
+        sllv    $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_20
+        li      $t1, 3
+        li      $t9, 4
+        mulo    $t1, $t1, $t9
+        addi    $t1, $t1, 2
+        li      $t2, 10
+        li      $t9, 3
+        div     $t2, $t2, $t9
+        sub     $t1, $t1, $t2
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        la      $t0, str_21
+        li      $t1, 2
+        addi    $t1, $t1, 3
+        li      $t2, 4
+        subi    $t2, $t2, 10
+        mulo    $t1, $t1, $t2
+        li      $t9, 3
+        div     $t1, $t1, $t9
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        lw      $t0, 8($sp)
+        lw      $t1, 12($sp)
+        ble     $t0, $t1, if_3
+
+        lw      $t0, 12($sp)
+        ble     $t0, $zero, if_3
+
+        la      $t0, str_22
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+if_3:
+        lw      $t0, 8($sp)
+        lw      $t1, 12($sp)
+        blt     $t0, $t1, if_4_then
+
+        lw      $t0, 8($sp)
+        li      $t9, 100
+        bne     $t0, $t9, if_4
+
+if_4_then:
+        la      $t0, str_23
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+if_4:
+        lw      $t0, 8($sp)
+        lw      $t1, 12($sp)
+        blt     $t0, $t1, if_5
+
+        la      $t0, str_24
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+if_5:
+        li      $s0, 0
+        li      $t0, 0
+        move    $a0, $t0
+        jal     noisy
+        move    $t0, $v0
+        beq     $t0, $zero, and_5_end
+
+        li      $t0, 1
+        move    $a0, $t0
+        jal     noisy
+        move    $t0, $v0
+        beq     $t0, $zero, and_5_end
+
+        li      $s0, 1
+and_5_end:
+        sw      $s0, 16($sp)
+
+        li      $s0, 0
+        li      $t0, 1
+        move    $a0, $t0
+        jal     noisy
+        move    $t0, $v0
+        bne     $t0, $zero, or_7_true
+
+        li      $t0, 0
+        move    $a0, $t0
+        jal     noisy
+        move    $t0, $v0
+        beq     $t0, $zero, or_7_end
+
+or_7_true:
+        li      $s0, 1
+or_7_end:
+        sw      $s0, 20($sp)
+
+        lw      $t0, 16($sp)
+        bne     $t0, $zero, if_6
+
+        lw      $t0, 20($sp)
+        beq     $t0, $zero, if_6
+
+        la      $t0, str_25
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+if_6:
+        la      $t0, str_26
+        lw      $t1, global_calls
+        move    $a0, $t0
+        move    $a1, $t1
+        jal     show
+
+        lw      $t0, 8($sp)
+        lw      $t1, 8($sp)
+        sub     $t0, $t0, $t1
+        move    $a0, $t0
+        jal     classify
+
+        lw      $t0, 8($sp)
+        move    $a0, $t0
+        jal     classify
+
+        lw      $t0, 12($sp)
+        move    $a0, $t0
+        jal     classify
+
+        lw      $t0, 12($sp)
+        sub     $t0, $zero, $t0
+        move    $a0, $t0
+        jal     classify
+
+        li      $t8, 1
+        sw      $t8, 24($sp)
+
+        j       for_0_end
+
+for_0_body:
+        lw      $t0, 24($sp)
+        li      $t9, 15
+        rem     $t0, $t0, $t9
+        bne     $t0, $zero, if_7
+
+        la      $t0, str_27
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        j       if_7_end
+
+if_7:
+        lw      $t0, 24($sp)
+        li      $t9, 3
+        rem     $t0, $t0, $t9
+        bne     $t0, $zero, if_8
+
+        la      $t0, str_28
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        j       if_8_end
+
+if_8:
+        lw      $t0, 24($sp)
+        li      $t9, 5
+        rem     $t0, $t0, $t9
+        bne     $t0, $zero, if_9
+
+        la      $t0, str_29
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        j       if_9_end
+
+if_9:
+        lw      $t0, 24($sp)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+if_9_end:
+if_8_end:
+if_7_end:
+        la      $t0, str_30
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 24($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 24($sp)
+
+for_0_end:
+        lw      $t0, 24($sp)
+        li      $t9, 15
+        ble     $t0, $t9, for_0_body
+
+        la      $t0, str_4
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $ra, 0($sp)
+        lw      $s0, 4($sp)
+        addi    $sp, $sp, 28
+        jr      $ra
+
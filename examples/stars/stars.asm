@@ -13,147 +13,154 @@
 _start:
         jal     main
         li      $v0, 10
-        syscall 
+        syscall
+
 stars:
-        addi    $sp, $sp, -28
-        sw      $ra, 24($sp)
+        subi    $sp, $sp, 28
+        sw      $ra, 0($sp)
 
-        sw      $a0, 20($sp)
-        sw      $a1, 16($sp)
-        li      $t0, 0
-        sw      $t0, 12($sp)
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        move    $t0, $a1
+        sw      $t0, 8($sp)
+
+        sw      $zero, 12($sp)
 
         j       for_3_end
 
 for_3_body:
-        lw      $t0, 16($sp)
-        beq     $t0, $zero, if_0
+        lw      $t0, 8($sp)
+        bne     $t0, $zero, if_0
 
-        li      $t0, 0
-        sw      $t0, 8($sp)
+        sw      $zero, 16($sp)
 
         j       for_0_end
 
 for_0_body:
-        la      $a0, str_0
+        la      $t0, str_0
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
-        lw      $t0, 8($sp)
-        li      $t1, 1
-        add     $t0, $t0, $t1
-        sw      $t0, 8($sp)
+        lw      $t0, 16($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 16($sp)
 
 for_0_end:
-        lw      $t0, 8($sp)
+        lw      $t0, 16($sp)
         lw      $t1, 12($sp)
         blt     $t0, $t1, for_0_body
 
 if_0:
-        lw      $t0, 16($sp)
-        bne     $t0, $zero, if_1
+        lw      $t0, 8($sp)
+        beq     $t0, $zero, if_1
 
-        lw      $t0, 20($sp)
+        lw      $t0, 4($sp)
         lw      $t1, 12($sp)
-        sub     $t2, $t0, $t1
-        sw      $t2, 4($sp)
+        sub     $t0, $t0, $t1
+        sw      $t0, 20($sp)
 
         j       for_1_end
 
 for_1_body:
-        la      $a0, str_1
+        la      $t0, str_1
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
-        lw      $t0, 4($sp)
-        li      $t1, 1
-        sub     $t0, $t0, $t1
-        sw      $t0, 4($sp)
+        lw      $t0, 20($sp)
+        subi    $t0, $t0, 1
+        sw      $t0, 20($sp)
 
 for_1_end:
-        lw      $t0, 4($sp)
-        li      $t1, 0
-        bge     $t0, $t1, for_1_body
+        lw      $t0, 20($sp)
+        bge     $t0, $zero, for_1_body
 
-        li      $t0, 0
-        sw      $t0, 4($sp)
+        sw      $zero, 24($sp)
 
         j       for_2_end
 
 for_2_body:
-        la      $a0, str_0
+        la      $t0, str_0
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
-        lw      $t0, 4($sp)
-        li      $t1, 1
-        add     $t0, $t0, $t1
-        sw      $t0, 4($sp)
+        lw      $t0, 24($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 24($sp)
 
 for_2_end:
-        lw      $t0, 4($sp)
+        lw      $t0, 24($sp)
         lw      $t1, 12($sp)
         blt     $t0, $t1, for_2_body
 
 if_1:
-        la      $a0, str_2
+        la      $t0, str_2
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
         lw      $t0, 12($sp)
-        li      $t1, 1
-        add     $t0, $t0, $t1
+        addi    $t0, $t0, 1
         sw      $t0, 12($sp)
 
 for_3_end:
         lw      $t0, 12($sp)
-        lw      $t1, 20($sp)
+        lw      $t1, 4($sp)
         ble     $t0, $t1, for_3_body
 
-        lw      $ra, 24($sp)
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 28
         jr      $ra
 
 main:
-        addi    $sp, $sp, -16
-        sw      $ra, 12($sp)
+        subi    $sp, $sp, 16
+        sw      $ra, 0($sp)
 
-        la      $a0, str_3
+        la      $t0, str_3
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
         li      $v0, 5
-        syscall 
-        sw      $v0, 8($sp)
+        syscall
+        move    $t0, $v0
+        sw      $t0, 4($sp)
 
-        la      $a0, str_4
+        la      $t0, str_4
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
         li      $v0, 5
-        syscall 
-        sw      $v0, 4($sp)
+        syscall
+        move    $t0, $v0
+        sw      $t0, 8($sp)
 
-        li      $t2, 0
-        sw      $t2, 0($sp)
+        sw      $zero, 12($sp)
 
-        lw      $t2, 4($sp)
-        li      $t3, 1
-        beq     $t2, $t3, if_2
+        lw      $t0, 8($sp)
+        li      $t9, 1
+        bne     $t0, $t9, if_2
 
-        li      $t2, 1
-        sw      $t2, 0($sp)
+        li      $t8, 1
+        sw      $t8, 12($sp)
 
 if_2:
-        la      $a0, str_2
+        la      $t0, str_2
+        move    $a0, $t0
         li      $v0, 4
-        syscall 
+        syscall
 
-        lw      $a0, 8($sp)
-        lw      $a1, 0($sp)
+        lw      $t0, 4($sp)
+        lw      $t1, 12($sp)
+        move    $a0, $t0
+        move    $a1, $t1
         jal     stars
 
-        lw      $ra, 12($sp)
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 16
         jr      $ra
 
@@ -0,0 +1,286 @@
+# Assembly generated by ecc (Evil C Compiler)
+
+.data
+	global_banner: .word str_0
+	str_0: .asciiz "\t\"ecc\" strings\\chars\n"
+	str_1: .asciiz "What's your name? "
+	str_2: .asciiz "\nHello, "
+	str_3: .asciiz "That's "
+	str_4: .asciiz " letters, or loudly "
+	str_5: .asciiz "Pick a letter: "
+	str_6: .asciiz "\n"
+	str_7: .asciiz " shows up "
+	str_8: .asciiz " times\n"
+
+.text
+.globl _start
+
+_start:
+        jal     main
+        li      $v0, 10
+        syscall
+
+length:
+        subi    $sp, $sp, 12
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        sw      $zero, 8($sp)
+
+        j       while_0_end
+
+while_0_body:
+        lw      $t0, 8($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 8($sp)
+
+while_0_end:
+        lw      $t0, 4($sp)
+        lw      $t1, 8($sp)
+        add     $t0, $t0, $t1
+        lb      $t0, 0($t0)
+        bne     $t0, $zero, while_0_body
+
+        lw      $t0, 8($sp)
+        move    $v0, $t0
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 12
+        jr      $ra
+
+upper:
+        subi    $sp, $sp, 8
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sb      $t0, 4($sp)
+
+        lb      $t0, 4($sp)
+        li      $t9, 97
+        blt     $t0, $t9, if_0
+
+        lb      $t0, 4($sp)
+        li      $t9, 122
+        bgt     $t0, $t9, if_0
+
+        lb      $t0, 4($sp)
+        subi    $t0, $t0, 32
+        move    $v0, $t0
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 8
+        jr      $ra
+
+if_0:
+        lb      $t0, 4($sp)
+        move    $v0, $t0
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 8
+        jr      $ra
+
+shout:
+        subi    $sp, $sp, 12
+        sw      $ra, 0($sp)
+
+        move    $t0, $a0
+        sw      $t0, 4($sp)
+
+        sw      $zero, 8($sp)
+
+        j       for_0_end
+
+for_0_body:
+        lw      $t0, 4($sp)
+        lw      $t1, 8($sp)
+        add     $t0, $t0, $t1
+        lb      $t0, 0($t0)
+        move    $a0, $t0
+        jal     upper
+        move    $t0, $v0
+        move    $a0, $t0
+        li      $v0, 11
+        syscall
+
+        lw      $t0, 8($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 8($sp)
+
+for_0_end:
+        lw      $t0, 4($sp)
+        lw      $t1, 8($sp)
+        add     $t0, $t0, $t1
+        lb      $t0, 0($t0)
+        bne     $t0, $zero, for_0_body
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 12
+        jr      $ra
+
+main:
+        subi    $sp, $sp, 32
+        sw      $ra, 0($sp)
+        sw      $s0, 4($sp)
+
+        lw      $t0, global_banner
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        li      $t0, 16
+        move    $a0, $t0
+        li      $v0, 9
+        syscall
+        move    $a1, $a0
+        move    $a0, $v0
+        li      $v0, 8
+        syscall
+        move    $t0, $a0
+        sw      $t0, 8($sp)
+
+        la      $t0, str_2
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 8($sp)
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        la      $t0, str_3
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 8($sp)
+        move    $a0, $t0
+        jal     length
+        move    $t0, $v0
+        subi    $t0, $t0, 1
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_4
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 8($sp)
+        move    $a0, $t0
+        jal     shout
+
+        la      $t0, str_5
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        li      $v0, 12
+        syscall
+        move    $t0, $v0
+        sb      $t0, 12($sp)
+
+        la      $t0, str_6
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        sw      $zero, 16($sp)
+
+        sw      $zero, 20($sp)
+
+        j       for_1_end
+
+for_1_body:
+        lw      $t0, 8($sp)
+        lw      $t1, 20($sp)
+        add     $t0, $t0, $t1
+        lb      $t0, 0($t0)
+        lb      $t1, 12($sp)
+        beq     $t0, $t1, if_1_then
+
+        lw      $t0, 8($sp)
+        lw      $t1, 20($sp)
+        add     $t0, $t0, $t1
+        lb      $s0, 0($t0)
+        lb      $t0, 12($sp)
+        move    $a0, $t0
+        jal     upper
+        move    $t0, $v0
+        bne     $s0, $t0, if_1
+
+if_1_then:
+        lw      $t0, 16($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 16($sp)
+
+if_1:
+        lw      $t0, 20($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 20($sp)
+
+for_1_end:
+        lw      $t0, 8($sp)
+        lw      $t1, 20($sp)
+        add     $t0, $t0, $t1
+        lb      $t0, 0($t0)
+        bne     $t0, $zero, for_1_body
+
+        li      $t0, 39
+        move    $a0, $t0
+        li      $v0, 11
+        syscall
+
+        lb      $t0, 12($sp)
+        move    $a0, $t0
+        li      $v0, 11
+        syscall
+
+        li      $t0, 39
+        move    $a0, $t0
+        li      $v0, 11
+        syscall
+
+        la      $t0, str_7
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $t0, 16($sp)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_8
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        li      $t8, 9
+        sb      $t8, 24($sp)
+
+        li      $t8, 10
+        sb      $t8, 28($sp)
+
+        lb      $t0, 24($sp)
+        lb      $t1, 28($sp)
+        add     $t0, $t0, $t1
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        lb      $t0, 28($sp)
+        move    $a0, $t0
+        li      $v0, 11
+        syscall
+
+        lw      $ra, 0($sp)
+        lw      $s0, 4($sp)
+        addi    $sp, $sp, 32
+        jr      $ra
+
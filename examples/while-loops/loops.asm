@@ -6,40 +6,45 @@
 _start:
         jal     main
         li      $v0, 10
-        syscall 
+        syscall
+
 main:
-        addi    $sp, $sp, -12
-        sw      $ra, 8($sp)
+        subi    $sp, $sp, 12
+        sw      $ra, 0($sp)
 
-        li      $t0, 10
-        sw      $t0, 4($sp)
+        sw      $zero, 4($sp)
+
+        li      $t8, 10
+        sw      $t8, 4($sp)
+
+        sw      $zero, 8($sp)
+
+        j       while_1_end
 
 while_1_body:
-        li      $t0, 0
-        sw      $t0, 0($sp)
+        sw      $zero, 8($sp)
+
+        j       while_0_end
 
 while_0_body:
-        lw      $t0, 0($sp)
-        li      $t1, 1
-        add     $t0, $t0, $t1
-        sw      $t0, 0($sp)
+        lw      $t0, 8($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 8($sp)
 
 while_0_end:
-        lw      $t0, 0($sp)
+        lw      $t0, 8($sp)
         lw      $t1, 4($sp)
         blt     $t0, $t1, while_0_body
 
         lw      $t0, 4($sp)
-        li      $t1, 1
-        sub     $t0, $t0, $t1
+        subi    $t0, $t0, 1
         sw      $t0, 4($sp)
 
 while_1_end:
         lw      $t0, 4($sp)
-        li      $t1, 0
-        bgt     $t0, $t1, while_1_body
+        bgt     $t0, $zero, while_1_body
 
-        lw      $ra, 8($sp)
+        lw      $ra, 0($sp)
         addi    $sp, $sp, 12
+        jr      $ra
 
-        jr      $ra
\ No newline at end of file
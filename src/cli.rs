@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 /** Very basic mips compiler for a custom, c-like language
  Compiled files are stored in the same directory as the binary as <file.asm>
@@ -10,12 +10,17 @@ use clap::Parser;
     "
 Very basic mips compiler for a custom, c-like language
 Compiled files are stored in the same directory as the binary as <file.asm>",
-    long_about = None
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Input file, must end in .ec extention
-    #[arg(value_name = "file.ec")]
-    pub input_file: String,
+    #[arg(value_name = "file.ec", required = true)]
+    pub input_file: Option<String>,
 
     /// Include comments in the generated assembly
     #[arg(short, long)]
@@ -29,7 +34,70 @@ pub struct Args {
     #[arg(short, long)]
     pub tokens: bool,
 
+    /// Print the intermediate representation to stdout
+    #[arg(long)]
+    pub ir: bool,
+
     /// Name of the output file
     #[arg(short, default_value = "out.asm")]
     pub output: String,
+
+    /// Check array indexes at runtime, a bad one prints its line and exits with 1
+    #[arg(long)]
+    pub bounds_check: bool,
+
+    /// Stop printing errors after this many
+    #[arg(long, default_value_t = 10)]
+    pub max_errors: usize,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Compile a file and run it in the built-in simulator instead of MARS
+    Run(RunArgs),
+
+    /// Run a file by walking its AST, no assembly involved
+    Interp(InterpArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct RunArgs {
+    /// Input file, must end in .ec extention
+    #[arg(value_name = "file.ec")]
+    pub input_file: String,
+
+    /// Check array indexes at runtime, a bad one prints its line and exits with 1
+    #[arg(long)]
+    pub bounds_check: bool,
+
+    /// Seed for irandrange, taken from the clock if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Give up after running this many instructions
+    #[arg(long)]
+    pub max_steps: Option<u64>,
+
+    /// Stop printing errors after this many
+    #[arg(long, default_value_t = 10)]
+    pub max_errors: usize,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct InterpArgs {
+    /// Input file, must end in .ec extention
+    #[arg(value_name = "file.ec")]
+    pub input_file: String,
+
+    /// Seed for irandrange, taken from the clock if not given
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Give up after running this many statements
+    #[arg(long)]
+    pub max_steps: Option<u64>,
+
+    /// Stop printing errors after this many
+    #[arg(long, default_value_t = 10)]
+    pub max_errors: usize,
 }
@@ -1,54 +1,79 @@
 use crate::error;
 use crate::error::CompileError;
+use crate::ir;
 use crate::lexer;
 use crate::mips;
 use crate::parser;
+use crate::semantic;
 
 pub struct Compiler {
     source: String,
+
+    // Only used to label spans in error messages
+    file_name: String,
 }
 
 impl Compiler {
-    pub fn new(source: &str) -> Self {
+    pub fn new(source: &str, file_name: &str) -> Self {
         Compiler {
             source: source.to_string(),
+            file_name: file_name.to_string(),
         }
     }
 
-    pub fn compile(&mut self, output_file: &str, emit_comments: bool) -> Result<(), error::CompileError> {
-        let mut l = lexer::Lexer::new(&self.source);
-        let tokens = l.tokenize()?;
-        let mut p = parser::Parser::new(tokens);
-        let program = p.parse()?;
-
-        let mut mips_gen = mips::MipsGenerator::new(program, emit_comments);
-        let mips_code = mips_gen.generate()?;
+    // The parser keeps going after an error, so this hands back every error it found
+    pub fn compile(&mut self, output_file: &str, emit_comments: bool, bounds_check: bool) -> Result<(), Vec<error::CompileError>> {
+        let mips_code = self.get_assembly(emit_comments, bounds_check)?;
 
-        let res = std::fs::write(output_file, mips_code.to_string());
+        let res = std::fs::write(output_file, mips_code);
 
         match res {
             Ok(_) => {
                 Ok(())
             }
             Err(e) => {
-                return Err(CompileError::GenericError {
+                Err(vec![CompileError::GenericError {
                     message: format!("Failed to write to output file: {}", e),
-                });
+                }])
             }
         }
     }
 
-    pub fn get_ast(&mut self) -> Result<parser::ast::Program, error::CompileError> {
-        let mut l = lexer::Lexer::new(&self.source);
-        let tokens = l.tokenize()?;
+    pub fn get_assembly(&mut self, emit_comments: bool, bounds_check: bool) -> Result<String, Vec<error::CompileError>> {
+        let module = self.get_ir(bounds_check)?;
+
+        let mut mips_gen = mips::MipsGenerator::new(&module, emit_comments);
+        let mips_code = mips_gen.generate();
+
+        Ok(mips_code.clone())
+    }
+
+    // The checked AST lowered to three-address code, what the backend picks instructions for
+    pub fn get_ir(&mut self, bounds_check: bool) -> Result<ir::Module, Vec<error::CompileError>> {
+        let program = self.get_checked_ast()?;
+
+        ir::lower(&program, bounds_check).map_err(|e| vec![e])
+    }
+
+    // The AST once the semantic pass is happy with it, what the interpreter runs
+    pub fn get_checked_ast(&mut self) -> Result<parser::ast::Program, Vec<error::CompileError>> {
+        let program = self.get_ast()?;
+
+        semantic::Analyzer::new().analyze(&program)?;
+
+        Ok(program)
+    }
+
+    pub fn get_ast(&mut self) -> Result<parser::ast::Program, Vec<error::CompileError>> {
+        let tokens = self.get_tokens().map_err(|e| vec![e])?;
         let mut p = parser::Parser::new(tokens);
         let program = p.parse()?;
 
         Ok(program)
     }
 
-    pub fn get_tokens(&mut self) -> Result<Vec<lexer::Token>, error::CompileError> {
-        let mut l = lexer::Lexer::new(&self.source);
+    pub fn get_tokens(&mut self) -> Result<Vec<lexer::SpannedToken>, error::CompileError> {
+        let mut l = lexer::Lexer::new(&self.source, &self.file_name);
         let tokens = l.tokenize()?;
 
         Ok(tokens)
@@ -0,0 +1,259 @@
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::io::IsTerminal;
+
+use crate::error::CompileError;
+use crate::lexer::Span;
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+// Tabs are echoed as this many spaces, like rustc, so the caret
+// line can be lined up under them without guessing the tab stops
+const TAB_WIDTH: usize = 4;
+
+// Renders errors the way rustc does, with the offending line and a caret
+// under the span. Anything without a span (missing main, panics) is just
+// the header plus notes
+#[derive(Debug)]
+pub struct Diagnostic {
+    code: Option<&'static str>,
+    message: String,
+    span: Option<Span>,
+    notes: Vec<String>,
+    help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: &str) -> Self {
+        Diagnostic {
+            code: None,
+            message: message.to_string(),
+            span: None,
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn with_code(mut self, code: &'static str) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    pub fn with_span(mut self, span: &Span) -> Self {
+        self.span = Some(span.clone());
+        self
+    }
+
+    pub fn with_note(mut self, note: &str) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help.push(help.to_string());
+        self
+    }
+
+    pub fn render(&self, source: &str, color: bool) -> String {
+        let paint = |style: &str, text: &str| {
+            if color {
+                format!("{}{}{}", style, text, RESET)
+            } else {
+                text.to_string()
+            }
+        };
+
+        let mut out = String::new();
+
+        let header = match self.code {
+            Some(code) => format!("error[{}]", code),
+            None => "error".to_string(),
+        };
+
+        let _ = writeln!(
+            out,
+            "{}{}",
+            paint(RED, &header),
+            paint(BOLD, &format!(": {}", self.message))
+        );
+
+        // Made up statements have a default span with no file, nothing to show
+        let span = self.span.as_ref().filter(|s| !s.file.is_empty());
+
+        // Notes and help line up with the gutter, which is as wide as the line number
+        let gutter = match span {
+            Some(span) => span.line.to_string().len(),
+            None => 0,
+        };
+        let pad = " ".repeat(gutter);
+
+        if let Some(span) = span {
+            let _ = writeln!(out, "{}{} {}", pad, paint(BLUE, "-->"), span);
+
+            if let Some(text) = source.lines().nth(span.line - 1) {
+                let bar = paint(BLUE, "|");
+
+                let _ = writeln!(out, "{} {}", pad, bar);
+                let _ = writeln!(
+                    out,
+                    "{} {} {}",
+                    paint(BLUE, &span.line.to_string()),
+                    bar,
+                    text.replace('\t', &" ".repeat(TAB_WIDTH))
+                );
+
+                let before: String = text.chars().take(span.column - 1).collect();
+                let indent = " ".repeat(Self::display_width(&before));
+                let carets = "^".repeat(Self::underline_width(source, span, text));
+
+                let _ = writeln!(out, "{} {} {}{}", pad, bar, indent, paint(RED, &carets));
+            }
+        }
+
+        for note in &self.notes {
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                pad,
+                paint(BLUE, "="),
+                paint(BOLD, &format!("note: {}", note))
+            );
+        }
+
+        for help in &self.help {
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                pad,
+                paint(BLUE, "="),
+                paint(BOLD, &format!("help: {}", help))
+            );
+        }
+
+        out
+    }
+
+    // Prints to stderr, coloured only when a person is looking at it
+    pub fn emit(&self, source: &str) {
+        let color = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        eprintln!("{}", self.render(source, color));
+    }
+
+    // Spans that run onto later lines are cut off at the end of the first one
+    fn underline_width(source: &str, span: &Span, line_text: &str) -> usize {
+        let covered = source.get(span.start..span.end).unwrap_or("");
+        let first_line = covered.lines().next().unwrap_or("");
+
+        let remaining: String = line_text.chars().skip(span.column - 1).collect();
+
+        Self::display_width(first_line)
+            .min(Self::display_width(&remaining))
+            .max(1)
+    }
+
+    // Columns text takes up once its tabs are expanded
+    fn display_width(text: &str) -> usize {
+        text.chars()
+            .map(|c| if c == '\t' { TAB_WIDTH } else { 1 })
+            .sum()
+    }
+}
+
+impl From<&CompileError> for Diagnostic {
+    fn from(error: &CompileError) -> Self {
+        match error {
+            CompileError::LexError { message, span } => Diagnostic::error(message)
+                .with_code("E0001")
+                .with_span(span),
+
+            CompileError::ParseError { message, span } => Diagnostic::error(message)
+                .with_code("E0101")
+                .with_span(span),
+
+            CompileError::TypeError { message, span } => Diagnostic::error(message)
+                .with_code("E0201")
+                .with_span(span),
+
+            CompileError::SemanticError { message, span } => Diagnostic::error(message)
+                .with_code("E0202")
+                .with_span(span),
+
+            CompileError::CodeGenError { message, span } => Diagnostic::error(message)
+                .with_code("E0301")
+                .with_span(span),
+
+            CompileError::GenericError { message } => Diagnostic::error(message),
+        }
+    }
+}
+
+// Prints up to limit errors, then a summary line like rustc's
+pub fn emit_all(errors: &[CompileError], source: &str, limit: usize) {
+    for error in errors.iter().take(limit) {
+        Diagnostic::from(error).emit(source);
+    }
+
+    if errors.len() > limit {
+        Diagnostic::error(&format!("{} more errors not shown", errors.len() - limit))
+            .with_help("use --max-errors to see more")
+            .emit(source);
+    }
+
+    if errors.len() > 1 {
+        Diagnostic::error(&format!("aborting due to {} previous errors", errors.len()))
+            .emit(source);
+    }
+}
+
+thread_local! {
+    // What the compiler was working on, so a panic can still point at the source
+    static CURRENT_SPAN: RefCell<Option<Span>> = const { RefCell::new(None) };
+}
+
+pub fn set_current_span(span: &Span) {
+    CURRENT_SPAN.with(|current| *current.borrow_mut() = Some(span.clone()));
+}
+
+// Anything that still panics is a compiler bug, this turns those into a
+// diagnostic instead of a Rust backtrace
+pub fn install_panic_hook(source: &str) {
+    let source = source.to_string();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+
+        let message = if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+
+        let mut diagnostic = Diagnostic::error(&format!("internal compiler error: {}", message))
+            .with_note("the compiler unexpectedly panicked, this is a bug");
+
+        if let Some(span) = CURRENT_SPAN.with(|current| current.borrow().clone()) {
+            diagnostic = diagnostic.with_span(&span);
+        }
+
+        if let Some(location) = info.location() {
+            diagnostic = diagnostic.with_note(&format!("panicked at {}", location));
+        }
+
+        // Replacing the hook also drops the default backtrace output
+        let backtrace = std::backtrace::Backtrace::capture();
+
+        if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+            diagnostic.emit(&source);
+            eprintln!("{}", backtrace);
+        } else {
+            diagnostic
+                .with_help("run with RUST_BACKTRACE=1 to see where it happened")
+                .emit(&source);
+        }
+    }));
+}
@@ -1,33 +1,44 @@
 use std::fmt;
 
+use crate::lexer::Span;
+
 pub type Result<T> = std::result::Result<T, CompileError>;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum CompileError {
-    LexError { message: String, line: usize },
-    ParseError { message: String, line: usize },
-    CodeGenError { message: String, line: usize },
-    TypeError { message: String, line: usize },
+    LexError { message: String, span: Span },
+    ParseError { message: String, span: Span },
+    CodeGenError { message: String, span: Span },
+    TypeError { message: String, span: Span },
+
+    // Undeclared and duplicate names
+    SemanticError { message: String, span: Span },
+
     GenericError { message: String},
 }
 
 impl fmt::Display for CompileError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            CompileError::LexError { message, line } => {
-                write!(f, "Lexical error at line {}: {}", line, message)
+            CompileError::LexError { message, span } => {
+                write!(f, "Lexical error at {}: {}", span, message)
+            }
+
+            CompileError::ParseError { message, span } => {
+                write!(f, "Parse error at {}: {}", span, message)
             }
 
-            CompileError::ParseError { message, line } => {
-                write!(f, "Parse error at line {}: {}", line, message)
+            CompileError::TypeError { message, span } => {
+                write!(f, "Type error at {}: {}", span, message)
             }
 
-            CompileError::TypeError { message, line } => {
-                write!(f, "Type error at line {}: {}", line, message)
+            CompileError::SemanticError { message, span } => {
+                write!(f, "Semantic error at {}: {}", span, message)
             }
 
-            CompileError::CodeGenError { message, line } => {
-                write!(f, "Code generation error at line {}: {}", line, message)
+            CompileError::CodeGenError { message, span } => {
+                write!(f, "Code generation error at {}: {}", span, message)
             }
 
             CompileError::GenericError { message } => {
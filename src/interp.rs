@@ -0,0 +1,969 @@
+mod data;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{BufRead, Write};
+
+use crate::ir::{StructLayout, reassigned_names, size_of};
+use crate::lexer::Span;
+use crate::parser::ast::{
+    BinaryOperator, BuiltinFunctionType, DataStorageType, DataValue, Expr, ExprKind, Parameter,
+    Program, Statement, StatementKind, Type, UnaryOperator, global_label,
+};
+use crate::rng::Rng;
+use crate::sim::console;
+use crate::sim::memory::{Memory, STACK_TOP};
+
+// Something that went wrong running the program, span is whatever was
+// being run at the time
+#[derive(Debug)]
+pub struct RuntimeError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Made up statements have a default span, there's nowhere to point
+        if self.span.file.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{} at {}", self.message, self.span)
+        }
+    }
+}
+
+impl std::error::Error for RuntimeError {}
+
+type Result<T> = std::result::Result<T, RuntimeError>;
+
+// Calls nested deeper than this are a stack overflow. Each one takes a few
+// kilobytes of the interpreter's own stack (a lot more in a debug build),
+// so whatever runs it should do so on a thread with THREAD_STACK_SIZE
+pub const MAX_CALL_DEPTH: usize = 10_000;
+pub const THREAD_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+// A value the way it would sit in a register. Structs and arrays never
+// fit in one, so theirs is their address
+#[derive(Debug, Clone)]
+struct Value {
+    bits: u32,
+    typ: Type,
+}
+
+impl Value {
+    fn new(bits: u32, typ: Type) -> Self {
+        Value { bits, typ }
+    }
+
+    fn int(n: i32) -> Self {
+        Value::new(n as u32, Type::Int32)
+    }
+
+    fn bool(b: bool) -> Self {
+        Value::new(b as u32, Type::Bool)
+    }
+}
+
+struct Variable {
+    address: u32,
+    typ: Type,
+
+    // Elements in the {...} it was declared with, as long as nothing can
+    // point it somewhere else. The same ones --bounds-check knows about
+    length: Option<usize>,
+}
+
+#[derive(Clone, Copy)]
+struct Function<'a> {
+    params: &'a [Parameter],
+    return_type: &'a Type,
+    body: &'a [Statement],
+}
+
+// Whether a statement finished normally or returned out of its function
+enum Flow {
+    Next,
+    Return(u32),
+}
+
+// Runs a program that passed the semantic pass straight off the AST. Memory
+// is laid out the way it is under MARS, globals and strings in the data
+// segment, locals on a stack growing down from the same top and alloc on
+// the same heap, so pointers, chars and structs behave like they do once
+// compiled. That makes it a reference to check the backend against
+pub struct Interpreter<'a> {
+    functions: HashMap<&'a str, Function<'a>>,
+    globals: HashMap<&'a str, Variable>,
+    structs: HashMap<String, StructLayout>,
+
+    // Names that lose their {...} length, see ir::reassigned_names
+    reassigned: HashSet<String>,
+
+    // Identical literals share a label, so they share an address too
+    strings: HashMap<&'a str, u32>,
+
+    memory: Memory,
+
+    // A list of scopes for each call in progress, innermost last
+    frames: Vec<Vec<HashMap<&'a str, Variable>>>,
+
+    // Locals are handed out downwards from here and given back when
+    // their scope ends
+    sp: u32,
+
+    rng: Rng,
+    steps: u64,
+    max_steps: Option<u64>,
+    input: &'a mut dyn BufRead,
+    output: &'a mut dyn Write,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(
+        program: &'a Program,
+        rng: Rng,
+        input: &'a mut dyn BufRead,
+        output: &'a mut dyn Write,
+    ) -> Self {
+        let data = data::lay_out(&program.segments.data.body);
+        let mut strings = HashMap::new();
+
+        for stmt in &program.segments.data.body {
+            if let StatementKind::DataDeclaration {
+                label: Some(label),
+                storage_type: DataStorageType::Asciiz,
+                values,
+            } = &stmt.kind
+                && label.starts_with("str_")
+                && let [DataValue::String(text)] = values.as_slice()
+            {
+                strings.insert(text.as_str(), data.labels[label]);
+            }
+        }
+
+        let mut functions = HashMap::new();
+        let mut globals = HashMap::new();
+
+        let reassigned = reassigned_names(&program.segments.text.body);
+
+        for stmt in &program.segments.text.body {
+            match &stmt.kind {
+                StatementKind::Function {
+                    name,
+                    params,
+                    return_type,
+                    body,
+                    ..
+                } => {
+                    functions.insert(
+                        name.as_str(),
+                        Function {
+                            params,
+                            return_type,
+                            body,
+                        },
+                    );
+                }
+
+                StatementKind::VariableDeclaration {
+                    var_type,
+                    identifier,
+                    operation,
+                } => {
+                    let length = match &operation.kind {
+                        ExprKind::ArrayInitializer { size, .. }
+                            if !reassigned.contains(identifier) =>
+                        {
+                            Some(*size)
+                        }
+                        _ => None,
+                    };
+
+                    globals.insert(
+                        identifier.as_str(),
+                        Variable {
+                            address: data.labels[&global_label(identifier)],
+                            typ: var_type.clone(),
+                            length,
+                        },
+                    );
+                }
+
+                _ => {}
+            }
+        }
+
+        Interpreter {
+            functions,
+            globals,
+            structs: StructLayout::from_statements(&program.segments.text.body),
+            reassigned,
+            strings,
+            memory: Memory::new(&data.bytes),
+            frames: Vec::new(),
+            sp: STACK_TOP,
+            rng,
+            steps: 0,
+            max_steps: None,
+            input,
+            output,
+        }
+    }
+
+    // Calls main and hands back the exit code, which like _start is 0
+    // whatever main returns. With max_steps set an infinite loop is an
+    // error instead of a hang
+    pub fn run(&mut self, max_steps: Option<u64>) -> Result<i32> {
+        self.max_steps = max_steps;
+
+        let span = Span::default();
+        let result = self.call_function("main", Vec::new(), &span).map(|_| 0);
+
+        // Whatever got printed before an error is still worth seeing
+        self.output
+            .flush()
+            .map_err(|e| error(format!("Failed to write output: {}", e), &span))?;
+
+        result
+    }
+
+    fn exec_block(&mut self, body: &'a [Statement]) -> Result<Flow> {
+        self.push_scope();
+        let sp = self.sp;
+
+        let flow = self.exec_all(body);
+
+        self.pop_scope();
+        self.sp = sp;
+        flow
+    }
+
+    fn exec_all(&mut self, body: &'a [Statement]) -> Result<Flow> {
+        for stmt in body {
+            if let Flow::Return(bits) = self.exec(stmt)? {
+                return Ok(Flow::Return(bits));
+            }
+        }
+
+        Ok(Flow::Next)
+    }
+
+    fn exec(&mut self, stmt: &'a Statement) -> Result<Flow> {
+        let span = &stmt.span;
+        self.tick(span)?;
+
+        match &stmt.kind {
+            StatementKind::VariableDeclaration {
+                var_type,
+                identifier,
+                operation,
+            } => self.declare(var_type, identifier, operation, span)?,
+
+            // Like the generated code the value comes first, then where it goes
+            StatementKind::VariableAssignment {
+                identifier,
+                operation,
+                is_dereference,
+                is_array_index,
+                indexer,
+            } => {
+                let value = self.eval(operation)?;
+
+                let (address, typ) = if *is_dereference {
+                    let (address, typ) = self.variable(identifier, span)?;
+                    let pointer = self.load(address, &typ, span)?;
+                    (pointer.bits, pointee(&typ))
+                } else if *is_array_index {
+                    let (address, typ) = self.variable(identifier, span)?;
+                    let array = self.load(address, &typ, span)?;
+                    self.element(array, indexer, self.length(identifier))?
+                } else {
+                    self.variable(identifier, span)?
+                };
+
+                self.store(address, &typ, value.bits, span)?;
+            }
+
+            StatementKind::IndirectAssignment { target, operation } => {
+                let value = self.eval(operation)?;
+                let (address, typ) = self.place(target)?;
+                self.store(address, &typ, value.bits, span)?;
+            }
+
+            StatementKind::FunctionCall {
+                function_name,
+                arguments,
+                builtin_function_type,
+                ..
+            } => {
+                self.call(
+                    function_name,
+                    arguments,
+                    builtin_function_type.as_ref(),
+                    span,
+                )?;
+            }
+
+            StatementKind::ExprStatement(expr) => {
+                self.eval(expr)?;
+            }
+
+            StatementKind::While {
+                condition, body, ..
+            } => {
+                while self.condition(condition)? {
+                    if let Flow::Return(bits) = self.exec_block(body)? {
+                        return Ok(Flow::Return(bits));
+                    }
+                }
+            }
+
+            // init gets a scope of its own that lasts the whole loop, the
+            // body gets a fresh one every time round
+            StatementKind::For {
+                init,
+                condition,
+                var_change,
+                body,
+                ..
+            } => {
+                self.push_scope();
+                let sp = self.sp;
+
+                let flow = self.exec_for(init, condition, var_change, body);
+
+                self.pop_scope();
+                self.sp = sp;
+                return flow;
+            }
+
+            StatementKind::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                if self.condition(condition)? {
+                    return self.exec_block(body);
+                }
+
+                if let Some(else_body) = else_body {
+                    return self.exec_block(else_body);
+                }
+            }
+
+            StatementKind::Return { value } => {
+                let value = self.eval(value)?;
+                return Ok(Flow::Return(value.bits));
+            }
+
+            // Only ever at the top level, where new already dealt with them
+            StatementKind::DataDeclaration { .. }
+            | StatementKind::Instruction { .. }
+            | StatementKind::Function { .. }
+            | StatementKind::Struct { .. } => {}
+        }
+
+        Ok(Flow::Next)
+    }
+
+    fn exec_for(
+        &mut self,
+        init: &'a Statement,
+        condition: &'a Expr,
+        var_change: &'a Statement,
+        body: &'a [Statement],
+    ) -> Result<Flow> {
+        self.exec(init)?;
+
+        while self.condition(condition)? {
+            if let Flow::Return(bits) = self.exec_block(body)? {
+                return Ok(Flow::Return(bits));
+            }
+
+            self.exec(var_change)?;
+        }
+
+        Ok(Flow::Next)
+    }
+
+    // Counts as a step too so an empty loop body still runs into max_steps
+    fn condition(&mut self, condition: &'a Expr) -> Result<bool> {
+        self.tick(&condition.span)?;
+        Ok(self.eval(condition)?.bits != 0)
+    }
+
+    fn declare(
+        &mut self,
+        var_type: &Type,
+        identifier: &'a str,
+        operation: &'a Expr,
+        span: &Span,
+    ) -> Result<()> {
+        // Worked out before the variable exists, so an outer variable with
+        // the same name is still the one being read. The pointer comes first
+        // and the elements right after it, same as the generated code
+        if let ExprKind::ArrayInitializer { body, size } = &operation.kind {
+            let mut elements = Vec::new();
+
+            for element in body.iter().take(*size) {
+                elements.push(self.eval(element)?.bits);
+            }
+
+            let address = self.allocate(4 + 4 * size, span)?;
+            self.store(address, var_type, address + 4, span)?;
+
+            for (i, bits) in elements.into_iter().enumerate() {
+                self.store(address + 4 + 4 * i as u32, &Type::Int32, bits, span)?;
+            }
+
+            let length = (!self.reassigned.contains(identifier)).then_some(*size);
+
+            self.bind(identifier, address, var_type, length);
+            return Ok(());
+        }
+
+        // Empty gives 0, so a plain declaration is zeroed like it is compiled
+        let value = self.eval(operation)?;
+        let address = self.allocate(self.size_of(var_type), span)?;
+
+        // Structs and arrays can't be copied, allocate already cleared them
+        if !matches!(var_type, Type::Struct(_) | Type::Array(..)) {
+            self.store(address, var_type, value.bits, span)?;
+        }
+
+        self.bind(identifier, address, var_type, None);
+        Ok(())
+    }
+
+    fn eval(&mut self, expr: &'a Expr) -> Result<Value> {
+        let span = &expr.span;
+
+        let value = match &expr.kind {
+            ExprKind::Integer(n) => Value::int(*n),
+            ExprKind::BoolLiteral(b) => Value::bool(*b),
+            ExprKind::CharLiteral(c) => Value::new(*c as u32, Type::Char),
+            ExprKind::StringLiteral(s) => Value::new(self.strings[s.as_str()], Type::String),
+            ExprKind::Empty => Value::new(0, Type::Void),
+
+            ExprKind::Identifier(name) => {
+                let (address, typ) = self.variable(name, span)?;
+                self.load(address, &typ, span)?
+            }
+
+            ExprKind::IdentifierReference(name) => {
+                let (address, typ) = self.variable(name, span)?;
+                Value::new(address, Type::pointer_to(typ))
+            }
+
+            ExprKind::IdentifierDereference(_)
+            | ExprKind::ArrayIndex { .. }
+            | ExprKind::FieldAccess { .. } => {
+                let (address, typ) = self.place(expr)?;
+                self.load(address, &typ, span)?
+            }
+
+            ExprKind::FunctionCall {
+                function_name,
+                arguments,
+                builtin_function_type,
+                ..
+            } => self.call(
+                function_name,
+                arguments,
+                builtin_function_type.as_ref(),
+                span,
+            )?,
+
+            ExprKind::UnaryOp { operator, operand } => {
+                let n = self.eval(operand)?.bits as i32;
+
+                match operator {
+                    UnaryOperator::Negate => {
+                        Value::int(0i32.checked_sub(n).ok_or_else(|| overflow(0, n, span))?)
+                    }
+                    UnaryOperator::Not => Value::bool(n == 0),
+                    UnaryOperator::BitwiseNot => Value::int(!n),
+                }
+            }
+
+            // The right side only runs when the left didn't already decide it
+            ExprKind::BinaryOp {
+                left,
+                operator: BinaryOperator::LogicalAnd,
+                right,
+            } => Value::bool(self.eval(left)?.bits != 0 && self.eval(right)?.bits != 0),
+
+            ExprKind::BinaryOp {
+                left,
+                operator: BinaryOperator::LogicalOr,
+                right,
+            } => Value::bool(self.eval(left)?.bits != 0 || self.eval(right)?.bits != 0),
+
+            ExprKind::BinaryOp {
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.eval(left)?;
+                let right = self.eval(right)?;
+                self.binary(left, operator, right, span)?
+            }
+
+            ExprKind::ArrayInitializer { .. } => {
+                return Err(error(
+                    "An array initialiser only works in a declaration".to_string(),
+                    span,
+                ));
+            }
+        };
+
+        Ok(value)
+    }
+
+    // Same rules as the generated code, add, sub and mul trap on overflow
+    // like MARS does and pointers move a whole element at a time
+    fn binary(
+        &self,
+        left: Value,
+        operator: &BinaryOperator,
+        right: Value,
+        span: &Span,
+    ) -> Result<Value> {
+        let (x, y) = (left.bits as i32, right.bits as i32);
+
+        let value = match operator {
+            BinaryOperator::Add | BinaryOperator::Subtract => {
+                let is_add = *operator == BinaryOperator::Add;
+
+                let (x, y, typ) = match (&left.typ, &right.typ) {
+                    (Type::Pointer(pointee), _) => {
+                        (x, y.wrapping_mul(self.size_of(pointee) as i32), left.typ)
+                    }
+
+                    (_, Type::Pointer(pointee)) if is_add => {
+                        (x.wrapping_mul(self.size_of(pointee) as i32), y, right.typ)
+                    }
+
+                    _ => (x, y, Type::Int32),
+                };
+
+                let result = if is_add {
+                    x.checked_add(y)
+                } else {
+                    x.checked_sub(y)
+                };
+
+                Value::new(result.ok_or_else(|| overflow(x, y, span))? as u32, typ)
+            }
+
+            BinaryOperator::Multiply => {
+                Value::int(x.checked_mul(y).ok_or_else(|| overflow(x, y, span))?)
+            }
+
+            BinaryOperator::Divide | BinaryOperator::Modulo if y == 0 => {
+                return Err(error("Division by zero".to_string(), span));
+            }
+
+            // INT_MIN / -1 wraps, MIPS div doesn't trap
+            BinaryOperator::Divide => Value::int(x.wrapping_div(y)),
+            BinaryOperator::Modulo => Value::int(x.wrapping_rem(y)),
+
+            BinaryOperator::LessThan => Value::bool(x < y),
+            BinaryOperator::GreaterThan => Value::bool(x > y),
+            BinaryOperator::LessEqual => Value::bool(x <= y),
+            BinaryOperator::GreaterEqual => Value::bool(x >= y),
+            BinaryOperator::Equal => Value::bool(x == y),
+            BinaryOperator::NotEqual => Value::bool(x != y),
+            BinaryOperator::BitwiseAnd => Value::int(x & y),
+            BinaryOperator::BitwiseOr => Value::int(x | y),
+            BinaryOperator::BitwiseXor => Value::int(x ^ y),
+
+            // Only the low five bits of the shift amount count
+            BinaryOperator::ShiftLeft => Value::int(x << (y & 31)),
+            BinaryOperator::ShiftRight => Value::int(x >> (y & 31)),
+
+            BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr => unreachable!(),
+        };
+
+        Ok(value)
+    }
+
+    // Where an assignable expression lives and what's there
+    fn place(&mut self, expr: &'a Expr) -> Result<(u32, Type)> {
+        let span = &expr.span;
+
+        match &expr.kind {
+            ExprKind::Identifier(name) => self.variable(name, span),
+
+            ExprKind::IdentifierDereference(name) => {
+                let (address, typ) = self.variable(name, span)?;
+                let pointer = self.load(address, &typ, span)?;
+                Ok((pointer.bits, pointee(&typ)))
+            }
+
+            ExprKind::ArrayIndex { array, indexer } => {
+                let length = match &array.kind {
+                    ExprKind::Identifier(name) => self.length(name),
+                    _ => None,
+                };
+
+                let array = self.eval(array)?;
+                self.element(array, indexer, length)
+            }
+
+            // A struct's value is already its address, a pointer's is what it points to
+            ExprKind::FieldAccess {
+                base,
+                field,
+                through_pointer,
+            } => {
+                let base = self.eval(base)?;
+
+                let name = match (&base.typ, through_pointer) {
+                    (Type::Struct(name), false) => name,
+                    (Type::Pointer(pointee), true) => match pointee.as_ref() {
+                        Type::Struct(name) => name,
+                        _ => return Err(error(format!("{} isn't a struct", pointee), span)),
+                    },
+                    (typ, _) => return Err(error(format!("{} isn't a struct", typ), span)),
+                };
+
+                let (offset, typ) = self
+                    .structs
+                    .get(name)
+                    .and_then(|layout| layout.fields.get(field))
+                    .ok_or_else(|| error(format!("{} has no field '{}'", name, field), span))?;
+
+                Ok((base.bits.wrapping_add(*offset as u32), typ.clone()))
+            }
+
+            _ => Err(error("This can't be assigned to".to_string(), span)),
+        }
+    }
+
+    // The address of array[indexer]. Indexes into a sized array, or a pointer
+    // still holding its {...} of length elements, are always checked where
+    // the compiled code would quietly read whatever is next door
+    fn element(
+        &mut self,
+        array: Value,
+        indexer: &'a Expr,
+        length: Option<usize>,
+    ) -> Result<(u32, Type)> {
+        let span = &indexer.span;
+        let index = self.eval(indexer)?.bits as i32;
+
+        let element = match &array.typ {
+            Type::String => Type::Char,
+
+            Type::Pointer(element) => {
+                if let Some(length) = length
+                    && (index < 0 || index as usize >= length)
+                {
+                    return Err(error(
+                        format!(
+                            "Index {} is out of bounds for an array of {}",
+                            index, length
+                        ),
+                        span,
+                    ));
+                }
+
+                element.as_ref().clone()
+            }
+
+            Type::Array(element, length) => {
+                if index < 0 || index as usize >= *length {
+                    return Err(error(
+                        format!("Index {} is out of bounds for {}", index, array.typ),
+                        span,
+                    ));
+                }
+
+                element.as_ref().clone()
+            }
+
+            typ => return Err(error(format!("{} can't be indexed", typ), span)),
+        };
+
+        let offset = (index as u32).wrapping_mul(self.size_of(&element) as u32);
+        Ok((array.bits.wrapping_add(offset), element))
+    }
+
+    fn call(
+        &mut self,
+        name: &str,
+        arguments: &'a [Expr],
+        builtin: Option<&BuiltinFunctionType>,
+        span: &Span,
+    ) -> Result<Value> {
+        let mut values = Vec::new();
+
+        for argument in arguments {
+            values.push(self.eval(argument)?);
+        }
+
+        match builtin {
+            Some(builtin) => self.call_builtin(builtin, &values, span),
+            None => self.call_function(name, values, span),
+        }
+    }
+
+    fn call_function(&mut self, name: &str, arguments: Vec<Value>, span: &Span) -> Result<Value> {
+        let Some(function) = self.functions.get(name).copied() else {
+            return Err(error(format!("No function called '{}'", name), span));
+        };
+
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err(error(
+                format!(
+                    "Stack overflow, calls nested more than {} deep",
+                    MAX_CALL_DEPTH
+                ),
+                span,
+            ));
+        }
+
+        let sp = self.sp;
+        self.frames.push(vec![HashMap::new()]);
+
+        let flow = self.enter(function, arguments, span);
+
+        self.frames.pop();
+        self.sp = sp;
+
+        // A char function's result isn't cut down to a byte, it's whatever
+        // was in the register
+        let bits = match flow? {
+            Flow::Return(bits) => bits,
+            Flow::Next if *function.return_type == Type::Void => 0,
+            Flow::Next => {
+                return Err(error(
+                    format!(
+                        "'{}' got to the end without returning a {}",
+                        name, function.return_type
+                    ),
+                    span,
+                ));
+            }
+        };
+
+        Ok(Value::new(bits, function.return_type.clone()))
+    }
+
+    // Parameters share the body's scope, and get stored like any other
+    // local so a char argument is cut down to a byte
+    fn enter(
+        &mut self,
+        function: Function<'a>,
+        arguments: Vec<Value>,
+        span: &Span,
+    ) -> Result<Flow> {
+        for (param, argument) in function.params.iter().zip(arguments) {
+            let address = self.allocate(4, span)?;
+            self.store(address, &param.param_type, argument.bits, span)?;
+            self.bind(&param.name, address, &param.param_type, None);
+        }
+
+        self.exec_all(function.body)
+    }
+
+    // Same behaviour as the syscalls the generated code makes
+    fn call_builtin(
+        &mut self,
+        builtin: &BuiltinFunctionType,
+        arguments: &[Value],
+        span: &Span,
+    ) -> Result<Value> {
+        let argument = |i: usize| arguments.get(i).map_or(0, |value| value.bits);
+        let at = |message: String| error(message, span);
+        let write_error = |e: std::io::Error| error(format!("Failed to write output: {}", e), span);
+
+        let value = match builtin {
+            BuiltinFunctionType::IntegerPrint => {
+                write!(self.output, "{}", argument(0) as i32).map_err(write_error)?;
+                Value::new(0, Type::Void)
+            }
+
+            BuiltinFunctionType::StringPrint => {
+                let bytes = self.memory.load_string(argument(0)).map_err(at)?;
+                self.output.write_all(&bytes).map_err(write_error)?;
+                Value::new(0, Type::Void)
+            }
+
+            BuiltinFunctionType::CharPrint => {
+                self.output
+                    .write_all(&[argument(0) as u8])
+                    .map_err(write_error)?;
+                Value::new(0, Type::Void)
+            }
+
+            BuiltinFunctionType::IntegerRead => {
+                self.output.flush().map_err(write_error)?;
+                Value::int(console::read_integer(self.input).map_err(at)?)
+            }
+
+            // A fresh buffer from the heap, whatever was read goes in it
+            BuiltinFunctionType::StringRead => {
+                let size = argument(0) as i32;
+                let buffer = self.memory.sbrk(size).map_err(at)?;
+
+                self.output.flush().map_err(write_error)?;
+
+                if let Some(bytes) = console::read_string(self.input, size).map_err(at)? {
+                    for (i, byte) in bytes.iter().enumerate() {
+                        self.memory
+                            .store(buffer + i as u32, 1, *byte as u32)
+                            .map_err(at)?;
+                    }
+                }
+
+                Value::new(buffer, Type::String)
+            }
+
+            // The first argument picks the generator but there's only one
+            BuiltinFunctionType::IntegerRandomRange => {
+                let upper = argument(1) as i32;
+
+                if upper <= 0 {
+                    return Err(at(format!(
+                        "The upper bound for a random number has to be positive, found {}",
+                        upper
+                    )));
+                }
+
+                Value::int(self.rng.below(upper))
+            }
+
+            BuiltinFunctionType::CharRead => {
+                self.output.flush().map_err(write_error)?;
+                let byte = console::read_character(self.input).map_err(at)?;
+                Value::new(byte as u32, Type::Char)
+            }
+
+            BuiltinFunctionType::Allocate => {
+                let address = self.memory.sbrk(argument(0) as i32).map_err(at)?;
+                Value::new(address, Type::pointer_to(Type::Void))
+            }
+        };
+
+        Ok(value)
+    }
+
+    // Chars are a sign extended byte like lb, structs and arrays are their address
+    fn load(&self, address: u32, typ: &Type, span: &Span) -> Result<Value> {
+        let bits = match typ {
+            Type::Char => self.memory.load(address, 1).map(|b| b as u8 as i8 as u32),
+            Type::Struct(_) | Type::Array(..) => Ok(address),
+            _ => self.memory.load(address, 4),
+        };
+
+        Ok(Value::new(
+            bits.map_err(|message| error(message, span))?,
+            typ.clone(),
+        ))
+    }
+
+    fn store(&mut self, address: u32, typ: &Type, bits: u32, span: &Span) -> Result<()> {
+        let size = if *typ == Type::Char { 1 } else { 4 };
+
+        self.memory
+            .store(address, size, bits)
+            .map_err(|message| error(message, span))
+    }
+
+    // Zeroed and word aligned, like a stack slot the generated code clears
+    fn allocate(&mut self, size: usize, span: &Span) -> Result<u32> {
+        let size = size.next_multiple_of(4).max(4) as u32;
+        let address = self.sp.wrapping_sub(size);
+
+        for offset in (0..size).step_by(4) {
+            self.memory
+                .store(address.wrapping_add(offset), 4, 0)
+                .map_err(|message| error(message, span))?;
+        }
+
+        self.sp = address;
+        Ok(address)
+    }
+
+    fn variable(&self, name: &str, span: &Span) -> Result<(u32, Type)> {
+        self.lookup(name)
+            .map(|variable| (variable.address, variable.typ.clone()))
+            .ok_or_else(|| error(format!("No variable called '{}'", name), span))
+    }
+
+    // How many elements name can be indexed with, if that's known
+    fn length(&self, name: &str) -> Option<usize> {
+        self.lookup(name).and_then(|variable| variable.length)
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Variable> {
+        let scopes = self.frames.last().into_iter().flatten().rev();
+
+        scopes
+            .filter_map(|scope| scope.get(name))
+            .chain(self.globals.get(name))
+            .next()
+    }
+
+    fn bind(&mut self, name: &'a str, address: u32, typ: &Type, length: Option<usize>) {
+        if let Some(scope) = self.frames.last_mut().and_then(|frame| frame.last_mut()) {
+            scope.insert(
+                name,
+                Variable {
+                    address,
+                    typ: typ.clone(),
+                    length,
+                },
+            );
+        }
+    }
+
+    fn push_scope(&mut self) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.push(HashMap::new());
+        }
+    }
+
+    fn pop_scope(&mut self) {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.pop();
+        }
+    }
+
+    fn size_of(&self, typ: &Type) -> usize {
+        size_of(typ, &self.structs)
+    }
+
+    fn tick(&mut self, span: &Span) -> Result<()> {
+        if self.max_steps.is_some_and(|max| self.steps >= max) {
+            return Err(error(format!("Gave up after {} steps", self.steps), span));
+        }
+
+        self.steps += 1;
+        Ok(())
+    }
+}
+
+// What dereferencing a value of type typ reaches, the semantic pass made
+// sure there's always something
+fn pointee(typ: &Type) -> Type {
+    match typ {
+        Type::Pointer(pointee) => pointee.as_ref().clone(),
+        _ => Type::Void,
+    }
+}
+
+fn error(message: String, span: &Span) -> RuntimeError {
+    RuntimeError {
+        message,
+        span: span.clone(),
+    }
+}
+
+fn overflow(x: i32, y: i32, span: &Span) -> RuntimeError {
+    error(format!("Arithmetic overflow with {} and {}", x, y), span)
+}
@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use crate::parser::ast::{DataStorageType, DataValue, Statement, StatementKind};
+use crate::sim::memory::DATA_BASE;
+
+// The data segment laid out the way the assembler would from the same
+// declarations, so globals and strings sit at the addresses they would under MARS
+pub struct DataSegment {
+    pub bytes: Vec<u8>,
+    pub labels: HashMap<String, u32>,
+}
+
+pub fn lay_out(declarations: &[Statement]) -> DataSegment {
+    let mut bytes = Vec::new();
+    let mut labels = HashMap::new();
+
+    // Offsets of words holding the address of a label, filled in at the end
+    // since a label can be used before it's declared
+    let mut fixups = Vec::new();
+
+    for stmt in declarations {
+        let StatementKind::DataDeclaration {
+            label,
+            storage_type,
+            values,
+        } = &stmt.kind
+        else {
+            continue;
+        };
+
+        let alignment = match storage_type {
+            DataStorageType::Word => 4,
+            DataStorageType::Align(n) => 1 << n,
+            _ => 1,
+        };
+
+        bytes.resize(bytes.len().next_multiple_of(alignment), 0);
+
+        if let Some(label) = label {
+            labels.insert(label.clone(), DATA_BASE + bytes.len() as u32);
+        }
+
+        match storage_type {
+            DataStorageType::Asciiz => {
+                for value in values {
+                    if let DataValue::String(text) = value {
+                        bytes.extend(text.bytes());
+                        bytes.push(0);
+                    }
+                }
+            }
+
+            DataStorageType::Word | DataStorageType::Byte => {
+                let size = if *storage_type == DataStorageType::Word {
+                    4
+                } else {
+                    1
+                };
+
+                for value in values {
+                    let number = match value {
+                        DataValue::Integer(n) => *n,
+                        DataValue::Label(label) => {
+                            fixups.push((bytes.len(), label.clone()));
+                            0
+                        }
+                        DataValue::String(_) => 0,
+                    };
+
+                    bytes.extend(&number.to_le_bytes()[..size]);
+                }
+            }
+
+            DataStorageType::Space(n) => bytes.resize(bytes.len() + n, 0),
+            DataStorageType::Align(_) => {}
+        }
+    }
+
+    // The parser only ever points at labels it declared itself
+    for (offset, label) in fixups {
+        let address = labels.get(&label).copied().unwrap_or_default();
+        bytes[offset..offset + 4].copy_from_slice(&address.to_le_bytes());
+    }
+
+    DataSegment { bytes, labels }
+}
@@ -0,0 +1,548 @@
+mod bounds;
+mod layout;
+mod lower;
+
+// The interpreter lays structs out the same way so pointers into them agree
+pub(crate) use bounds::reassigned_names;
+pub(crate) use layout::{StructLayout, size_of};
+pub use lower::lower;
+
+use std::fmt;
+
+use crate::parser::ast::{BuiltinFunctionType, Statement};
+
+// Three address code sitting between the AST and the backend. Every type,
+// scope and struct layout is dealt with by the time a program gets here,
+// what's left is plain words moving between temps, memory and calls.
+// A backend only has to pick instructions for each of these
+
+// A virtual register. There are as many as the lowering wants, the backend
+// decides which real register (or stack slot) each one ends up in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Temp(pub usize);
+
+// Index into Function::blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockId(pub usize);
+
+// Index into Function::slots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SlotId(pub usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Temp(Temp),
+    Const(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Width {
+    // Sign extended when loaded, like lb
+    Byte,
+    Word,
+}
+
+// What a load, store or address is relative to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Base {
+    // An address held in a temp
+    Temp(Temp),
+
+    // Somewhere in the function's own frame
+    Slot(SlotId),
+
+    // Something in the data segment, a global or a string
+    Label(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    // Traps on the most negative number, like sub does
+    Negate,
+
+    // 1 for 0, 0 for anything else
+    Not,
+
+    BitwiseNot,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    // These three trap on overflow
+    Add,
+    Subtract,
+    Multiply,
+
+    // Doesn't, turns an index into a byte offset
+    Scale,
+
+    // Dividing by zero stops the program, INT_MIN / -1 wraps
+    Divide,
+    Modulo,
+
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+
+    // Only the low five bits of the right side count, >> keeps the sign
+    ShiftLeft,
+    ShiftRight,
+
+    // 1 or 0
+    Compare(Condition),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+
+    // Unsigned <, so a negative index looks huge. Bounds checks use it
+    Below,
+}
+
+impl Condition {
+    // The same test with its sides swapped, a < b is b > a
+    pub fn mirrored(self) -> Condition {
+        match self {
+            Condition::Less => Condition::Greater,
+            Condition::LessEqual => Condition::GreaterEqual,
+            Condition::Greater => Condition::Less,
+            Condition::GreaterEqual => Condition::LessEqual,
+            other => other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Inst {
+    Const {
+        dest: Temp,
+        value: i32,
+    },
+
+    // Where base + offset is, rather than what's there
+    Address {
+        dest: Temp,
+        base: Base,
+        offset: i32,
+    },
+
+    Unary {
+        dest: Temp,
+        op: UnaryOp,
+        src: Temp,
+    },
+
+    Binary {
+        dest: Temp,
+        op: BinaryOp,
+        left: Temp,
+        right: Operand,
+    },
+
+    Load {
+        dest: Temp,
+        width: Width,
+        base: Base,
+        offset: i32,
+    },
+
+    // A byte store keeps the low byte of value
+    Store {
+        width: Width,
+        value: Operand,
+        base: Base,
+        offset: i32,
+    },
+
+    // The index-th argument this function was called with. Only ever at
+    // the start of the entry block, before anything else can get to them
+    Param {
+        dest: Temp,
+        index: usize,
+    },
+
+    Call {
+        dest: Option<Temp>,
+        function: String,
+        args: Vec<Temp>,
+    },
+
+    Builtin {
+        dest: Option<Temp>,
+        builtin: BuiltinFunctionType,
+        args: Vec<Temp>,
+    },
+
+    // Handed to the assembler as is, only _start has these
+    Asm {
+        opcode: String,
+        operands: Vec<String>,
+    },
+}
+
+impl Inst {
+    // The temp this writes, if any
+    pub fn dest(&self) -> Option<Temp> {
+        match self {
+            Inst::Const { dest, .. }
+            | Inst::Address { dest, .. }
+            | Inst::Unary { dest, .. }
+            | Inst::Binary { dest, .. }
+            | Inst::Load { dest, .. }
+            | Inst::Param { dest, .. } => Some(*dest),
+
+            Inst::Call { dest, .. } | Inst::Builtin { dest, .. } => *dest,
+
+            Inst::Store { .. } | Inst::Asm { .. } => None,
+        }
+    }
+
+    // The temps this reads
+    pub fn uses(&self) -> Vec<Temp> {
+        match self {
+            Inst::Unary { src, .. } => vec![*src],
+
+            Inst::Binary { left, right, .. } => {
+                [Some(*left), right.temp()].into_iter().flatten().collect()
+            }
+
+            Inst::Load { base, .. } | Inst::Address { base, .. } => {
+                base.temp().into_iter().collect()
+            }
+
+            Inst::Store { value, base, .. } => {
+                [value.temp(), base.temp()].into_iter().flatten().collect()
+            }
+
+            Inst::Call { args, .. } | Inst::Builtin { args, .. } => args.clone(),
+
+            Inst::Const { .. } | Inst::Param { .. } | Inst::Asm { .. } => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    Jump(BlockId),
+
+    // To then when left and right pass the test, otherwise to otherwise
+    Branch {
+        condition: Condition,
+        left: Temp,
+        right: Operand,
+        then: BlockId,
+        otherwise: BlockId,
+    },
+
+    Return(Option<Temp>),
+
+    // --bounds-check caught a bad index on this line of the source
+    OutOfBounds {
+        line: usize,
+    },
+
+    // Nothing gets here, like the end of _start after it exits
+    Unreachable,
+}
+
+impl Terminator {
+    pub fn uses(&self) -> Vec<Temp> {
+        match self {
+            Terminator::Branch { left, right, .. } => {
+                [Some(*left), right.temp()].into_iter().flatten().collect()
+            }
+
+            Terminator::Return(value) => value.iter().copied().collect(),
+
+            Terminator::Jump(_) | Terminator::OutOfBounds { .. } | Terminator::Unreachable => {
+                Vec::new()
+            }
+        }
+    }
+
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Terminator::Jump(target) => vec![*target],
+            Terminator::Branch {
+                then, otherwise, ..
+            } => vec![*then, *otherwise],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    // Unique across the whole program, the backend can use it as is
+    pub label: String,
+    pub instructions: Vec<Inst>,
+    pub terminator: Terminator,
+}
+
+// A chunk of the frame for one local, zeroed or not is up to the lowering
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot {
+    pub name: String,
+    pub size: usize,
+}
+
+// Blocks are in the order they should be laid out, the first is the
+// entry and nothing ever jumps back to it. A temp never lives across a
+// jump back to an earlier block except the one that's its last use, so
+// from where it's first mentioned to where it's last mentioned in this
+// order covers everywhere it's live
+#[derive(Debug, Clone, PartialEq)]
+pub struct Function {
+    pub name: String,
+    pub blocks: Vec<Block>,
+    pub slots: Vec<Slot>,
+
+    // Temps are numbered from 0 up to this
+    pub temps: usize,
+
+    // _start has no frame, no parameters and never returns
+    pub has_frame: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    // Straight from the parser, globals and string literals
+    pub data: Vec<Statement>,
+    pub functions: Vec<Function>,
+}
+
+impl Operand {
+    pub fn temp(&self) -> Option<Temp> {
+        match self {
+            Operand::Temp(temp) => Some(*temp),
+            Operand::Const(_) => None,
+        }
+    }
+}
+
+impl Base {
+    pub fn temp(&self) -> Option<Temp> {
+        match self {
+            Base::Temp(temp) => Some(*temp),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Temp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operand::Temp(temp) => write!(f, "{}", temp),
+            Operand::Const(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+impl fmt::Display for Width {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Width::Byte => write!(f, "b"),
+            Width::Word => write!(f, "w"),
+        }
+    }
+}
+
+// Memory operands are written [base+offset]
+fn write_place(f: &mut fmt::Formatter, base: &Base, offset: i32) -> fmt::Result {
+    match base {
+        Base::Temp(temp) => write!(f, "[{}", temp)?,
+        Base::Slot(slot) => write!(f, "[slot{}", slot.0)?,
+        Base::Label(label) => write!(f, "[{}", label)?,
+    }
+
+    if offset != 0 {
+        write!(f, "{:+}", offset)?;
+    }
+
+    write!(f, "]")
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Condition::Less => "lt",
+            Condition::LessEqual => "le",
+            Condition::Greater => "gt",
+            Condition::GreaterEqual => "ge",
+            Condition::Equal => "eq",
+            Condition::NotEqual => "ne",
+            Condition::Below => "ltu",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            BinaryOp::Add => "add",
+            BinaryOp::Subtract => "sub",
+            BinaryOp::Multiply => "mul",
+            BinaryOp::Scale => "scale",
+            BinaryOp::Divide => "div",
+            BinaryOp::Modulo => "mod",
+            BinaryOp::BitwiseAnd => "and",
+            BinaryOp::BitwiseOr => "or",
+            BinaryOp::BitwiseXor => "xor",
+            BinaryOp::ShiftLeft => "shl",
+            BinaryOp::ShiftRight => "shr",
+            BinaryOp::Compare(condition) => return write!(f, "set{}", condition),
+        };
+        write!(f, "{}", s)
+    }
+}
+
+fn write_args(f: &mut fmt::Formatter, args: &[Temp]) -> fmt::Result {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    write!(f, "({})", args.join(", "))
+}
+
+impl fmt::Display for Inst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(dest) = self.dest() {
+            write!(f, "{} = ", dest)?;
+        }
+
+        match self {
+            Inst::Const { value, .. } => write!(f, "{}", value),
+
+            Inst::Address { base, offset, .. } => {
+                write!(f, "&")?;
+                write_place(f, base, *offset)
+            }
+
+            Inst::Unary { op, src, .. } => {
+                let op = match op {
+                    UnaryOp::Negate => "neg",
+                    UnaryOp::Not => "not",
+                    UnaryOp::BitwiseNot => "bnot",
+                };
+                write!(f, "{} {}", op, src)
+            }
+
+            Inst::Binary {
+                op, left, right, ..
+            } => write!(f, "{} {}, {}", op, left, right),
+
+            Inst::Load {
+                width,
+                base,
+                offset,
+                ..
+            } => {
+                write!(f, "load.{} ", width)?;
+                write_place(f, base, *offset)
+            }
+
+            Inst::Store {
+                width,
+                value,
+                base,
+                offset,
+            } => {
+                write!(f, "store.{} {}, ", width, value)?;
+                write_place(f, base, *offset)
+            }
+
+            Inst::Param { index, .. } => write!(f, "param {}", index),
+
+            Inst::Call { function, args, .. } => {
+                write!(f, "call {}", function)?;
+                write_args(f, args)
+            }
+
+            Inst::Builtin { builtin, args, .. } => {
+                write!(f, "builtin {:?}", builtin)?;
+                write_args(f, args)
+            }
+
+            Inst::Asm { opcode, operands } if operands.is_empty() => {
+                write!(f, "asm {}", opcode.trim())
+            }
+
+            Inst::Asm { opcode, operands } => {
+                write!(f, "asm {} {}", opcode.trim(), operands.join(", "))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Function {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "function {}", self.name)?;
+
+        for (i, slot) in self.slots.iter().enumerate() {
+            writeln!(f, "    slot{}: {}, {} bytes", i, slot.name, slot.size)?;
+        }
+
+        for block in &self.blocks {
+            writeln!(f, "{}:", block.label)?;
+
+            for inst in &block.instructions {
+                writeln!(f, "    {}", inst)?;
+            }
+
+            let label = |id: &BlockId| self.blocks[id.0].label.as_str();
+
+            match &block.terminator {
+                Terminator::Jump(target) => writeln!(f, "    jump {}", label(target))?,
+
+                Terminator::Branch {
+                    condition,
+                    left,
+                    right,
+                    then,
+                    otherwise,
+                } => writeln!(
+                    f,
+                    "    branch {} {}, {} ? {} : {}",
+                    condition,
+                    left,
+                    right,
+                    label(then),
+                    label(otherwise)
+                )?,
+
+                Terminator::Return(Some(value)) => writeln!(f, "    return {}", value)?,
+                Terminator::Return(None) => writeln!(f, "    return")?,
+                Terminator::OutOfBounds { line } => {
+                    writeln!(f, "    out of bounds on line {}", line)?
+                }
+                Terminator::Unreachable => writeln!(f, "    unreachable")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Module {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, function) in self.functions.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}", function)?;
+        }
+
+        Ok(())
+    }
+}
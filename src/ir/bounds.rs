@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+
+use crate::parser::ast::{Expr, ExprKind, Statement, StatementKind};
+
+// Names that might stop pointing at the array they were declared with,
+// either assigned to directly or handed out with & so something else
+// can. Their length isn't known after the declaration, so they're never
+// bounds checked. Doesn't care about scopes, skipping one too many is fine
+pub fn reassigned_names(statements: &[Statement]) -> HashSet<String> {
+    let mut names = HashSet::new();
+    collect_statements(statements, &mut names);
+    names
+}
+
+fn collect_statements(statements: &[Statement], names: &mut HashSet<String>) {
+    for stmt in statements {
+        match &stmt.kind {
+            StatementKind::VariableDeclaration { operation, .. } => {
+                collect_expr(operation, names);
+            }
+
+            StatementKind::VariableAssignment {
+                identifier,
+                operation,
+                is_dereference,
+                is_array_index,
+                indexer,
+            } => {
+                if !is_dereference && !is_array_index {
+                    names.insert(identifier.clone());
+                }
+
+                collect_expr(operation, names);
+                collect_expr(indexer, names);
+            }
+
+            StatementKind::IndirectAssignment { target, operation } => {
+                collect_expr(target, names);
+                collect_expr(operation, names);
+            }
+
+            StatementKind::Function { body, .. } => collect_statements(body, names),
+
+            StatementKind::FunctionCall { arguments, .. } => {
+                for arg in arguments {
+                    collect_expr(arg, names);
+                }
+            }
+
+            StatementKind::While {
+                condition, body, ..
+            } => {
+                collect_expr(condition, names);
+                collect_statements(body, names);
+            }
+
+            StatementKind::For {
+                init,
+                condition,
+                var_change,
+                body,
+                ..
+            } => {
+                collect_statements(std::slice::from_ref(&**init), names);
+                collect_expr(condition, names);
+                collect_statements(std::slice::from_ref(&**var_change), names);
+                collect_statements(body, names);
+            }
+
+            StatementKind::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                collect_expr(condition, names);
+                collect_statements(body, names);
+
+                if let Some(else_body) = else_body {
+                    collect_statements(else_body, names);
+                }
+            }
+
+            StatementKind::Return { value } | StatementKind::ExprStatement(value) => {
+                collect_expr(value, names);
+            }
+
+            StatementKind::DataDeclaration { .. }
+            | StatementKind::Instruction { .. }
+            | StatementKind::Struct { .. } => {}
+        }
+    }
+}
+
+fn collect_expr(expr: &Expr, names: &mut HashSet<String>) {
+    match &expr.kind {
+        ExprKind::IdentifierReference(name) => {
+            names.insert(name.clone());
+        }
+
+        ExprKind::BinaryOp { left, right, .. } => {
+            collect_expr(left, names);
+            collect_expr(right, names);
+        }
+
+        ExprKind::UnaryOp { operand, .. } => collect_expr(operand, names),
+
+        ExprKind::ArrayInitializer { body, .. } => {
+            for element in body {
+                collect_expr(element, names);
+            }
+        }
+
+        ExprKind::ArrayIndex { array, indexer } => {
+            collect_expr(array, names);
+            collect_expr(indexer, names);
+        }
+
+        ExprKind::FieldAccess { base, .. } => collect_expr(base, names),
+
+        ExprKind::FunctionCall { arguments, .. } => {
+            for arg in arguments {
+                collect_expr(arg, names);
+            }
+        }
+
+        ExprKind::Integer(_)
+        | ExprKind::Identifier(_)
+        | ExprKind::IdentifierDereference(_)
+        | ExprKind::StringLiteral(_)
+        | ExprKind::BoolLiteral(_)
+        | ExprKind::CharLiteral(_)
+        | ExprKind::Empty => {}
+    }
+}
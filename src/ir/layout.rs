@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::parser::ast::{Statement, StatementKind, StructField, Type};
+
+// Where each field of a struct sits. Every field starts on a word, a char
+// wastes three bytes but lw and sw never see an unaligned address
+pub struct StructLayout {
+    pub size: usize,
+    pub fields: HashMap<String, (usize, Type)>,
+}
+
+impl StructLayout {
+    // Lays out every struct declared in statements. A struct can only be laid
+    // out once every struct it holds by value is, the semantic pass already
+    // made sure that always happens eventually
+    pub fn from_statements(statements: &[Statement]) -> HashMap<String, StructLayout> {
+        let mut pending: Vec<(&String, &Vec<StructField>)> = statements
+            .iter()
+            .filter_map(|stmt| match &stmt.kind {
+                StatementKind::Struct { name, fields } => Some((name, fields)),
+                _ => None,
+            })
+            .collect();
+
+        let mut layouts = HashMap::new();
+
+        while !pending.is_empty() {
+            let before = pending.len();
+
+            pending.retain(|(name, fields)| {
+                let ready = fields
+                    .iter()
+                    .all(|field| match field.field_type.innermost() {
+                        Type::Struct(inner) => layouts.contains_key(inner),
+                        _ => true,
+                    });
+
+                if !ready {
+                    return true;
+                }
+
+                let mut size = 0;
+                let mut offsets = HashMap::new();
+
+                for field in fields.iter() {
+                    offsets.insert(field.name.clone(), (size, field.field_type.clone()));
+                    size += size_of(&field.field_type, &layouts).next_multiple_of(4);
+                }
+
+                layouts.insert(
+                    name.to_string(),
+                    StructLayout {
+                        size,
+                        fields: offsets,
+                    },
+                );
+                false
+            });
+
+            if pending.len() == before {
+                break;
+            }
+        }
+
+        layouts
+    }
+}
+
+// Bytes a value of typ takes up. Everything is a word except chars
+// and whatever is laid out in place, structs and arrays
+pub fn size_of(typ: &Type, structs: &HashMap<String, StructLayout>) -> usize {
+    match typ {
+        Type::Char => 1,
+        Type::Struct(name) => structs.get(name).map_or(4, |layout| layout.size),
+        Type::Array(element, n) => n * size_of(element, structs),
+        _ => 4,
+    }
+}
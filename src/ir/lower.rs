@@ -0,0 +1,1353 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    diagnostic,
+    error::{CompileError, Result},
+    lexer::Span,
+    parser::ast::{
+        BinaryOperator, BuiltinFunctionType, DataStorageType, DataValue, Expr, ExprKind, Parameter,
+        Program, Statement, StatementKind, Type, UnaryOperator, global_label,
+    },
+};
+
+use super::{
+    Base, BinaryOp, Block, BlockId, Condition, Function, Inst, Module, Operand, Slot, SlotId, Temp,
+    Terminator, UnaryOp, Width, bounds,
+    layout::{StructLayout, size_of},
+};
+
+// Turns a program the semantic pass is happy with into IR. With
+// bounds_check every index into an array of known length is checked
+pub fn lower(program: &Program, bounds_check: bool) -> Result<Module> {
+    Lowering::new(program, bounds_check).module()
+}
+
+#[derive(Clone)]
+struct Variable {
+    base: Base,
+    typ: Type,
+
+    // Element count of a pointer declared with {...}[n] that nothing
+    // can point somewhere else later, for --bounds-check
+    length: Option<usize>,
+}
+
+// Where an assignable expression lives and what's there
+struct Place {
+    base: Base,
+    offset: i32,
+    typ: Type,
+}
+
+struct Lowering<'a> {
+    program: &'a Program,
+    bounds_check: bool,
+
+    // The statement being lowered, errors without a better span point here
+    span: Span,
+
+    // Field offsets and sizes of every struct
+    structs: HashMap<String, StructLayout>,
+
+    // Names declared at the top level, these live in the data segment
+    globals: HashMap<String, Variable>,
+
+    // Return types, pointer arithmetic needs to know what a call hands back
+    function_types: HashMap<String, Type>,
+
+    // The label of each string literal
+    strings: HashMap<String, String>,
+
+    // Pointers that can't be trusted to still hold their {...}[n] array
+    reassigned: HashSet<String>,
+
+    // Labels for && and || (and everything else that only exists once
+    // lowered) are numbered from here, across the whole program
+    num_labels: usize,
+
+    // The function being lowered. blocks is in the order they were made,
+    // order is the order they were started in, which is how they get laid out
+    blocks: Vec<Block>,
+    order: Vec<BlockId>,
+    current: BlockId,
+
+    // Whether current still needs a terminator
+    open: bool,
+
+    slots: Vec<Slot>,
+    temps: usize,
+
+    // Innermost last. Leaving a block forgets its names, but its slots stay
+    scopes: Vec<HashMap<String, Variable>>,
+}
+
+impl<'a> Lowering<'a> {
+    fn new(program: &'a Program, bounds_check: bool) -> Self {
+        Lowering {
+            program,
+            bounds_check,
+            span: Span::default(),
+            structs: HashMap::new(),
+            globals: HashMap::new(),
+            function_types: HashMap::new(),
+            strings: HashMap::new(),
+            reassigned: HashSet::new(),
+            num_labels: 0,
+            blocks: Vec::new(),
+            order: Vec::new(),
+            current: BlockId(0),
+            open: false,
+            slots: Vec::new(),
+            temps: 0,
+            scopes: Vec::new(),
+        }
+    }
+
+    fn module(mut self) -> Result<Module> {
+        let text = &self.program.segments.text.body;
+
+        self.structs = StructLayout::from_statements(text);
+        self.reassigned = bounds::reassigned_names(text);
+
+        for stmt in text {
+            match &stmt.kind {
+                StatementKind::VariableDeclaration {
+                    identifier,
+                    var_type,
+                    operation,
+                } => {
+                    let length = match &operation.kind {
+                        ExprKind::ArrayInitializer { size, .. }
+                            if !self.reassigned.contains(identifier) =>
+                        {
+                            Some(*size)
+                        }
+                        _ => None,
+                    };
+
+                    self.globals.insert(
+                        identifier.clone(),
+                        Variable {
+                            base: Base::Label(global_label(identifier)),
+                            typ: var_type.clone(),
+                            length,
+                        },
+                    );
+                }
+
+                StatementKind::Function {
+                    name, return_type, ..
+                } => {
+                    self.function_types
+                        .insert(name.clone(), return_type.clone());
+                }
+
+                _ => {}
+            }
+        }
+
+        // Identical literals share a label, the first one wins
+        for stmt in &self.program.segments.data.body {
+            if let StatementKind::DataDeclaration {
+                label: Some(label),
+                storage_type: DataStorageType::Asciiz,
+                values,
+            } = &stmt.kind
+                && let [DataValue::String(text)] = values.as_slice()
+            {
+                self.strings
+                    .entry(text.clone())
+                    .or_insert_with(|| label.clone());
+            }
+        }
+
+        let mut functions = Vec::new();
+
+        for stmt in text {
+            if let StatementKind::Function {
+                name,
+                params,
+                body,
+                use_stack,
+                ..
+            } = &stmt.kind
+            {
+                self.span = stmt.span.clone();
+                functions.push(self.function(name, params, body, *use_stack)?);
+            }
+        }
+
+        Ok(Module {
+            data: self.program.segments.data.body.clone(),
+            functions,
+        })
+    }
+
+    fn function(
+        &mut self,
+        name: &str,
+        params: &[Parameter],
+        body: &[Statement],
+        has_frame: bool,
+    ) -> Result<Function> {
+        self.blocks.clear();
+        self.order.clear();
+        self.slots.clear();
+        self.temps = 0;
+
+        // Parameters share the body's scope
+        self.scopes = vec![HashMap::new()];
+
+        let entry = self.new_block(name.to_string());
+        self.start(entry);
+
+        // Copied into the frame like any other local, so a char argument
+        // is cut down to a byte
+        for (i, param) in params.iter().enumerate() {
+            let value = self.temp();
+            self.emit(Inst::Param {
+                dest: value,
+                index: i,
+            });
+
+            let slot = self.slot(&param.name, 4);
+            self.emit(Inst::Store {
+                width: width(&param.param_type),
+                value: Operand::Temp(value),
+                base: Base::Slot(slot),
+                offset: 0,
+            });
+
+            self.bind(&param.name, Base::Slot(slot), &param.param_type, None);
+        }
+
+        for stmt in body {
+            self.statement(stmt)?;
+        }
+
+        // Only reachable when a function that should return something doesn't
+        self.terminate(if has_frame {
+            Terminator::Return(None)
+        } else {
+            Terminator::Unreachable
+        });
+
+        Ok(self.finish(name, has_frame))
+    }
+
+    // Drops the blocks nothing can get to, the ones after a return say,
+    // and numbers what's left in layout order
+    fn finish(&mut self, name: &str, has_frame: bool) -> Function {
+        let mut reachable = HashSet::new();
+        let mut pending = vec![self.order[0]];
+
+        while let Some(id) = pending.pop() {
+            if reachable.insert(id) {
+                pending.extend(self.blocks[id.0].terminator.successors());
+            }
+        }
+
+        let order: Vec<BlockId> = self
+            .order
+            .iter()
+            .copied()
+            .filter(|id| reachable.contains(id))
+            .collect();
+
+        let renumbered: HashMap<BlockId, BlockId> = order
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, BlockId(i)))
+            .collect();
+
+        let blocks = order
+            .iter()
+            .map(|id| {
+                let mut block = self.blocks[id.0].clone();
+
+                match &mut block.terminator {
+                    Terminator::Jump(target) => *target = renumbered[target],
+
+                    Terminator::Branch {
+                        then, otherwise, ..
+                    } => {
+                        *then = renumbered[then];
+                        *otherwise = renumbered[otherwise];
+                    }
+
+                    _ => {}
+                }
+
+                block
+            })
+            .collect();
+
+        Function {
+            name: name.to_string(),
+            blocks,
+            slots: std::mem::take(&mut self.slots),
+            temps: self.temps,
+            has_frame,
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Result<()> {
+        // Lets the panic hook show which statement we were on
+        diagnostic::set_current_span(&statement.span);
+
+        // Put back afterwards so errors in a loop condition don't
+        // point at the last statement of the body
+        let outer_span = std::mem::replace(&mut self.span, statement.span.clone());
+
+        match &statement.kind {
+            StatementKind::VariableDeclaration {
+                var_type,
+                identifier,
+                operation,
+            } => self.declare(var_type, identifier, operation)?,
+
+            // The value comes first, then where it goes
+            StatementKind::VariableAssignment {
+                identifier,
+                operation,
+                is_dereference,
+                is_array_index,
+                indexer,
+            } => {
+                let (value, _) = self.operand(operation)?;
+                let variable = self.variable(identifier)?;
+                let place = Place {
+                    base: variable.base.clone(),
+                    offset: 0,
+                    typ: variable.typ.clone(),
+                };
+
+                let place = if *is_dereference {
+                    let typ = pointee(&place.typ);
+                    let pointer = self.load(place);
+
+                    Place {
+                        base: Base::Temp(pointer),
+                        offset: 0,
+                        typ,
+                    }
+                } else if *is_array_index {
+                    let typ = place.typ.clone();
+                    let array = self.load(place);
+                    self.element(array, &typ, variable.length, indexer)?
+                } else {
+                    place
+                };
+
+                self.store(value, place);
+            }
+
+            StatementKind::IndirectAssignment { target, operation } => {
+                let (value, _) = self.operand(operation)?;
+                let place = self.place(target)?;
+                self.store(value, place);
+            }
+
+            StatementKind::FunctionCall {
+                function_name,
+                arguments,
+                builtin_function_type,
+                ..
+            } => {
+                self.call(function_name, arguments, builtin_function_type, false)?;
+            }
+
+            StatementKind::ExprStatement(expr) => {
+                self.value(expr)?;
+            }
+
+            StatementKind::Instruction { opcode, operands } => {
+                self.emit(Inst::Asm {
+                    opcode: opcode.clone(),
+                    operands: operands.clone(),
+                });
+            }
+
+            // The condition is checked at the bottom, so each time round
+            // only takes the one branch
+            StatementKind::While {
+                body_label,
+                end_label,
+                condition,
+                body,
+            } => {
+                let body_block = self.new_block(body_label.clone());
+                let check = self.new_block(end_label.clone());
+                let exit = self.new_block(format!("{}_exit", end_label));
+
+                self.terminate(Terminator::Jump(check));
+
+                self.start(body_block);
+                self.block(body)?;
+
+                self.start(check);
+                self.branch(condition, body_block, exit)?;
+
+                self.start(exit);
+            }
+
+            // init gets a scope of its own that lasts the whole loop, the
+            // body gets a fresh one every time round
+            StatementKind::For {
+                init,
+                body_label,
+                end_label,
+                condition,
+                var_change,
+                body,
+            } => {
+                self.scopes.push(HashMap::new());
+                self.statement(init)?;
+
+                let body_block = self.new_block(body_label.clone());
+                let check = self.new_block(end_label.clone());
+                let exit = self.new_block(format!("{}_exit", end_label));
+
+                self.terminate(Terminator::Jump(check));
+
+                self.start(body_block);
+                self.block(body)?;
+                self.statement(var_change)?;
+
+                self.start(check);
+                self.branch(condition, body_block, exit)?;
+
+                self.start(exit);
+                self.scopes.pop();
+            }
+
+            StatementKind::If {
+                label,
+                condition,
+                body,
+                else_body,
+            } => {
+                let then = self.new_block(format!("{}_then", label));
+
+                match else_body {
+                    Some(else_body) => {
+                        let otherwise = self.new_block(label.clone());
+                        let end = self.new_block(format!("{}_end", label));
+
+                        self.branch(condition, then, otherwise)?;
+
+                        self.start(then);
+                        self.block(body)?;
+                        self.terminate(Terminator::Jump(end));
+
+                        self.start(otherwise);
+                        self.block(else_body)?;
+
+                        self.start(end);
+                    }
+
+                    None => {
+                        let end = self.new_block(label.clone());
+
+                        self.branch(condition, then, end)?;
+
+                        self.start(then);
+                        self.block(body)?;
+
+                        self.start(end);
+                    }
+                }
+            }
+
+            StatementKind::Return { value } => {
+                let value = match value.kind {
+                    ExprKind::Empty => None,
+                    _ => Some(self.value(value)?.0),
+                };
+
+                self.terminate(Terminator::Return(value));
+
+                // Anything after a return still has to go somewhere,
+                // finish throws it away
+                let dead = self.new_label("dead");
+                let dead = self.new_block(dead);
+                self.start(dead);
+            }
+
+            StatementKind::Struct { name, .. } => {
+                return Err(self.error(format!(
+                    "Struct {} can't be declared inside a function",
+                    name
+                )));
+            }
+
+            StatementKind::Function { name, .. } => {
+                return Err(self.error(format!(
+                    "Function {} can't be declared inside another function",
+                    name
+                )));
+            }
+
+            StatementKind::DataDeclaration { .. } => {
+                return Err(
+                    self.error("Data declarations only belong in the data segment".to_string())
+                );
+            }
+        }
+
+        self.span = outer_span;
+        diagnostic::set_current_span(&self.span);
+
+        Ok(())
+    }
+
+    // Statements in a scope of their own
+    fn block(&mut self, body: &[Statement]) -> Result<()> {
+        self.scopes.push(HashMap::new());
+
+        for stmt in body {
+            self.statement(stmt)?;
+        }
+
+        self.scopes.pop();
+        Ok(())
+    }
+
+    // The initialiser is worked out before the variable exists, so an outer
+    // variable with the same name is still the one being read
+    fn declare(&mut self, var_type: &Type, identifier: &str, operation: &Expr) -> Result<()> {
+        // The pointer comes first and the elements right after it
+        if let ExprKind::ArrayInitializer { body, size } = &operation.kind {
+            if *var_type != Type::pointer_to(Type::Int32) {
+                return Err(self.error(format!(
+                    "Array {} must be declared with a pointer type, not {:?}",
+                    identifier, var_type
+                )));
+            }
+
+            let slot = self.slot(identifier, 4 + 4 * size);
+
+            for i in 0..*size {
+                let value = match body.get(i) {
+                    Some(element) => self.operand(element)?.0,
+                    None => Operand::Const(0),
+                };
+
+                self.emit(Inst::Store {
+                    width: Width::Word,
+                    value,
+                    base: Base::Slot(slot),
+                    offset: 4 + 4 * i as i32,
+                });
+            }
+
+            let first = self.temp();
+            self.emit(Inst::Address {
+                dest: first,
+                base: Base::Slot(slot),
+                offset: 4,
+            });
+            self.emit(Inst::Store {
+                width: Width::Word,
+                value: Operand::Temp(first),
+                base: Base::Slot(slot),
+                offset: 0,
+            });
+
+            // A new declaration hides whatever length an outer one had
+            let length = (!self.reassigned.contains(identifier)).then_some(*size);
+            self.bind(identifier, Base::Slot(slot), var_type, length);
+
+            return Ok(());
+        }
+
+        // Structs and arrays can't be copied so there's never a value
+        // to store, just clear every word of them
+        if let Type::Struct(_) | Type::Array(..) = var_type {
+            let size = self.size_of(var_type).next_multiple_of(4);
+            let slot = self.slot(identifier, size);
+
+            self.zero_fill(slot, size);
+            self.bind(identifier, Base::Slot(slot), var_type, None);
+
+            return Ok(());
+        }
+
+        // Empty gives 0, so a plain declaration is zeroed
+        let (value, _) = self.operand(operation)?;
+        let slot = self.slot(identifier, 4);
+
+        self.store(
+            value,
+            Place {
+                base: Base::Slot(slot),
+                offset: 0,
+                typ: var_type.clone(),
+            },
+        );
+        self.bind(identifier, Base::Slot(slot), var_type, None);
+
+        Ok(())
+    }
+
+    // Small blocks get a store for each word, anything bigger than a
+    // few words gets a loop
+    fn zero_fill(&mut self, slot: SlotId, size: usize) {
+        if size <= 32 {
+            for offset in (0..size).step_by(4) {
+                self.emit(Inst::Store {
+                    width: Width::Word,
+                    value: Operand::Const(0),
+                    base: Base::Slot(slot),
+                    offset: offset as i32,
+                });
+            }
+
+            return;
+        }
+
+        let current = self.temp();
+        let end = self.temp();
+
+        self.emit(Inst::Address {
+            dest: current,
+            base: Base::Slot(slot),
+            offset: 0,
+        });
+        self.emit(Inst::Address {
+            dest: end,
+            base: Base::Slot(slot),
+            offset: size as i32,
+        });
+
+        let label = self.new_label("zero");
+        let body = self.new_block(label.clone());
+        let exit = self.new_block(format!("{}_end", label));
+
+        self.start(body);
+
+        self.emit(Inst::Store {
+            width: Width::Word,
+            value: Operand::Const(0),
+            base: Base::Temp(current),
+            offset: 0,
+        });
+        self.emit(Inst::Binary {
+            dest: current,
+            op: BinaryOp::Add,
+            left: current,
+            right: Operand::Const(4),
+        });
+
+        self.terminate(Terminator::Branch {
+            condition: Condition::Less,
+            left: current,
+            right: Operand::Temp(end),
+            then: body,
+            otherwise: exit,
+        });
+
+        self.start(exit);
+    }
+
+    // Any expression into a fresh temp, along with its type
+    fn value(&mut self, expr: &Expr) -> Result<(Temp, Type)> {
+        if let Some((value, typ)) = constant(expr) {
+            let dest = self.temp();
+            self.emit(Inst::Const { dest, value });
+            return Ok((dest, typ));
+        }
+
+        let result = match &expr.kind {
+            ExprKind::StringLiteral(s) => {
+                let Some(label) = self.strings.get(s).cloned() else {
+                    return Err(self.error(format!(
+                        "String literal \"{}\" not found in data segment",
+                        s
+                    )));
+                };
+
+                let dest = self.temp();
+                self.emit(Inst::Address {
+                    dest,
+                    base: Base::Label(label),
+                    offset: 0,
+                });
+                (dest, Type::String)
+            }
+
+            ExprKind::IdentifierReference(name) => {
+                let variable = self.variable(name)?;
+                let dest = self.temp();
+
+                self.emit(Inst::Address {
+                    dest,
+                    base: variable.base,
+                    offset: 0,
+                });
+                (dest, Type::pointer_to(variable.typ))
+            }
+
+            ExprKind::Identifier(_)
+            | ExprKind::IdentifierDereference(_)
+            | ExprKind::ArrayIndex { .. }
+            | ExprKind::FieldAccess { .. } => {
+                let place = self.place(expr)?;
+                let typ = place.typ.clone();
+                (self.load(place), typ)
+            }
+
+            ExprKind::FunctionCall {
+                function_name,
+                arguments,
+                builtin_function_type,
+                ..
+            } => {
+                let (dest, typ) =
+                    self.call(function_name, arguments, builtin_function_type, true)?;
+
+                // Always there when asked for
+                match dest {
+                    Some(dest) => (dest, typ),
+                    None => return Err(self.error(format!("{} has no value", function_name))),
+                }
+            }
+
+            ExprKind::UnaryOp { operator, operand } => {
+                let (src, _) = self.value(operand)?;
+                let dest = self.temp();
+
+                let (op, typ) = match operator {
+                    UnaryOperator::Negate => (UnaryOp::Negate, Type::Int32),
+                    UnaryOperator::Not => (UnaryOp::Not, Type::Bool),
+                    UnaryOperator::BitwiseNot => (UnaryOp::BitwiseNot, Type::Int32),
+                };
+
+                self.emit(Inst::Unary { dest, op, src });
+                (dest, typ)
+            }
+
+            // 0 unless the whole thing turns out true, the right side only
+            // runs when the left didn't already decide it
+            ExprKind::BinaryOp {
+                operator: operator @ (BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr),
+                ..
+            } => {
+                let dest = self.temp();
+                self.emit(Inst::Const { dest, value: 0 });
+
+                let label = self.new_label(match operator {
+                    BinaryOperator::LogicalAnd => "and",
+                    _ => "or",
+                });
+                let yes = self.new_block(format!("{}_true", label));
+                let end = self.new_block(format!("{}_end", label));
+
+                self.branch(expr, yes, end)?;
+
+                self.start(yes);
+                self.emit(Inst::Const { dest, value: 1 });
+
+                self.start(end);
+                (dest, Type::Bool)
+            }
+
+            ExprKind::BinaryOp {
+                left,
+                operator,
+                right,
+            } => self.binary(left, operator, right)?,
+
+            ExprKind::ArrayInitializer { .. } => {
+                return Err(CompileError::CodeGenError {
+                    message: "Array initializers are only allowed in declarations".to_string(),
+                    span: expr.span.clone(),
+                });
+            }
+
+            ExprKind::Integer(_)
+            | ExprKind::BoolLiteral(_)
+            | ExprKind::CharLiteral(_)
+            | ExprKind::Empty => unreachable!(),
+        };
+
+        Ok(result)
+    }
+
+    // Like value, but a literal stays a constant for the backend to
+    // fold into whatever uses it
+    fn operand(&mut self, expr: &Expr) -> Result<(Operand, Type)> {
+        match constant(expr) {
+            Some((value, typ)) => Ok((Operand::Const(value), typ)),
+            None => {
+                let (temp, typ) = self.value(expr)?;
+                Ok((Operand::Temp(temp), typ))
+            }
+        }
+    }
+
+    // Add, sub and mul trap on overflow, and pointers move a whole element at a time
+    fn binary(
+        &mut self,
+        left: &Expr,
+        operator: &BinaryOperator,
+        right: &Expr,
+    ) -> Result<(Temp, Type)> {
+        let op = match operator {
+            BinaryOperator::Add => BinaryOp::Add,
+            BinaryOperator::Subtract => BinaryOp::Subtract,
+            BinaryOperator::Multiply => BinaryOp::Multiply,
+            BinaryOperator::Divide => BinaryOp::Divide,
+            BinaryOperator::Modulo => BinaryOp::Modulo,
+            BinaryOperator::BitwiseAnd => BinaryOp::BitwiseAnd,
+            BinaryOperator::BitwiseOr => BinaryOp::BitwiseOr,
+            BinaryOperator::BitwiseXor => BinaryOp::BitwiseXor,
+            BinaryOperator::ShiftLeft => BinaryOp::ShiftLeft,
+            BinaryOperator::ShiftRight => BinaryOp::ShiftRight,
+
+            BinaryOperator::LessThan
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::LessEqual
+            | BinaryOperator::GreaterEqual
+            | BinaryOperator::Equal
+            | BinaryOperator::NotEqual => BinaryOp::Compare(comparison(operator)),
+
+            BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr => unreachable!(),
+        };
+
+        // A constant can only go on the right, so 1 + x is worked out as x + 1
+        let swap = constant(left).is_some()
+            && constant(right).is_none()
+            && matches!(
+                op,
+                BinaryOp::Add
+                    | BinaryOp::Multiply
+                    | BinaryOp::BitwiseAnd
+                    | BinaryOp::BitwiseOr
+                    | BinaryOp::BitwiseXor
+                    | BinaryOp::Compare(_)
+            );
+
+        let (op, left, right) = match (swap, op) {
+            (true, BinaryOp::Compare(condition)) => {
+                (BinaryOp::Compare(condition.mirrored()), right, left)
+            }
+            (true, op) => (op, right, left),
+            (false, op) => (op, left, right),
+        };
+
+        let (mut left, left_type) = self.value(left)?;
+        let (mut right, right_type) = self.operand(right)?;
+
+        let typ = match (op, &left_type, &right_type) {
+            (BinaryOp::Add | BinaryOp::Subtract, Type::Pointer(pointee), _) => {
+                let size = self.size_of(pointee);
+                right = self.scale(right, size);
+                left_type.clone()
+            }
+
+            (BinaryOp::Add, _, Type::Pointer(pointee)) => {
+                let size = self.size_of(pointee);
+                left = match self.scale(Operand::Temp(left), size) {
+                    Operand::Temp(temp) => temp,
+                    Operand::Const(_) => unreachable!(),
+                };
+                right_type.clone()
+            }
+
+            (BinaryOp::Compare(_), _, _) => Type::Bool,
+            _ => Type::Int32,
+        };
+
+        let dest = self.temp();
+        self.emit(Inst::Binary {
+            dest,
+            op,
+            left,
+            right,
+        });
+
+        Ok((dest, typ))
+    }
+
+    // value times size, without trapping
+    fn scale(&mut self, value: Operand, size: usize) -> Operand {
+        match value {
+            _ if size == 1 => value,
+            Operand::Const(n) => Operand::Const(n.wrapping_mul(size as i32)),
+            Operand::Temp(temp) => {
+                let dest = self.temp();
+                self.emit(Inst::Binary {
+                    dest,
+                    op: BinaryOp::Scale,
+                    left: temp,
+                    right: Operand::Const(size as i32),
+                });
+                Operand::Temp(dest)
+            }
+        }
+    }
+
+    // Ends the current block by going to then when the condition holds and
+    // to otherwise when it doesn't. && and || only look at the right side
+    // when they have to, and never turn anything into a 0 or 1 on the way
+    fn branch(&mut self, condition: &Expr, then: BlockId, otherwise: BlockId) -> Result<()> {
+        match &condition.kind {
+            ExprKind::UnaryOp {
+                operator: UnaryOperator::Not,
+                operand,
+            } => self.branch(operand, otherwise, then),
+
+            ExprKind::BinaryOp {
+                left,
+                operator: operator @ (BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr),
+                right,
+            } => {
+                let is_and = *operator == BinaryOperator::LogicalAnd;
+
+                let label = self.new_label(if is_and { "and" } else { "or" });
+                let rest = self.new_block(format!("{}_right", label));
+
+                if is_and {
+                    self.branch(left, rest, otherwise)?;
+                } else {
+                    self.branch(left, then, rest)?;
+                }
+
+                self.start(rest);
+                self.branch(right, then, otherwise)
+            }
+
+            ExprKind::BinaryOp {
+                left,
+                operator:
+                    operator @ (BinaryOperator::LessThan
+                    | BinaryOperator::LessEqual
+                    | BinaryOperator::GreaterThan
+                    | BinaryOperator::GreaterEqual
+                    | BinaryOperator::Equal
+                    | BinaryOperator::NotEqual),
+                right,
+            } => {
+                let condition = comparison(operator);
+
+                let (condition, left, right) =
+                    if constant(left).is_some() && constant(right).is_none() {
+                        (condition.mirrored(), right, left)
+                    } else {
+                        (condition, left, right)
+                    };
+
+                let (left, _) = self.value(left)?;
+                let (right, _) = self.operand(right)?;
+
+                self.terminate(Terminator::Branch {
+                    condition,
+                    left,
+                    right,
+                    then,
+                    otherwise,
+                });
+                Ok(())
+            }
+
+            _ => {
+                let (value, _) = self.value(condition)?;
+
+                self.terminate(Terminator::Branch {
+                    condition: Condition::NotEqual,
+                    left: value,
+                    right: Operand::Const(0),
+                    then,
+                    otherwise,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    // wants_value is false for a call on its own as a statement, whatever
+    // it hands back isn't kept
+    fn call(
+        &mut self,
+        function_name: &str,
+        arguments: &[Expr],
+        builtin: &Option<BuiltinFunctionType>,
+        wants_value: bool,
+    ) -> Result<(Option<Temp>, Type)> {
+        let mut args = Vec::new();
+
+        for argument in arguments {
+            args.push(self.value(argument)?.0);
+        }
+
+        let dest = wants_value.then(|| self.temp());
+
+        let typ = match builtin {
+            Some(builtin) => {
+                self.emit(Inst::Builtin {
+                    dest,
+                    builtin: builtin.clone(),
+                    args,
+                });
+
+                builtin_type(builtin)
+            }
+
+            None => {
+                self.emit(Inst::Call {
+                    dest,
+                    function: function_name.to_string(),
+                    args,
+                });
+
+                self.function_types
+                    .get(function_name)
+                    .cloned()
+                    .unwrap_or(Type::Void)
+            }
+        };
+
+        Ok((dest, typ))
+    }
+
+    // Where an assignable expression lives. A field of something that's
+    // already a place just moves the offset along
+    fn place(&mut self, expr: &Expr) -> Result<Place> {
+        match &expr.kind {
+            ExprKind::Identifier(name) => {
+                let variable = self.variable(name)?;
+
+                Ok(Place {
+                    base: variable.base,
+                    offset: 0,
+                    typ: variable.typ,
+                })
+            }
+
+            // The pointer itself is the address
+            ExprKind::IdentifierDereference(name) => {
+                let variable = self.variable(name)?;
+                let typ = pointee(&variable.typ);
+
+                let pointer = self.load(Place {
+                    base: variable.base,
+                    offset: 0,
+                    typ: variable.typ,
+                });
+
+                Ok(Place {
+                    base: Base::Temp(pointer),
+                    offset: 0,
+                    typ,
+                })
+            }
+
+            // Arrays are already where they are, so their value is their
+            // address. Anything else is a pointer or string that holds it
+            ExprKind::ArrayIndex { array, indexer } => {
+                let length = match &array.kind {
+                    ExprKind::Identifier(name) => self.variable(name)?.length,
+                    _ => None,
+                };
+
+                let (array, typ) = self.value(array)?;
+                self.element(array, &typ, length, indexer)
+            }
+
+            ExprKind::FieldAccess {
+                base,
+                field,
+                through_pointer,
+            } => {
+                let place = if *through_pointer {
+                    let (pointer, typ) = self.value(base)?;
+
+                    Place {
+                        base: Base::Temp(pointer),
+                        offset: 0,
+                        typ: pointee(&typ),
+                    }
+                } else {
+                    self.place(base)?
+                };
+
+                let found = match &place.typ {
+                    Type::Struct(name) => self
+                        .structs
+                        .get(name)
+                        .and_then(|layout| layout.fields.get(field)),
+                    _ => None,
+                };
+
+                let Some((offset, typ)) = found.cloned() else {
+                    return Err(self.error(format!("Unknown field '{}'", field)));
+                };
+
+                Ok(Place {
+                    base: place.base,
+                    offset: place.offset + offset as i32,
+                    typ,
+                })
+            }
+
+            _ => Err(self.error("Can't take the address of this expression".to_string())),
+        }
+    }
+
+    // Where array[indexer] is. array is the address of the first element
+    // and length how many there are, if that's known
+    fn element(
+        &mut self,
+        array: Temp,
+        typ: &Type,
+        length: Option<usize>,
+        indexer: &Expr,
+    ) -> Result<Place> {
+        let (element, length) = match typ {
+            Type::String => (Type::Char, length),
+            Type::Pointer(element) => (element.as_ref().clone(), length),
+            Type::Array(element, n) => (element.as_ref().clone(), Some(*n)),
+            typ => return Err(self.error(format!("{} can't be indexed", typ))),
+        };
+
+        let (index, _) = self.operand(indexer)?;
+
+        if self.bounds_check
+            && let Some(length) = length
+        {
+            self.check_bounds(&index, length, indexer.span.line);
+        }
+
+        let size = self.size_of(&element);
+
+        // A constant index just moves the offset along
+        if let Operand::Const(n) = index {
+            return Ok(Place {
+                base: Base::Temp(array),
+                offset: n.wrapping_mul(size as i32),
+                typ: element,
+            });
+        }
+
+        let offset = self.scale(index, size);
+        let address = self.temp();
+
+        self.emit(Inst::Binary {
+            dest: address,
+            op: BinaryOp::Add,
+            left: array,
+            right: offset,
+        });
+
+        Ok(Place {
+            base: Base::Temp(address),
+            offset: 0,
+            typ: element,
+        })
+    }
+
+    // Carries on unless 0 <= index < length, compared unsigned so a
+    // negative index looks huge and fails too
+    fn check_bounds(&mut self, index: &Operand, length: usize, line: usize) {
+        let index = match index {
+            Operand::Const(n) if *n >= 0 && (*n as usize) < length => return,
+
+            Operand::Const(n) => {
+                let dest = self.temp();
+                self.emit(Inst::Const { dest, value: *n });
+                dest
+            }
+
+            Operand::Temp(temp) => *temp,
+        };
+
+        let label = self.new_label("bounds");
+        let ok = self.new_block(format!("{}_ok", label));
+        let fail = self.new_block(format!("{}_fail", label));
+
+        self.terminate(Terminator::Branch {
+            condition: Condition::Below,
+            left: index,
+            right: Operand::Const(length as i32),
+            then: ok,
+            otherwise: fail,
+        });
+
+        self.start(fail);
+        self.terminate(Terminator::OutOfBounds { line });
+
+        self.start(ok);
+    }
+
+    // The value at place. Structs and arrays never fit in a register,
+    // so theirs is their address
+    fn load(&mut self, place: Place) -> Temp {
+        if let Type::Struct(_) | Type::Array(..) = place.typ {
+            if let (Base::Temp(address), 0) = (&place.base, place.offset) {
+                return *address;
+            }
+
+            let dest = self.temp();
+            self.emit(Inst::Address {
+                dest,
+                base: place.base,
+                offset: place.offset,
+            });
+            return dest;
+        }
+
+        let dest = self.temp();
+        self.emit(Inst::Load {
+            dest,
+            width: width(&place.typ),
+            base: place.base,
+            offset: place.offset,
+        });
+        dest
+    }
+
+    fn store(&mut self, value: Operand, place: Place) {
+        self.emit(Inst::Store {
+            width: width(&place.typ),
+            value,
+            base: place.base,
+            offset: place.offset,
+        });
+    }
+
+    // Locals shadow globals
+    fn variable(&self, name: &str) -> Result<Variable> {
+        self.scopes
+            .iter()
+            .rev()
+            .filter_map(|scope| scope.get(name))
+            .chain(self.globals.get(name))
+            .next()
+            .cloned()
+            .ok_or_else(|| self.error(format!("Use of undeclared variable '{}'", name)))
+    }
+
+    fn bind(&mut self, name: &str, base: Base, typ: &Type, length: Option<usize>) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(
+                name.to_string(),
+                Variable {
+                    base,
+                    typ: typ.clone(),
+                    length,
+                },
+            );
+        }
+    }
+
+    fn temp(&mut self) -> Temp {
+        self.temps += 1;
+        Temp(self.temps - 1)
+    }
+
+    fn slot(&mut self, name: &str, size: usize) -> SlotId {
+        self.slots.push(Slot {
+            name: name.to_string(),
+            size: size.next_multiple_of(4).max(4),
+        });
+        SlotId(self.slots.len() - 1)
+    }
+
+    fn new_label(&mut self, prefix: &str) -> String {
+        self.num_labels += 1;
+        format!("{}_{}", prefix, self.num_labels - 1)
+    }
+
+    // Made but not laid out until it's started
+    fn new_block(&mut self, label: String) -> BlockId {
+        self.blocks.push(Block {
+            label,
+            instructions: Vec::new(),
+            terminator: Terminator::Unreachable,
+        });
+        BlockId(self.blocks.len() - 1)
+    }
+
+    // Starting a block while the current one is still open falls through into it
+    fn start(&mut self, block: BlockId) {
+        if self.open {
+            self.terminate(Terminator::Jump(block));
+        }
+
+        self.current = block;
+        self.order.push(block);
+        self.open = true;
+    }
+
+    fn terminate(&mut self, terminator: Terminator) {
+        self.blocks[self.current.0].terminator = terminator;
+        self.open = false;
+    }
+
+    fn emit(&mut self, inst: Inst) {
+        self.blocks[self.current.0].instructions.push(inst);
+    }
+
+    fn size_of(&self, typ: &Type) -> usize {
+        size_of(typ, &self.structs)
+    }
+
+    fn error(&self, message: String) -> CompileError {
+        CompileError::CodeGenError {
+            message,
+            span: self.span.clone(),
+        }
+    }
+}
+
+// Literals, which never need working out at runtime
+fn constant(expr: &Expr) -> Option<(i32, Type)> {
+    match &expr.kind {
+        ExprKind::Integer(n) => Some((*n, Type::Int32)),
+        ExprKind::BoolLiteral(b) => Some((*b as i32, Type::Bool)),
+        ExprKind::CharLiteral(c) => Some((*c as i32, Type::Char)),
+        ExprKind::Empty => Some((0, Type::Void)),
+        _ => None,
+    }
+}
+
+fn comparison(operator: &BinaryOperator) -> Condition {
+    match operator {
+        BinaryOperator::LessThan => Condition::Less,
+        BinaryOperator::LessEqual => Condition::LessEqual,
+        BinaryOperator::GreaterThan => Condition::Greater,
+        BinaryOperator::GreaterEqual => Condition::GreaterEqual,
+        BinaryOperator::Equal => Condition::Equal,
+        _ => Condition::NotEqual,
+    }
+}
+
+// A char only takes up one byte of its slot
+fn width(typ: &Type) -> Width {
+    match typ {
+        Type::Char => Width::Byte,
+        _ => Width::Word,
+    }
+}
+
+// What dereferencing a value of type typ reaches, the semantic pass made
+// sure there's always something
+fn pointee(typ: &Type) -> Type {
+    match typ {
+        Type::Pointer(pointee) => pointee.as_ref().clone(),
+        _ => Type::Void,
+    }
+}
+
+fn builtin_type(builtin: &BuiltinFunctionType) -> Type {
+    match builtin {
+        BuiltinFunctionType::IntegerRead | BuiltinFunctionType::IntegerRandomRange => Type::Int32,
+        BuiltinFunctionType::StringRead => Type::String,
+        BuiltinFunctionType::CharRead => Type::Char,
+        BuiltinFunctionType::Allocate => Type::pointer_to(Type::Void),
+        BuiltinFunctionType::IntegerPrint
+        | BuiltinFunctionType::StringPrint
+        | BuiltinFunctionType::CharPrint => Type::Void,
+    }
+}
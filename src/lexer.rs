@@ -1,41 +1,93 @@
+mod span;
 mod token;
 
+use std::rc::Rc;
+
 use crate::error::{CompileError, Result};
-pub use token::Token;
+pub use span::Span;
+pub use token::{SpannedToken, Token};
 
 pub struct Lexer {
     source: Vec<char>,
+    file: Rc<str>,
     line: usize,
+    column: usize,
     current: usize,
+
+    // current indexes chars, this is the matching byte offset for spans
+    byte: usize,
+
+    // Where the token being scanned started
+    token_start: Span,
 }
 
 impl Lexer {
-    pub fn new(source: &str) -> Self {
+    pub fn new(source: &str, file: &str) -> Self {
+        let file: Rc<str> = Rc::from(file);
+
         Lexer {
             source: source.chars().collect(),
+            file: file.clone(),
             line: 1,
+            column: 1,
             current: 0,
+            byte: 0,
+            token_start: Span {
+                file,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 0,
+            },
         }
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Token>> {
+    pub fn tokenize(&mut self) -> Result<Vec<SpannedToken>> {
         let mut tokens = Vec::new();
 
-        while !self.is_at_end() {
-            self.skip_comments();
-            self.skip_whitespace();
+        loop {
+            self.skip_whitespace_and_comments();
+            self.token_start = self.position();
 
             if self.is_at_end() {
                 break;
             }
 
-            tokens.push(self.next_token()?);
+            let token = self.next_token()?;
+
+            tokens.push(SpannedToken {
+                token,
+                span: self.token_span(),
+            });
         }
 
-        tokens.push(Token::Eof);
+        tokens.push(SpannedToken {
+            token: Token::Eof,
+            span: self.token_span(),
+        });
+
         Ok(tokens)
     }
 
+    // An empty span at the current position
+    fn position(&self) -> Span {
+        Span {
+            file: self.file.clone(),
+            line: self.line,
+            column: self.column,
+            start: self.byte,
+            end: self.byte,
+        }
+    }
+
+    // From the start of the current token up to what has been consumed so far
+    fn token_span(&self) -> Span {
+        Span {
+            end: self.byte,
+            ..self.token_start.clone()
+        }
+    }
+
     fn next_token(&mut self) -> Result<Token> {
         let ch = self.peek();
 
@@ -91,22 +143,47 @@ impl Lexer {
                 Ok(Token::Star)
             }
 
+            // Comments are skipped before a token starts, so this is always division
+            '/' => {
+                self.advance();
+                Ok(Token::Slash)
+            }
+
+            '%' => {
+                self.advance();
+                Ok(Token::Percent)
+            }
+
             '=' => self.scan_equal(),
             '!' => self.scan_not(),
 
-            '&' => {
+            '&' => self.scan_ampersand(),
+            '|' => self.scan_pipe(),
+
+            '^' => {
                 self.advance();
-                Ok(Token::Ampersand)
+                Ok(Token::Caret)
             }
 
-            '0'..='9' => self.scan_number(false),
+            '~' => {
+                self.advance();
+                Ok(Token::Tilde)
+            }
+
+            '.' => {
+                self.advance();
+                Ok(Token::Dot)
+            }
+
+            '0'..='9' => self.scan_number(),
             'a'..='z' | 'A'..='Z' | '_' => self.scan_identifier(),
             '"' => self.scan_string_literal(),
+            '\'' => self.scan_char_literal(),
 
             // Unknown character
             _ => Err(CompileError::LexError {
                 message: format!("Unexpected character '{}'", ch),
-                line: self.line,
+                span: self.token_span(),
             }),
         }
     }
@@ -131,6 +208,8 @@ impl Lexer {
             "int32" => Token::Int32,
             "bool" => Token::Bool,
             "string" => Token::String,
+            "char" => Token::Char,
+            "struct" => Token::Struct,
             "if" => Token::If,
             "true" => Token::BoolLiteral(true),
             "false" => Token::BoolLiteral(false),
@@ -144,7 +223,7 @@ impl Lexer {
         Ok(token)
     }
 
-    fn scan_number(&mut self, is_negative: bool) -> Result<Token> {
+    fn scan_number(&mut self) -> Result<Token> {
         let start = self.current;
 
         while !self.is_at_end() && self.peek().is_ascii_digit() {
@@ -165,46 +244,141 @@ impl Lexer {
 
         let text: String = self.source[start..self.current].iter().collect();
 
-        let mut value: i32 = if is_hex {
-            i32::from_str_radix(&text[2..], 16).map_err(|_| CompileError::LexError {
+        // Hex is a bit pattern so 0xFFFFFFFF is -1, but a decimal literal
+        // has to fit in an int32. -2147483648 is -2147483647 - 1, like C
+        let value: i32 = if is_hex {
+            u32::from_str_radix(&text[2..], 16).map_err(|_| CompileError::LexError {
                 message: format!("Invalid hex number: {}", text),
-                line: self.line,
-            })?
+                span: self.token_span(),
+            })? as i32
         } else {
             text.parse::<i32>().map_err(|_| CompileError::LexError {
-                message: format!("Invalid number: {}", text),
-                line: self.line,
+                message: format!("Number {} doesn't fit in an int32", text),
+                span: self.token_span(),
             })?
         };
 
-        if is_negative {
-            value *= -1;
-        }
-
         Ok(Token::Integer(value))
     }
 
     fn scan_string_literal(&mut self) -> Result<Token> {
         self.advance();
-        let start = self.current;
+        let mut text = String::new();
 
         while !self.is_at_end() && self.peek() != '"' {
-            self.advance();
+            if self.peek() == '\\' {
+                text.push(self.scan_escape()?);
+            } else {
+                text.push(self.advance());
+            }
         }
 
         if self.is_at_end() {
             return Err(CompileError::LexError {
                 message: "Unterminated string literal".to_string(),
-                line: self.line,
+                span: self.token_span(),
             });
         }
 
-        let text: String = self.source[start..self.current].iter().collect();
         self.advance();
 
         Ok(Token::StringLiteral(text))
     }
 
+    fn scan_char_literal(&mut self) -> Result<Token> {
+        self.advance();
+
+        let ch = match self.peek() {
+            '\\' => self.scan_escape()?,
+
+            '\'' => {
+                self.advance();
+                return Err(CompileError::LexError {
+                    message: "Empty character literal".to_string(),
+                    span: self.token_span(),
+                });
+            }
+
+            _ if self.is_at_end() => '\0',
+            _ => self.advance(),
+        };
+
+        if self.is_at_end() || self.peek() != '\'' {
+            return Err(CompileError::LexError {
+                message: "Expected ' to close the character literal".to_string(),
+                span: self.token_span(),
+            });
+        }
+
+        self.advance();
+
+        // A char is a single byte
+        if !ch.is_ascii() {
+            return Err(CompileError::LexError {
+                message: format!("Character literal '{}' isn't ASCII", ch),
+                span: self.token_span(),
+            });
+        }
+
+        Ok(Token::CharLiteral(ch))
+    }
+
+    // Expects to be on the backslash, returns the character it stands for
+    fn scan_escape(&mut self) -> Result<char> {
+        let start = self.position();
+        self.advance();
+
+        // Let the caller report the missing quote
+        if self.is_at_end() {
+            return Ok('\\');
+        }
+
+        let ch = match self.advance() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+
+            'x' => {
+                let mut digits = String::new();
+
+                while digits.len() < 2 && self.peek().is_ascii_hexdigit() {
+                    digits.push(self.advance());
+                }
+
+                // Strings are text, a byte above 0x7f isn't a character on its own
+                match u8::from_str_radix(&digits, 16) {
+                    Ok(n) if digits.len() == 2 && n.is_ascii() => n as char,
+                    _ => {
+                        return Err(CompileError::LexError {
+                            message: "\\x escapes need two hex digits between 00 and 7f"
+                                .to_string(),
+                            span: Span {
+                                end: self.byte,
+                                ..start
+                            },
+                        });
+                    }
+                }
+            }
+
+            other => {
+                return Err(CompileError::LexError {
+                    message: format!("Unknown escape sequence '\\{}'", other),
+                    span: Span {
+                        end: self.byte,
+                        ..start
+                    },
+                });
+            }
+        };
+
+        Ok(ch)
+    }
+
     fn scan_equal(&mut self) -> Result<Token> {
         self.advance();
 
@@ -261,12 +435,45 @@ impl Lexer {
                 Ok(Token::MinusMinus)
             }
 
-            '0'..='9' => self.scan_number(true),
+            '>' => {
+                self.advance();
+                Ok(Token::Arrow)
+            }
 
             _ => Ok(Token::Minus),
         }
     }
 
+    fn scan_ampersand(&mut self) -> Result<Token> {
+        self.advance();
+
+        let ch = self.peek();
+
+        match ch {
+            '&' => {
+                self.advance();
+                Ok(Token::AmpersandAmpersand)
+            }
+
+            _ => Ok(Token::Ampersand),
+        }
+    }
+
+    fn scan_pipe(&mut self) -> Result<Token> {
+        self.advance();
+
+        let ch = self.peek();
+
+        match ch {
+            '|' => {
+                self.advance();
+                Ok(Token::PipePipe)
+            }
+
+            _ => Ok(Token::Pipe),
+        }
+    }
+
     fn scan_less_than(&mut self) -> Result<Token> {
         self.advance();
 
@@ -307,38 +514,24 @@ impl Lexer {
         }
     }
 
-    fn skip_whitespace(&mut self) {
+    fn skip_whitespace_and_comments(&mut self) {
         while !self.is_at_end() {
             match self.peek() {
-                ' ' | '\t' | '\r' => {
-                    self.advance();
-                }
-                '\n' => {
-                    self.line += 1;
+                ' ' | '\t' | '\r' | '\n' => {
                     self.advance();
                 }
-                _ => break,
-            }
-        }
-    }
 
-    // Not proud of this implementation
-    fn skip_comments(&mut self) {
-        let mut new_source: Vec<char> = Vec::with_capacity(self.source.len());
-        let mut i = 0;
-        while i < self.source.len() {
-            if i + 1 < self.source.len() && self.source[i] == '/' && self.source[i + 1] == '/' {
-                let mut j = i + 2;
-                while j < self.source.len() && self.source[j] != '\n' {
-                    j += 1;
+                // Skipped in place rather than stripped up front so spans
+                // still line up with the original source
+                '/' if self.peek_next() == '/' => {
+                    while !self.is_at_end() && self.peek() != '\n' {
+                        self.advance();
+                    }
                 }
-                i = j;
-            } else {
-                new_source.push(self.source[i]);
-                i += 1;
+
+                _ => break,
             }
         }
-        self.source = new_source;
     }
 
     fn peek(&self) -> char {
@@ -349,9 +542,25 @@ impl Lexer {
         }
     }
 
+    fn peek_next(&self) -> char {
+        match self.source.get(self.current + 1) {
+            Some(ch) => *ch,
+            None => '\0',
+        }
+    }
+
     fn advance(&mut self) -> char {
         let ch = self.peek();
         self.current += 1;
+        self.byte += ch.len_utf8();
+
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
         ch
     }
 
@@ -0,0 +1,31 @@
+use std::fmt;
+use std::rc::Rc;
+
+// start and end are byte offsets into the source, end is exclusive
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Span {
+    pub file: Rc<str>,
+    pub line: usize,
+    pub column: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    // Covers everything from the start of self to the end of other
+    pub fn to(&self, other: &Span) -> Span {
+        Span {
+            file: self.file.clone(),
+            line: self.line,
+            column: self.column,
+            start: self.start,
+            end: other.end.max(self.end),
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
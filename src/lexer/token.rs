@@ -1,3 +1,5 @@
+use super::Span;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     // Keywords
@@ -5,6 +7,8 @@ pub enum Token {
     Int32,
     Bool,
     String,
+    Char,
+    Struct,
     If,
     Else,
     While,
@@ -15,6 +19,7 @@ pub enum Token {
     Integer(i32),
     StringLiteral(String),
     BoolLiteral(bool),
+    CharLiteral(char),
     
     // Identifier (this comment is somewhat redundant)
     Identifier(String),
@@ -31,7 +36,16 @@ pub enum Token {
     Plus,
     Minus,
     Star,
+    Slash,
+    Percent,
     Ampersand,
+    AmpersandAmpersand,
+    Pipe,
+    PipePipe,
+    Caret,
+    Tilde,
+    Dot,
+    Arrow,
 
     // Almost operators but not really
     PlusPlus,
@@ -52,4 +66,10 @@ pub enum Token {
     // Other
     Eof,
     //NewLine,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SpannedToken {
+    pub token: Token,
+    pub span: Span,
+}
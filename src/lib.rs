@@ -0,0 +1,13 @@
+// Everything but the command line lives here so tests/ can drive the
+// compiler, simulator and interpreter directly
+pub mod compiler;
+pub mod diagnostic;
+pub mod error;
+pub mod interp;
+pub mod ir;
+pub mod lexer;
+pub mod mips;
+pub mod parser;
+pub mod rng;
+pub mod semantic;
+pub mod sim;
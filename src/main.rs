@@ -1,48 +1,32 @@
 mod cli;
-mod error;
-mod lexer;
-mod parser;
-mod mips;
-mod compiler;
 
 use std::path::Path;
 
 use clap::{CommandFactory, Parser, error::ErrorKind};
-use cli::Args;
+use cli::{Args, Command, InterpArgs, RunArgs};
 
-use crate::compiler::Compiler;
+use ecc::compiler::Compiler;
+use ecc::diagnostic::{self, Diagnostic};
+use ecc::interp::{self, Interpreter};
+use ecc::rng::Rng;
+use ecc::sim;
 
 fn main() {
     let args = Args::parse();
 
-    let path = Path::new(args.input_file.as_str());
-
-    if !path.exists() {
-        Args::command()
-                .error(ErrorKind::ArgumentConflict, format!("{:?} is not a valid path", path))
-                .exit();
-    }
-
-    if !path.is_file() {
-        Args::command()
-                .error(ErrorKind::ArgumentConflict, format!("{:?} is not a file", path))
-                .exit();
+    match &args.command {
+        Some(Command::Run(run_args)) => run(run_args),
+        Some(Command::Interp(interp_args)) => interp(interp_args),
+        None => {}
     }
 
-    if let Some(extension) = path.extension() {
-        if extension != "ec" {
-            // I really don't like using unwrap, but this is okay for now
-            let file_name = path.file_name().unwrap();
-
-            Args::command()
-                .error(ErrorKind::ArgumentConflict, format!("{} must be an ec file", file_name.display()))
-                .exit();
-        }
-    }
+    // clap won't let there be neither a subcommand nor a file
+    let input_file = args.input_file.as_deref().unwrap_or_default();
+    let source = read_source(input_file);
 
-    let source = std::fs::read_to_string(path).unwrap_or_else(|_| String::from(""));
+    diagnostic::install_panic_hook(&source);
 
-    let mut compiler = Compiler::new(&source);
+    let mut compiler = Compiler::new(&source, &Path::new(input_file).display().to_string());
 
     let output = args.output.as_str();
 
@@ -53,7 +37,8 @@ fn main() {
             }
 
             Err(e) => {
-                eprintln!("Error generating tokens: {}", e);
+                diagnostic::emit_all(&[e], &source, args.max_errors);
+                std::process::exit(1);
             }
         }
     }
@@ -64,19 +49,167 @@ fn main() {
                 println!("{:#?}", ast);
             }
 
-            Err(e) => {
-                eprintln!("Error generating AST: {}", e);
+            Err(errors) => {
+                diagnostic::emit_all(&errors, &source, args.max_errors);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if args.ir {
+        match compiler.get_ir(args.bounds_check) {
+            Ok(module) => {
+                print!("{}", module);
+            }
+
+            Err(errors) => {
+                diagnostic::emit_all(&errors, &source, args.max_errors);
+                std::process::exit(1);
             }
         }
     }
 
-    match compiler.compile(output, args.comment) {
+    match compiler.compile(output, args.comment, args.bounds_check) {
         Ok(_) => {
             println!("Compilation successful!");
         }
 
+        Err(errors) => {
+            diagnostic::emit_all(&errors, &source, args.max_errors);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Makes sure input_file is an .ec file that exists and reads it, anything
+// else is a usage error
+fn read_source(input_file: &str) -> String {
+    let path = Path::new(input_file);
+
+    if !path.exists() {
+        Args::command()
+                .error(ErrorKind::ArgumentConflict, format!("{:?} is not a valid path", path))
+                .exit();
+    }
+
+    if !path.is_file() {
+        Args::command()
+                .error(ErrorKind::ArgumentConflict, format!("{:?} is not a file", path))
+                .exit();
+    }
+
+    if let Some(extension) = path.extension()
+        && extension != "ec"
+    {
+        // I really don't like using unwrap, but this is okay for now
+        let file_name = path.file_name().unwrap();
+
+        Args::command()
+                .error(ErrorKind::ArgumentConflict, format!("{} must be an ec file", file_name.display()))
+                .exit();
+    }
+
+    std::fs::read_to_string(path).unwrap_or_else(|_| String::from(""))
+}
+
+// ecc run, compiles in memory and hands the assembly to the simulator.
+// Exits with whatever code the program did
+fn run(args: &RunArgs) -> ! {
+    let source = read_source(&args.input_file);
+
+    diagnostic::install_panic_hook(&source);
+
+    let mut compiler = Compiler::new(&source, &Path::new(&args.input_file).display().to_string());
+
+    let assembly = match compiler.get_assembly(false, args.bounds_check) {
+        Ok(assembly) => assembly,
+
+        Err(errors) => {
+            diagnostic::emit_all(&errors, &source, args.max_errors);
+            std::process::exit(1);
+        }
+    };
+
+    let rng = match args.seed {
+        Some(seed) => Rng::new(seed),
+        None => Rng::from_clock(),
+    };
+
+    let result = sim::Simulator::new(&assembly, rng).and_then(|mut simulator| {
+        simulator.run(&mut std::io::stdin().lock(), &mut std::io::stdout().lock(), args.max_steps)
+    });
+
+    match result {
+        Ok(code) => std::process::exit(code),
+
         Err(e) => {
-            eprintln!("Error compiling: {}", e);
+            let mut diagnostic = Diagnostic::error(&format!("runtime error: {}", e.message));
+
+            if let Some(line) = e.line
+                && let Some(text) = assembly.lines().nth(line - 1)
+            {
+                diagnostic = diagnostic.with_note(&format!(
+                    "at line {} of the generated assembly: {}",
+                    line,
+                    text.trim()
+                ));
+            }
+
+            diagnostic.emit(&source);
+            std::process::exit(1);
         }
     }
 }
+
+// ecc interp, runs the checked AST directly. Exits with whatever code the
+// program did. Recursion in the program is recursion in the interpreter,
+// so it gets a thread with plenty of stack
+fn interp(args: &InterpArgs) -> ! {
+    let source = read_source(&args.input_file);
+
+    diagnostic::install_panic_hook(&source);
+
+    let mut compiler = Compiler::new(&source, &Path::new(&args.input_file).display().to_string());
+
+    let rng = match args.seed {
+        Some(seed) => Rng::new(seed),
+        None => Rng::from_clock(),
+    };
+
+    let code = std::thread::scope(|scope| {
+        let thread = std::thread::Builder::new()
+            .stack_size(interp::THREAD_STACK_SIZE)
+            .spawn_scoped(scope, || {
+                let program = match compiler.get_checked_ast() {
+                    Ok(program) => program,
+
+                    Err(errors) => {
+                        diagnostic::emit_all(&errors, &source, args.max_errors);
+                        return 1;
+                    }
+                };
+
+                let mut input = std::io::stdin().lock();
+                let mut output = std::io::stdout().lock();
+
+                let result = Interpreter::new(&program, rng, &mut input, &mut output).run(args.max_steps);
+
+                match result {
+                    Ok(code) => code,
+
+                    Err(e) => {
+                        Diagnostic::error(&format!("runtime error: {}", e.message))
+                            .with_span(&e.span)
+                            .emit(&source);
+                        1
+                    }
+                }
+            })
+            .unwrap_or_else(|e| panic!("Couldn't start the interpreter: {}", e));
+
+        // A panic in there has already been reported by the hook
+        thread.join().unwrap_or(101)
+    });
+
+    std::process::exit(code);
+}
@@ -4,47 +4,143 @@ mod allocator;
 // This might just be the messiest file
 // But that's fine because I'm awesome
 
+use std::collections::HashSet;
+
 use crate::{
-    error::CompileError,
-    mips::allocator::{Allocator, Register, VariableLocation},
-    parser::ast::{
-        Argument, BinaryOperator, BitwiseShiftType, BuiltinFunctionType, DataStorageType, Expr,
-        Program, Statement, Type,
+    ir::{
+        Base, BinaryOp, Block, Condition, Function, Inst, Module, Operand, Temp, Terminator,
+        UnaryOp, Width,
     },
+    lexer::Span,
+    mips::allocator::{Allocation, Location, Register, SCRATCH},
+    parser::ast::{BuiltinFunctionType, DataStorageType, DataValue, Statement, StatementKind},
 };
 
-pub struct MipsGenerator {
-    program: Program,
+// Picks MIPS instructions for each IR instruction, once the allocator
+// has decided where every temp lives
+pub struct MipsGenerator<'a> {
+    module: &'a Module,
     generated: String,
-    line: usize,
     emit_comments: bool,
+
+    // The IR instruction being selected, the first line it turns into
+    // gets it as a comment
+    comment: Option<String>,
+
+    // The function being generated
+    allocation: Allocation,
+    frame: Frame,
 }
 
-impl MipsGenerator {
-    pub fn new(program: Program, emit_comments: bool) -> Self {
+// Where everything sits in a function's frame, as offsets from $sp. From
+// the bottom, arguments past the fourth for whatever it calls (o32, the
+// callee finds them right above its own frame), $ra, the $s registers it
+// uses, room to keep $t registers across a call, its locals and then
+// temps that didn't get a register
+#[derive(Default)]
+struct Frame {
+    size: usize,
+    ra: usize,
+    saved: Vec<(Register, usize)>,
+    kept: usize,
+    slots: Vec<usize>,
+    spills: usize,
+}
+
+impl Frame {
+    fn new(function: &Function, allocation: &Allocation) -> Self {
+        let outgoing = function
+            .blocks
+            .iter()
+            .flat_map(|block| &block.instructions)
+            .filter_map(|inst| match inst {
+                Inst::Call { args, .. } if args.len() > 4 => Some(args.len() * 4),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+
+        let mut size = outgoing;
+
+        let ra = size;
+        size += 4;
+
+        let mut saved = Vec::new();
+
+        for reg in &allocation.saved {
+            saved.push((*reg, size));
+            size += 4;
+        }
+
+        let kept = size;
+        size += 4 * allocation
+            .live_across
+            .values()
+            .map(|live| live.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut slots = Vec::new();
+
+        for slot in &function.slots {
+            slots.push(size);
+            size += slot.size;
+        }
+
+        let spills = size;
+        size += 4 * allocation.spills;
+
+        Frame {
+            size,
+            ra,
+            saved,
+            kept,
+            slots,
+            spills,
+        }
+    }
+}
+
+impl<'a> MipsGenerator<'a> {
+    pub fn new(module: &'a Module, emit_comments: bool) -> Self {
         MipsGenerator {
-            program,
+            module,
             generated: String::new(),
-            line: 1,
             emit_comments,
+            comment: None,
+            allocation: Allocation::default(),
+            frame: Frame::default(),
         }
     }
 
-    pub fn generate(&mut self) -> Result<&String, CompileError> {
-        // This feels hacky
-        if !self.check_if_main_exists() {
-            return Err(CompileError::CodeGenError {
-                message: "No main function found".to_string(),
-                line: self.line,
-            });
-        }
-
+    pub fn generate(&mut self) -> &String {
         self.emit("# Assembly generated by ecc (Evil C Compiler)\n");
 
-        let data_segment_body = self.program.segments.data.body.clone();
-        let text_segment_body = self.program.segments.text.body.clone();
+        let module = self.module;
+        let mut data_segment_body = module.data.clone();
+
+        // Only there when something needs it, --bounds-check on its own
+        // doesn't mean anything got checked
+        let bounds_error = module
+            .functions
+            .iter()
+            .flat_map(|function| &function.blocks)
+            .any(|block| matches!(block.terminator, Terminator::OutOfBounds { .. }));
+
+        if bounds_error {
+            data_segment_body.push(Statement::new(
+                StatementKind::DataDeclaration {
+                    label: Some("_bounds_message".to_string()),
+                    storage_type: DataStorageType::Asciiz,
+                    values: vec![DataValue::String(
+                        "Array index out of bounds on line ".to_string(),
+                    )],
+                },
+                Span::default(),
+            ));
+        }
 
-        if data_segment_body.len() > 0 {
+        if !data_segment_body.is_empty() {
             self.emit(".data");
         }
 
@@ -52,32 +148,39 @@ impl MipsGenerator {
             self.generate_data_label(stmt);
         }
 
-        if data_segment_body.len() > 0 {
+        if !data_segment_body.is_empty() {
             self.emit("");
         }
 
-        if text_segment_body.len() > 0 {
-            self.emit(".text");
-            self.emit(".globl _start\n");
+        self.emit(".text");
+        self.emit(".globl _start\n");
+
+        for function in &module.functions {
+            self.generate_function(function);
         }
 
-        for stmt in &text_segment_body {
-            match stmt {
-                Statement::Function { .. } => self.generate_function(stmt),
-                _ => {}
-            }
+        if bounds_error {
+            self.generate_bounds_error();
         }
 
-        Ok(&self.generated)
+        &self.generated
     }
 
     fn emit(&mut self, line: &str) {
         self.generated.push_str(line);
-        self.generated.push_str("\n");
+        self.generated.push('\n');
     }
 
-    // {:<8} alligns the operands
+    // {:<8} alligns the operands. Without a comment of its own an
+    // instruction gets the IR it came from, if it's the first one
     fn emit_instruction(&mut self, opcode: &str, operands: &str, comment: &str) {
+        let pending = self.comment.take();
+
+        let comment = match pending {
+            Some(ir) if comment.is_empty() => ir,
+            _ => comment.to_string(),
+        };
+
         if self.emit_comments && !comment.is_empty() {
             self.generated.push_str(&format!(
                 "        {:<8}{:<24}#{}\n",
@@ -96,2130 +199,694 @@ impl MipsGenerator {
         self.generated.push_str(":\n");
     }
 
-    fn check_if_main_exists(&self) -> bool {
-        // This is so much worse than just "hacky"
-        for stmt in &self.program.segments.text.body {
-            if let Statement::Function { name, .. } = stmt {
-                if name == "main" {
-                    return true;
-                }
-            }
-        }
-
-        false
-    }
-
-    fn generate_function(&mut self, function: &Statement) {
-        if let Statement::Function {
-            name,
-            params,
-            body,
-            return_type: _,
-            use_stack,
-        } = function
-        {
-            if *use_stack {
-                // Each function get its own allocator with its own stack frame
-                let allocator = &mut Allocator::new();
-
-                let stack_size = allocator.calculate_needed_stack_space(&body, params.len());
-
-                self.emit_label(name);
+    fn generate_function(&mut self, function: &Function) {
+        self.allocation = allocator::allocate(function);
+        self.frame = Frame::new(function, &self.allocation);
 
-                self.emit_instruction(
-                    "subi",
-                    &format!("$sp, $sp, {}", stack_size),
-                    &format!("Allocate {} bytes of stack space", stack_size),
-                );
-                allocator.add_stack_variable("$ra");
-
-                // It's okay to unwrap here because we just added $ra
-                self.emit_instruction(
-                    "sw",
-                    &format!(
-                        "$ra, {}($sp)",
-                        allocator.get_stack_variable_offset("$ra").unwrap()
-                    ),
-                    "Store the original return address on the stack",
-                );
+        // The entry block is labelled with the function's name and
+        // nothing jumps back to it, so the prologue only runs once
+        self.emit_label(&function.name);
 
-                for (i, param) in params.iter().enumerate() {
-                    allocator.add_stack_variable(&param.name);
-                    let param_reg = ["$a0", "$a1", "$a2", "$a3"][i];
-                    let offset = allocator.get_stack_variable_offset(&param.name).unwrap();
-
-                    self.emit_instruction(
-                        "sw",
-                        &format!("{}, {}($sp)", param_reg, offset),
-                        &format!("Save parameter {} from {} to stack", param.name, param_reg),
-                    );
-                }
-
-                self.emit("");
-
-                for stmt in body {
-                    self.generate_statement(&stmt, allocator);
-                }
-            } else {
-                self.emit_label(name);
-
-                for stmt in body {
-                    self.generate_statement(&stmt, &mut Allocator::new());
-                }
-            }
+        if function.has_frame {
+            self.generate_prologue();
         }
-    }
 
-    fn generate_function_call(
-        &mut self,
-        function_name: &String,
-        arguments: &Vec<Argument>,
-        is_builtin_function: &bool,
-        builtin_function_type: &Option<BuiltinFunctionType>,
-        allocator: &mut Allocator,
-    ) {
-        if *is_builtin_function {
-            let syscall_number = match builtin_function_type {
-                Some(BuiltinFunctionType::IntegerPrint) => 1,
-                Some(BuiltinFunctionType::IntegerRead) => 5,
-                Some(BuiltinFunctionType::StringRead) => 8,
-                Some(BuiltinFunctionType::StringPrint) => 4,
-                Some(BuiltinFunctionType::IntegerRandomRange) => 42,
-
-                None => -1,
-            };
-
-            for arg in arguments {
-                match &arg.expr {
-                    Expr::Identifier(name) => {
-                        let offset = self.get_offset_with_panic(allocator, &name);
-                        self.emit_instruction(
-                            "lw",
-                            &format!("$a0, {}($sp)", offset),
-                            &format!("Load argument {} into $a0 from {}($sp)", name, offset),
-                        );
-                    }
-
-                    Expr::Integer(n) => {
-                        self.emit_instruction(
-                            "li",
-                            &format!("$a0, {}", n),
-                            &format!("Load {} into argument register $a0", n),
-                        );
-                    }
-
-                    Expr::StringLiteral(s) => {
-                        let label = match self.get_data_label_for_string(s) {
-                            Some(l) => l,
-                            None => {
-                                panic!("Label not found for {}", s);
-                            }
-                        };
+        // Only the blocks something jumps to need a label, the rest
+        // are fallen into from the one before
+        let mut targets = HashSet::new();
 
-                        self.emit_instruction("la", &format!("$a0, {}", label), &format!("Load the address of the string stored at {} into argument register $a0", label));
-                    }
-
-                    Expr::FunctionCall {
-                        function_name,
-                        arguments,
-                        is_builtin_function,
-                        builtin_function_type,
-                    } => {
-                        self.generate_function_call(
-                            function_name,
-                            arguments,
-                            is_builtin_function,
-                            builtin_function_type,
-                            allocator,
-                        );
-
-                        self.emit_instruction(
-                            "move",
-                            "$a0, $v0",
-                            "Move return value from $v0 to $a0 for the next syscall argument",
-                        );
-                    }
-
-                    Expr::ArrayIndex {
-                        array_name,
-                        indexer,
-                    } => {
-                        let array_ptr = self.get_offset_with_panic(allocator, array_name);
-
-                        match &**indexer {
-                            Expr::Identifier(name) => {
-                                let indexer_offset = self.get_offset_with_panic(allocator, name);
-
-                                let indexer_temp = self.get_temp_with_panic(allocator);
-                                let array_ptr_temp = self.get_temp_with_panic(allocator);
-
-                                let array_index_offset_temp = self.get_temp_with_panic(allocator);
-
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, {}($sp)", array_ptr_temp, array_ptr),
-                                    &format!("Load array ptr into reg"),
-                                );
-
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, {}($sp)", indexer_temp, indexer_offset),
-                                    &format!("get {}", name),
-                                );
-
-                                self.emit_instruction(
-                                    "sll",
-                                    &format!("{}, {}, 2", array_index_offset_temp, indexer_temp),
-                                    &format!("calculate array byte offset"),
-                                );
-
-                                self.emit_instruction(
-                                    "add",
-                                    &format!(
-                                        "{}, {}, {}",
-                                        array_index_offset_temp,
-                                        array_index_offset_temp,
-                                        array_ptr_temp
-                                    ),
-                                    &format!("Add offset and array ptr"),
-                                );
-
-                                self.emit("");
-
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("$a0, ({})", array_index_offset_temp),
-                                    "",
-                                );
-
-                                allocator.free_temp(indexer_temp);
-                                allocator.free_temp(array_ptr_temp);
-                                allocator.free_temp(array_index_offset_temp);
-                            }
-
-                            _ => {
-                                panic!(
-                                    "{:?} not implemented for array index in generate_function_call",
-                                    &**indexer
-                                );
-                            }
-                        }
-                    }
+        for (i, block) in function.blocks.iter().enumerate() {
+            targets.extend(jumps(&block.terminator, i + 1));
+        }
 
-                    _ => {
-                        panic!(
-                            "expr {:?} not implemented in generate_function_call",
-                            arg.expr
-                        );
-                    }
-                }
+        for (i, block) in function.blocks.iter().enumerate() {
+            if i > 0 && targets.contains(&i) {
+                self.emit_label(&block.label);
             }
 
-            self.emit_instruction(
-                "li",
-                &format!("$v0, {}", syscall_number),
-                &format!(
-                    "Load the appropriate syscall number {} into $v0",
-                    syscall_number
-                ),
-            );
-            self.emit_instruction("syscall", "", "");
-            self.emit("");
-        } else {
-            for i in 0..arguments.len() {
-                match &arguments[i].expr {
-                    Expr::Identifier(name) => {
-                        let offset = self.get_offset_with_panic(allocator, &name);
-                        self.emit_instruction(
-                            "lw",
-                            &format!("$a{}, {}($sp)", i, offset),
-                            &format!("Load argument {} into $a{} from {}($sp)", name, i, offset),
-                        );
-                    }
-
-                    Expr::Integer(n) => {
-                        self.emit_instruction(
-                            "li",
-                            &format!("$a{}, {}", i, n),
-                            &format!("Load {} into argument register $a{}", n, i),
-                        );
-                    }
-
-                    Expr::StringLiteral(s) => {
-                        let label = match self.get_data_label_for_string(s) {
-                            Some(l) => l,
-                            None => {
-                                panic!("Label not found for {}", s);
-                            }
-                        };
+            for (j, inst) in block.instructions.iter().enumerate() {
+                self.comment = Some(inst.to_string());
+                self.generate_instruction(inst, (i, j));
+            }
 
-                        self.emit_instruction("la", &format!("$a0, {}", label), &format!("Load the address of the string stored at {} into argument register $a0", label));
-                    }
+            self.comment = None;
+            self.generate_terminator(function, block, i + 1);
+        }
+    }
 
-                    Expr::IdentifierReference(name) => {
-                        self.emit_instruction(
-                            "addi",
-                            &format!(
-                                "$a{}, $sp, {}",
-                                i,
-                                self.get_offset_with_panic(allocator, name)
-                            ),
-                            name,
-                        );
-                    }
+    fn generate_prologue(&mut self) {
+        let size = self.frame.size;
 
-                    _ => {
-                        panic!(
-                            "expr {:?} not implemented in generate_function_call",
-                            arguments[i].expr
-                        );
-                    }
-                }
-            }
+        self.emit_instruction(
+            "subi",
+            &format!("$sp, $sp, {}", size),
+            &format!("Allocate {} bytes of stack space", size),
+        );
+        self.emit_instruction(
+            "sw",
+            &format!("$ra, {}($sp)", self.frame.ra),
+            "Store the original return address on the stack",
+        );
 
+        for (reg, offset) in self.frame.saved.clone() {
             self.emit_instruction(
-                "jal",
-                function_name,
-                &format!("Call function {}", function_name),
+                "sw",
+                &format!("{}, {}($sp)", reg, offset),
+                &format!("Save callee-saved register {}", reg),
             );
-
-            self.emit("");
         }
+
+        self.emit("");
     }
 
-    fn generate_return(&mut self, expr: &Expr, allocator: &mut Allocator) {
-        let mut reg = String::new();
-        let mut include_return_register = true;
-
-        match expr {
-            Expr::Identifier(name) => {
-                reg = match allocator.get_variable_register(name) {
-                    Some(r) => r,
-                    None => {
-                        panic!(
-                            "Register or Offset not found for {} in generate_return",
-                            name
-                        );
-                    }
-                };
+    fn generate_instruction(&mut self, inst: &Inst, at: (usize, usize)) {
+        match inst {
+            Inst::Const { dest, value } => {
+                let d = self.destination(*dest);
+                self.emit_instruction("li", &format!("{}, {}", d, value), "");
+                self.spill(*dest);
             }
 
-            Expr::Integer(n) => {
-                let temp = match allocator.allocate_temp() {
-                    Some(reg) => reg,
-                    None => {
-                        panic!("Out of temp registers in generate_return")
-                    }
+            Inst::Address { dest, base, offset } => {
+                let (instruction, operands) = match base {
+                    Base::Slot(slot) => (
+                        "addi",
+                        format!("$sp, {}", self.frame.slots[slot.0] as i32 + offset),
+                    ),
+                    Base::Label(label) => ("la", label_plus(label, *offset)),
+                    Base::Temp(temp) => ("addi", format!("{}, {}", self.source(*temp, 1), offset)),
                 };
 
-                self.emit_instruction(
-                    "li",
-                    &format!("{}, {}", temp, n),
-                    &format!("Load {} into temp register {}", temp, n),
-                );
-
-                reg = temp.to_string();
+                let d = self.destination(*dest);
+                self.emit_instruction(instruction, &format!("{}, {}", d, operands), "");
+                self.spill(*dest);
             }
 
-            Expr::BoolLiteral(value) => {
-                let int_value = if *value { 1 } else { 0 };
+            Inst::Unary { dest, op, src } => {
+                let s = self.source(*src, 0);
+                let d = self.destination(*dest);
 
-                let temp = match allocator.allocate_temp() {
-                    Some(reg) => reg,
-                    None => {
-                        panic!("Out of temp registers in generate_return")
-                    }
+                let (instruction, operands) = match op {
+                    UnaryOp::Negate => ("sub", format!("{}, $zero, {}", d, s)),
+                    UnaryOp::Not => ("sltiu", format!("{}, {}, 1", d, s)),
+                    UnaryOp::BitwiseNot => ("nor", format!("{}, {}, $zero", d, s)),
                 };
 
-                self.emit_instruction(
-                    "li",
-                    &format!("{}, {}", temp, int_value),
-                    &format!("Load {} into temp register {}", temp, int_value),
-                );
-
-                reg = temp.to_string();
+                self.emit_instruction(instruction, &operands, "");
+                self.spill(*dest);
             }
 
-            Expr::Empty => {
-                include_return_register = false;
-            }
+            Inst::Binary {
+                dest,
+                op,
+                left,
+                right,
+            } => self.generate_binary(*dest, *op, *left, right),
 
-            _ => {
-                panic!("Expr {:?} not implemented in generate_return", expr);
-            }
-        }
+            Inst::Load {
+                dest,
+                width,
+                base,
+                offset,
+            } => {
+                let address = self.address(base, *offset);
+                let d = self.destination(*dest);
 
-        if include_return_register {
-            if reg.contains("($sp)") {
-                self.emit_instruction(
-                    "lw",
-                    &format!("$v0, {}", reg),
-                    &format!("Load value at {} into return register", reg),
-                );
-            } else {
-                self.emit_instruction(
-                    "move",
-                    &format!("$v0, {}", reg),
-                    &format!("Move value from register {} to return register", reg),
-                );
-            }
-        }
+                let instruction = match width {
+                    Width::Byte => "lb",
+                    Width::Word => "lw",
+                };
 
-        self.emit_instruction(
-            "lw",
-            &format!(
-                "$ra, {}($sp)",
-                allocator.get_stack_variable_offset("$ra").unwrap()
-            ),
-            &format!(
-                "Load stored return address from {}($sp) back into $ra",
-                allocator.get_stack_variable_offset("$ra").unwrap()
-            ),
-        );
+                self.emit_instruction(instruction, &format!("{}, {}", d, address), "");
+                self.spill(*dest);
+            }
 
-        self.emit_instruction(
-            "addi",
-            &format!("$sp, $sp, {}", allocator.get_stack_size()),
-            &format!(
-                "Deallocate {} bytes of stack space",
-                allocator.get_stack_size()
-            ),
-        );
-        self.emit_instruction("jr", "$ra\n", "Jump back to the function caller's address");
-    }
+            Inst::Store {
+                width,
+                value,
+                base,
+                offset,
+            } => {
+                let value = self.register(value, 0);
+                let address = self.address(base, *offset);
 
-    fn generate_statement(&mut self, statement: &Statement, allocator: &mut Allocator) {
-        match statement {
-            Statement::VariableDeclaration {
-                var_type,
-                identifier,
-                operation,
-            } => self.generate_variable_declaration(var_type, identifier, operation, allocator),
-
-            Statement::VariableAssignment {
-                identifier,
-                operation,
-                is_dereference,
-                is_array_index,
-                indexer,
-            } => self.generate_variable_assignment(
-                identifier,
-                operation,
-                is_dereference,
-                is_array_index,
-                indexer,
-                allocator,
-            ),
+                let instruction = match width {
+                    Width::Byte => "sb",
+                    Width::Word => "sw",
+                };
 
-            Statement::Instruction { opcode, operands } => {
-                self.emit_instruction(opcode, &operands.join(", "), "");
+                self.emit_instruction(instruction, &format!("{}, {}", value, address), "");
+                self.emit("");
             }
 
-            Statement::While {
-                body_label,
-                end_label,
-                condition,
-                body,
-            } => {
-                self.generate_while(body_label, end_label, condition, body, allocator);
-            }
+            // Past the fourth they're in the caller's frame, right above ours
+            Inst::Param { dest, index } => {
+                let d = self.destination(*dest);
 
-            Statement::For {
-                init,
-                body_label,
-                end_label,
-                condition,
-                var_change,
-                body,
-            } => {
-                self.generate_for(
-                    init, body_label, end_label, condition, var_change, body, allocator,
-                );
-            }
+                if *index < 4 {
+                    self.emit_instruction("move", &format!("{}, $a{}", d, index), "");
+                } else {
+                    let offset = self.frame.size + index * 4;
+                    self.emit_instruction("lw", &format!("{}, {}($sp)", d, offset), "");
+                }
 
-            Statement::If {
-                label,
-                condition,
-                body,
-            } => {
-                self.generate_if(label, condition, body, allocator);
+                self.spill(*dest);
             }
 
-            Statement::Return { value } => {
-                self.generate_return(value, allocator);
-            }
+            Inst::Call {
+                dest,
+                function,
+                args,
+            } => self.generate_call(*dest, function, args, at),
 
-            Statement::FunctionCall {
-                function_name,
-                arguments,
-                is_builtin_function,
-                builtin_function_type,
-            } => {
-                self.generate_function_call(
-                    function_name,
-                    arguments,
-                    is_builtin_function,
-                    builtin_function_type,
-                    allocator,
-                );
-            }
+            Inst::Builtin {
+                dest,
+                builtin,
+                args,
+            } => self.generate_builtin(*dest, builtin, args),
 
-            // Statement::NewLine => {
-            //     self.line += 1;
-            // }
-            _ => {
-                panic!("statement not implemented");
-            }
-        }
-    }
+            // The parser ends some of _start's with a newline to space it out
+            Inst::Asm { opcode, operands } => {
+                self.emit_instruction(opcode.trim_end(), &operands.join(", "), "");
 
-    // This function is a mess, so be careful
-    // You might hurt your eyes reading it
-    fn generate_variable_declaration(
-        &mut self,
-        var_type: &Type,
-        identifier: &String,
-        operation: &Expr,
-        allocator: &mut Allocator,
-    ) {
-        allocator.add_stack_variable(identifier);
-
-        match var_type {
-            Type::Int32 => {
-                if let Expr::BinaryOp {
-                    left,
-                    operator,
-                    right,
-                    is_not: _,
-                } = operation
-                {
-                    if *operator == BinaryOperator::Empty && **right == Expr::Empty {
-                        let reg = match allocator.allocate_temp() {
-                            Some(r) => r,
-                            None => panic!("Ran out of temporary registers"),
-                        };
-
-                        match &**left {
-                            Expr::Integer(n) => {
-                                self.emit_instruction(
-                                    "li",
-                                    &format!("{}, {}", reg, n),
-                                    &format!("Load {} into register {}", n, reg),
-                                );
-                                self.emit_instruction(
-                                    "sw",
-                                    &format!(
-                                        "{}, {}($sp)\n",
-                                        reg,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                    &format!(
-                                        "Store {} from register {} into stack at {}($sp)",
-                                        n,
-                                        reg,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                );
-                                allocator.free_temp(reg);
-                            }
-
-                            Expr::Identifier(name) => {
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!(
-                                        "{}, {}($sp)",
-                                        reg,
-                                        self.get_offset_with_panic(allocator, name)
-                                    ),
-                                    &format!(
-                                        "Load value in variable {} from {}($sp) into register {}",
-                                        name,
-                                        self.get_offset_with_panic(allocator, name),
-                                        reg
-                                    ),
-                                );
-                                self.emit_instruction(
-                                    "sw",
-                                    &format!(
-                                        "{}, {}($sp)\n",
-                                        reg,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                    &format!(
-                                        "Store value from register {} into {}($sp)",
-                                        reg,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                );
-                                allocator.free_temp(reg);
-                            }
-
-                            Expr::IdentifierDereference(name) => {
-                                let dest_offset = self.get_offset_with_panic(allocator, identifier);
-                                let from_offset = self.get_offset_with_panic(allocator, name);
-
-                                let from_temp = self.get_temp_with_panic(allocator);
-                                let dest_temp = self.get_temp_with_panic(allocator);
-
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, {}($sp)", from_temp, from_offset),
-                                    name,
-                                );
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, ({})", dest_temp, from_temp),
-                                    &format!("Store value of {} in temp", identifier),
-                                );
-                                self.emit_instruction(
-                                    "sw",
-                                    &format!("{}, {}($sp)", dest_temp, dest_offset),
-                                    identifier,
-                                );
-                                self.emit("");
-
-                                allocator.free_temp(from_temp);
-                                allocator.free_temp(dest_temp);
-                            }
-
-                            Expr::ArrayIndex {
-                                array_name,
-                                indexer,
-                            } => {
-                                let base_offset = self.get_offset_with_panic(allocator, array_name);
-                                let base_ptr_temp = self.get_temp_with_panic(allocator);
-                                let addr_temp = self.get_temp_with_panic(allocator);
-
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, {}($sp)", base_ptr_temp, base_offset),
-                                    &format!("Load base pointer of {}", array_name),
-                                );
-
-                                match &**indexer {
-                                    Expr::Identifier(name) => {
-                                        let idx_offset =
-                                            self.get_offset_with_panic(allocator, name);
-                                        self.emit_instruction(
-                                            "lw",
-                                            &format!("{}, {}($sp)", addr_temp, idx_offset),
-                                            &format!("Load index {}", name),
-                                        );
-                                    }
-                                    Expr::Integer(n) => {
-                                        self.emit_instruction(
-                                            "li",
-                                            &format!("{}, {}", addr_temp, n),
-                                            &format!("Load index {}", n),
-                                        );
-                                    }
-                                    _ => panic!("Unsupported indexer in ArrayIndex right operand"),
-                                }
-
-                                self.emit_instruction(
-                                    "sll",
-                                    &format!("{}, {}, 2", addr_temp, addr_temp),
-                                    "Multiply index by 4",
-                                );
-                                self.emit_instruction(
-                                    "add",
-                                    &format!("{}, {}, {}", addr_temp, addr_temp, base_ptr_temp),
-                                    "Compute element address",
-                                );
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, ({})", reg, addr_temp),
-                                    &format!("Load {}[i] into {}", array_name, reg),
-                                );
-                                self.emit_instruction(
-                                    "sw",
-                                    &format!(
-                                        "{}, {}($sp)",
-                                        reg,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                    &format!(
-                                        "Store {}[i] into {}($sp)",
-                                        array_name,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                );
-
-                                self.emit("");
-
-                                allocator.free_temp(base_ptr_temp);
-                                allocator.free_temp(addr_temp);
-                                allocator.free_temp(reg);
-                            }
-
-                            Expr::Empty => {
-                                self.emit_instruction(
-                                    "li",
-                                    &format!("{}, {}", reg, 0),
-                                    &format!("Load {} into register {}", 0, reg),
-                                );
-                                self.emit_instruction(
-                                    "sw",
-                                    &format!(
-                                        "{}, {}($sp)\n",
-                                        reg,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                    &format!(
-                                        "Store {} from register {} into stack at {}($sp)",
-                                        0,
-                                        reg,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                );
-                            }
-
-                            Expr::FunctionCall {
-                                function_name,
-                                arguments,
-                                is_builtin_function,
-                                builtin_function_type,
-                            } => {
-                                if arguments.len() > 4 {
-                                    panic!("Maximum of 4 arguments allowed for {}", function_name);
-                                }
-
-                                let registers = ["$a0", "$a1", "$a2", "$a3"];
-
-                                for i in 0..arguments.len() {
-                                    match &arguments[i].expr {
-                                        Expr::Integer(n) => {
-                                            self.emit_instruction(
-                                                "li",
-                                                &format!("{}, {}", registers[i], n),
-                                                &format!(
-                                                    "Load {} into register {}",
-                                                    n, registers[i]
-                                                ),
-                                            );
-                                        }
-                                        Expr::Identifier(name) => {
-                                            let offset =
-                                                self.get_offset_with_panic(allocator, &name);
-                                            self.emit_instruction(
-                                                "lw",
-                                                &format!("{}, {}($sp)", registers[i], offset),
-                                                &format!(
-                                                    "Load value at {}($sp) into {}",
-                                                    offset, registers[i]
-                                                ),
-                                            );
-                                        }
-                                        _ => panic!(
-                                            "Argument Expr {:?} not implemented in generate_variable_declaration",
-                                            arguments[i].expr
-                                        ),
-                                    }
-                                }
-
-                                if *is_builtin_function {
-                                    let syscall_number = match builtin_function_type {
-                                        Some(BuiltinFunctionType::IntegerPrint) => 1,
-                                        Some(BuiltinFunctionType::IntegerRead) => 5,
-                                        Some(BuiltinFunctionType::StringRead) => 8,
-                                        Some(BuiltinFunctionType::StringPrint) => 4,
-                                        Some(BuiltinFunctionType::IntegerRandomRange) => 42,
-                                        None => -1,
-                                    };
-                                    self.emit_instruction(
-                                        "li",
-                                        &format!("$v0, {}", syscall_number),
-                                        "Load syscall number into $a0",
-                                    );
-                                    self.emit_instruction("syscall", "", "");
-                                } else {
-                                    self.emit_instruction(
-                                        "jal",
-                                        function_name,
-                                        &format!("Call function {}", function_name),
-                                    );
-                                }
-
-                                if function_name == "irandrange" {
-                                    self.emit_instruction(
-                                        "sw",
-                                        &format!(
-                                            "$a0, {}($sp)\n",
-                                            self.get_offset_with_panic(allocator, identifier)
-                                        ),
-                                        &format!(
-                                            "Store value in register $a0 into {}($sp)",
-                                            self.get_offset_with_panic(allocator, identifier)
-                                        ),
-                                    );
-                                } else {
-                                    self.emit_instruction(
-                                        "sw",
-                                        &format!(
-                                            "$v0, {}($sp)\n",
-                                            self.get_offset_with_panic(allocator, identifier)
-                                        ),
-                                        &format!(
-                                            "Store value in register $v0 into {}($sp)",
-                                            self.get_offset_with_panic(allocator, identifier)
-                                        ),
-                                    );
-                                }
-                            }
-
-                            _ => panic!(
-                                "Expr {:?} not implemented in generate_variable_declaration",
-                                **left
-                            ),
-                        }
-                    } else {
-                        let left_temp = match allocator.allocate_temp() {
-                            Some(r) => r,
-                            None => panic!("Ran out of temporary registers"),
-                        };
-                        let right_temp = match allocator.allocate_temp() {
-                            Some(r) => r,
-                            None => panic!("Ran out of temporary registers"),
-                        };
-
-                        match &**left {
-                            Expr::Integer(n) => {
-                                self.emit_instruction(
-                                    "li",
-                                    &format!("{}, {}", left_temp, n),
-                                    &format!("Load {} into left register {}", n, left_temp),
-                                );
-                            }
-                            Expr::Identifier(name) => {
-                                let reg_option = allocator.get_variable_register(name);
-                                match reg_option {
-                                    Some(reg) => {
-                                        self.emit_instruction(
-                                            "lw",
-                                            &format!("{}, {}", left_temp, reg),
-                                            &format!(
-                                                "Load value from {} into left register {}",
-                                                reg, left_temp
-                                            ),
-                                        );
-                                    }
-                                    None => panic!(
-                                        "Register or Offset not found for {} in generate_variable_declaration",
-                                        name
-                                    ),
-                                }
-                            }
-                            _ => panic!(
-                                "Expr {:?} not implemented in generate_variable_declaration",
-                                **left
-                            ),
-                        }
-
-                        match &**right {
-                            Expr::Integer(n) => {
-                                self.emit_instruction(
-                                    "li",
-                                    &format!("{}, {}", right_temp, n),
-                                    &format!("Load {} into right register {}", n, right_temp),
-                                );
-                            }
-                            Expr::Identifier(name) => {
-                                let reg_option = allocator.get_variable_register(name);
-                                match reg_option {
-                                    Some(reg) => {
-                                        self.emit_instruction(
-                                            "lw",
-                                            &format!("{}, {}", right_temp, reg),
-                                            &format!(
-                                                "Load value from {} into right register {}",
-                                                reg, right_temp
-                                            ),
-                                        );
-                                    }
-                                    None => panic!(
-                                        "Register or Offset not found for {} in generate_variable_declaration",
-                                        name
-                                    ),
-                                }
-                            }
-
-                            Expr::ArrayIndex {
-                                array_name,
-                                indexer,
-                            } => {
-                                let base_offset = self.get_offset_with_panic(allocator, array_name);
-                                let base_ptr_temp = self.get_temp_with_panic(allocator);
-                                let addr_temp = self.get_temp_with_panic(allocator);
-
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, {}($sp)", base_ptr_temp, base_offset),
-                                    &format!("Load base pointer of {}", array_name),
-                                );
-
-                                match &**indexer {
-                                    Expr::Identifier(name) => {
-                                        let idx_offset =
-                                            self.get_offset_with_panic(allocator, name);
-                                        self.emit_instruction(
-                                            "lw",
-                                            &format!("{}, {}($sp)", addr_temp, idx_offset),
-                                            &format!("Load index {}", name),
-                                        );
-                                    }
-                                    Expr::Integer(n) => {
-                                        self.emit_instruction(
-                                            "li",
-                                            &format!("{}, {}", addr_temp, n),
-                                            &format!("Load index {}", n),
-                                        );
-                                    }
-                                    _ => panic!("Unsupported indexer in ArrayIndex right operand"),
-                                }
-
-                                self.emit_instruction(
-                                    "sll",
-                                    &format!("{}, {}, 2", addr_temp, addr_temp),
-                                    "Multiply index by 4",
-                                );
-                                self.emit_instruction(
-                                    "add",
-                                    &format!("{}, {}, {}", addr_temp, addr_temp, base_ptr_temp),
-                                    "Compute element address",
-                                );
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, ({})", right_temp, addr_temp),
-                                    &format!("Load {}[i] into {}", array_name, right_temp),
-                                );
-
-                                allocator.free_temp(base_ptr_temp);
-                                allocator.free_temp(addr_temp);
-                            }
-
-                            _ => panic!(
-                                "Expr {:?} not implemented in generate_variable_declaration",
-                                **right
-                            ),
-                        }
-
-                        let temp = match allocator.allocate_temp() {
-                            Some(r) => r,
-                            None => panic!("Ran out of temporary registers"),
-                        };
-
-                        match operator {
-                            BinaryOperator::Add => {
-                                self.emit_instruction(
-                                    "add",
-                                    &format!("{}, {}, {}", temp, left_temp, right_temp),
-                                    &format!(
-                                        "Add values from {} and {} and store in register {}",
-                                        left_temp, right_temp, temp
-                                    ),
-                                );
-                            }
-                            BinaryOperator::Subtract => {
-                                self.emit_instruction(
-                                    "sub",
-                                    &format!("{}, {}, {}", temp, left_temp, right_temp),
-                                    &format!(
-                                        "Sub values from {} and {} and store in register {}",
-                                        left_temp, right_temp, temp
-                                    ),
-                                );
-                            }
-                            BinaryOperator::Multiply => {
-                                self.emit_instruction(
-                                    "mulo",
-                                    &format!("{}, {}, {}", temp, left_temp, right_temp),
-                                    &format!(
-                                        "Multiply values from {} and {} and store in register {}",
-                                        left_temp, right_temp, temp
-                                    ),
-                                );
-                            }
-                            _ => panic!("Unsupported binary operator: {:?}", operator),
-                        }
-
-                        self.emit_instruction(
-                            "sw",
-                            &format!(
-                                "{}, {}($sp)\n",
-                                temp,
-                                self.get_offset_with_panic(allocator, identifier),
-                            ),
-                            &format!(
-                                "Store value from register {} to {}($sp)",
-                                temp,
-                                self.get_offset_with_panic(allocator, identifier)
-                            ),
-                        );
-
-                        allocator.free_temp(temp);
-                        allocator.free_temp(left_temp);
-                        allocator.free_temp(right_temp);
-                    }
+                if opcode.ends_with('\n') {
+                    self.emit("");
                 }
             }
+        }
+    }
 
-            Type::Int32Pointer => match operation {
-                Expr::BinaryOp {
-                    left,
-                    operator,
-                    right,
-                    ..
-                } => {
-                    if operator != &BinaryOperator::Empty || right != &Box::new(Expr::Empty) {
-                        panic!(
-                            "Pointer declaration cannot have an operator or a right expression in generate_variable_declaration"
-                        );
-                    }
+    fn generate_binary(&mut self, dest: Temp, op: BinaryOp, left: Temp, right: &Operand) {
+        let l = self.source(left, 0);
 
-                    match &**left {
-                        Expr::IdentifierReference(name) => {
-                            let from_offset = self.get_offset_with_panic(allocator, name);
-                            let addr_temp = self.get_temp_with_panic(allocator);
-
-                            self.emit_instruction(
-                                "addi",
-                                &format!("{}, $sp, {}", addr_temp, from_offset),
-                                &format!("Store address of {} in {}", name, addr_temp),
-                            );
-                            self.emit_instruction(
-                                "sw",
-                                &format!(
-                                    "{}, {}($sp)",
-                                    addr_temp,
-                                    self.get_offset_with_panic(allocator, identifier)
-                                ),
-                                identifier,
-                            );
-                            self.emit("");
-                            allocator.free_temp(addr_temp);
-                        }
-
-                        Expr::ArrayInitializer { body, size } => {
-                            let base_offset = self.get_offset_with_panic(allocator, identifier);
-
-                            if body.len() > 0 {
-                                for i in 0..*size {
-                                    let expr = *body[i].clone();
-                                    match expr {
-                                        Expr::Integer(n) => {
-                                            let t = self.get_temp_with_panic(allocator);
-                                            self.emit_instruction(
-                                                "li",
-                                                &format!("{}, {}", t, n),
-                                                "",
-                                            );
-                                            self.emit_instruction(
-                                                "sw",
-                                                &format!("{}, {}($sp)", t, base_offset + 4 + i * 4),
-                                                &format!("{}[{}]: {}", identifier, i, n),
-                                            );
-                                            allocator.free_temp(t);
-                                        }
-                                        _ => panic!(
-                                            "Expr {:?} not implemented in generate_variable_declaration",
-                                            expr
-                                        ),
-                                    }
-                                }
-                            }
-
-                            // Skip over the element slots so the next variable lands after them
-                            allocator.reserve_array_elements(*size);
-
-                            // Store the address of the first element into the pointer's own slot
-                            let addr_temp = self.get_temp_with_panic(allocator);
-                            self.emit_instruction(
-                                "addi",
-                                &format!("{}, $sp, {}", addr_temp, base_offset + 4),
-                                &format!("Get address of first element of {}", identifier),
-                            );
-                            self.emit_instruction(
-                                "sw",
-                                &format!("{}, {}($sp)", addr_temp, base_offset),
-                                &format!("Store base pointer for {}", identifier),
-                            );
-                            allocator.free_temp(addr_temp);
-                            self.emit("");
-                        }
-
-                        _ => panic!(
-                            "Can only reference an identifier, not {:?} in generate_variable_declaration",
-                            &**left
-                        ),
-                    }
-                }
-                _ => panic!("This should never print"),
-            },
+        // Multiplying by a power of two is a shift
+        if let (BinaryOp::Scale, Operand::Const(size)) = (op, right)
+            && size.count_ones() == 1
+        {
+            let d = self.destination(dest);
+            self.emit_instruction(
+                "sll",
+                &format!("{}, {}, {}", d, l, size.trailing_zeros()),
+                "",
+            );
+            self.spill(dest);
+            return;
+        }
 
-            Type::String => match operation {
-                Expr::StringLiteral(str) => {
-                    let reg = match allocator.allocate_temp() {
-                        Some(r) => r,
-                        None => panic!("Ran out of temporary registers"),
-                    };
-
-                    let label = match self.get_data_label_for_string(str) {
-                        Some(l) => l,
-                        None => panic!("String literal {} not found in data segment", str),
-                    };
-
-                    self.emit_instruction(
-                        "la",
-                        &format!("{}, {}", reg, label),
-                        &format!("Load string from address {} into register {}", label, reg),
-                    );
-                    self.emit_instruction(
-                        "sw",
-                        &format!(
-                            "{}, {}($sp)\n",
-                            reg,
-                            self.get_offset_with_panic(allocator, identifier),
-                        ),
-                        &format!(
-                            "Store string address from {} into {}($sp)",
-                            reg,
-                            self.get_offset_with_panic(allocator, identifier)
-                        ),
-                    );
-                    allocator.free_temp(reg);
-                }
+        if let Operand::Const(n) = right
+            && let Some(instruction) = immediate_form(op, *n)
+        {
+            let n = match op {
+                BinaryOp::ShiftLeft | BinaryOp::ShiftRight => n & 31,
+                _ => *n,
+            };
 
-                Expr::Identifier(name) => {
-                    let reg = match allocator.allocate_temp() {
-                        Some(r) => r,
-                        None => panic!("Ran out of temporary registers"),
-                    };
-
-                    let offset = match allocator.get_stack_variable_offset(name) {
-                        Some(n) => n,
-                        None => panic!("Variable {} not found in stack", name),
-                    };
-
-                    self.emit_instruction(
-                        "lw",
-                        &format!("{}, {}($sp)", reg, offset),
-                        &format!("Load value from {}($sp) into register {}", offset, reg),
-                    );
-                    self.emit_instruction(
-                        "sw",
-                        &format!(
-                            "{}, {}($sp)\n",
-                            reg,
-                            self.get_offset_with_panic(allocator, identifier),
-                        ),
-                        &format!(
-                            "Store value from register {} into {}($sp)",
-                            reg,
-                            self.get_offset_with_panic(allocator, identifier)
-                        ),
-                    );
-                    allocator.free_temp(reg);
-                }
+            let d = self.destination(dest);
+            self.emit_instruction(instruction, &format!("{}, {}, {}", d, l, n), "");
+            self.spill(dest);
+            return;
+        }
 
-                _ => {
-                    CompileError::TypeError {
-                        message: "Unsupported expression in variable initialization".to_string(),
-                        line: self.line,
-                    };
-                }
+        let instruction = match op {
+            BinaryOp::Add => "add",
+            BinaryOp::Subtract => "sub",
+            BinaryOp::Multiply => "mulo",
+            BinaryOp::Scale => "mul",
+
+            // The three operand pseudo instructions, not the real div. Those
+            // check for zero and break, the real one carries on with whatever
+            // was in LO and HI
+            BinaryOp::Divide => "div",
+            BinaryOp::Modulo => "rem",
+            BinaryOp::BitwiseAnd => "and",
+            BinaryOp::BitwiseOr => "or",
+            BinaryOp::BitwiseXor => "xor",
+            BinaryOp::ShiftLeft => "sllv",
+
+            // int32 is signed so >> keeps the sign bit
+            BinaryOp::ShiftRight => "srav",
+
+            BinaryOp::Compare(condition) => match condition {
+                Condition::Less => "slt",
+                Condition::LessEqual => "sle",
+                Condition::Greater => "sgt",
+                Condition::GreaterEqual => "sge",
+                Condition::Equal => "seq",
+                Condition::NotEqual => "sne",
+                Condition::Below => "sltu",
             },
+        };
 
-            Type::Bool => {
-                if let Expr::BinaryOp {
-                    left,
-                    operator,
-                    right,
-                    ..
-                } = operation
-                {
-                    if *operator == BinaryOperator::Empty && **right == Expr::Empty {
-                        let reg = match allocator.allocate_temp() {
-                            Some(r) => r,
-                            None => panic!("Ran out of temporary registers"),
-                        };
-
-                        match &**left {
-                            Expr::BoolLiteral(value) => {
-                                let int_value = if *value { 1 } else { 0 };
-                                self.emit_instruction(
-                                    "li",
-                                    &format!("{}, {}", reg, int_value),
-                                    &format!("Load boolean {} into register {}", value, reg),
-                                );
-                                self.emit_instruction(
-                                    "sw",
-                                    &format!(
-                                        "{}, {}($sp)\n",
-                                        reg,
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                    &format!(
-                                        "Store boolean into {}($sp)",
-                                        self.get_offset_with_panic(allocator, identifier)
-                                    ),
-                                );
-                            }
-                            _ => panic!("Unexpected expression in Bool declaration"),
-                        }
-
-                        allocator.free_temp(reg);
-                    }
-                }
-            }
+        let r = self.register(right, 1);
+        let d = self.destination(dest);
 
-            _ => panic!(
-                "Type {:?} not implemented in generate_variable_declaration",
-                var_type
-            ),
-        }
+        self.emit_instruction(instruction, &format!("{}, {}, {}", d, l, r), "");
+        self.spill(dest);
     }
 
-    // This one is ugly, I wrote this in a blur while I was super tired
-    // I'm sorry
-    fn emit_store(
+    // Arguments go in $a0-$a3 and the frame, and whatever $t registers
+    // are still needed afterwards wait in the frame too
+    fn generate_call(
         &mut self,
-        value_reg: Register,
-        identifier: &String,
-        is_array_index: &bool,
-        indexer: &Expr,
-        allocator: &mut Allocator,
+        dest: Option<Temp>,
+        function: &str,
+        args: &[Temp],
+        at: (usize, usize),
     ) {
-        if *is_array_index {
-            let base_ptr_temp = self.get_temp_with_panic(allocator);
-            let index_temp = self.get_temp_with_panic(allocator);
-            let addr_temp = self.get_temp_with_panic(allocator);
-
-            let base_offset = self.get_offset_with_panic(allocator, identifier);
-
+        let live = self
+            .allocation
+            .live_across
+            .get(&at)
+            .cloned()
+            .unwrap_or_default();
+
+        for (i, reg) in live.iter().enumerate() {
             self.emit_instruction(
-                "lw",
-                &format!("{}, {}($sp)", base_ptr_temp, base_offset),
-                &format!("Load base pointer of {}", identifier),
+                "sw",
+                &format!("{}, {}($sp)", reg, self.frame.kept + i * 4),
+                &format!("Keep {} across the call", reg),
             );
+        }
 
-            match indexer {
-                Expr::Identifier(name) => {
-                    let idx_offset = self.get_offset_with_panic(allocator, name);
-                    self.emit_instruction(
-                        "lw",
-                        &format!("{}, {}($sp)", index_temp, idx_offset),
-                        &format!("Load index {}", name),
-                    );
-                }
-                Expr::Integer(n) => {
-                    self.emit_instruction(
-                        "li",
-                        &format!("{}, {}", index_temp, n),
-                        &format!("Load index {}", n),
-                    );
-                }
-                _ => panic!("Unsupported indexer in emit_store"),
-            }
+        self.pass_arguments(args);
 
-            self.emit_instruction(
-                "sll",
-                &format!("{}, {}, 2", addr_temp, index_temp),
-                "Multiply index by 4",
-            );
-            self.emit_instruction(
-                "add",
-                &format!("{}, {}, {}", addr_temp, addr_temp, base_ptr_temp),
-                "Compute element address",
-            );
-            self.emit_instruction(
-                "sw",
-                &format!("{}, ({})", value_reg, addr_temp),
-                &format!("{}[i] = result", identifier),
-            );
+        self.emit_instruction("jal", function, &format!("Call function {}", function));
 
-            allocator.free_temp(base_ptr_temp);
-            allocator.free_temp(index_temp);
-            allocator.free_temp(addr_temp);
-        } else {
-            let offset = self.get_offset_with_panic(allocator, identifier);
+        for (i, reg) in live.iter().enumerate() {
             self.emit_instruction(
-                "sw",
-                &format!("{}, {}($sp)", value_reg, offset),
-                &format!("Store into {}($sp)", offset),
+                "lw",
+                &format!("{}, {}($sp)", reg, self.frame.kept + i * 4),
+                &format!("Restore {}", reg),
             );
         }
-        self.emit("");
-    }
 
-    fn generate_variable_assignment(
-        &mut self,
-        identifier: &String,
-        operation: &Expr,
-        is_dereference: &bool,
-        is_array_index: &bool,
-        indexer: &Expr,
-        allocator: &mut Allocator,
-    ) {
-        let reg = match allocator.allocate_temp() {
-            Some(r) => r,
-            None => panic!("Ran out of temporary registers"),
-        };
-
-        match operation {
-            Expr::Integer(value) => {
+        match dest {
+            Some(dest) => {
+                let d = self.destination(dest);
                 self.emit_instruction(
-                    "li",
-                    &format!("{}, {}", reg, value),
-                    &format!("Load {} into register {}", value, reg),
+                    "move",
+                    &format!("{}, $v0", d),
+                    &format!("Result of {}", function),
                 );
-
-                if *is_dereference {
-                    let addr_reg = self.get_temp_with_panic(allocator);
-                    let offset = self.get_offset_with_panic(allocator, identifier);
-                    self.emit_instruction(
-                        "lw",
-                        &format!("{}, {}($sp)", addr_reg, offset),
-                        identifier,
-                    );
-                    self.emit_instruction(
-                        "sw",
-                        &format!("{}, ({})\n", reg, addr_reg),
-                        &format!("*{} = {}", identifier, value),
-                    );
-                    allocator.free_temp(addr_reg);
-                    self.emit("");
-                } else {
-                    self.emit_store(reg, identifier, is_array_index, indexer, allocator);
-                }
-
-                allocator.free_temp(reg);
+                self.spill(dest);
             }
 
-            Expr::Identifier(name) => {
-                let offset = match allocator.get_stack_variable_offset(name) {
-                    Some(n) => n,
-                    None => panic!("Variable {} not found in stack", name),
-                };
+            None => self.emit(""),
+        }
+    }
 
+    // Like o32 the first four still get a (unused) slot each on the stack,
+    // so argument i is at i * 4
+    fn pass_arguments(&mut self, args: &[Temp]) {
+        for (i, arg) in args.iter().enumerate() {
+            if i >= 4 {
+                let value = self.source(*arg, 0);
                 self.emit_instruction(
-                    "lw",
-                    &format!("{}, {}($sp)", reg, offset),
-                    &format!("Load value at {}($sp) into register {}", offset, reg),
+                    "sw",
+                    &format!("{}, {}($sp)", value, i * 4),
+                    &format!("Pass argument {} on the stack", i),
                 );
-
-                self.emit_store(reg, identifier, is_array_index, indexer, allocator);
-                allocator.free_temp(reg);
+                continue;
             }
 
-            Expr::BoolLiteral(value) => {
-                let int_value = if *value { 1 } else { 0 };
-                self.emit_instruction(
-                    "li",
-                    &format!("{}, {}", reg, int_value),
-                    &format!("Load boolean {} into register {}", value, reg),
-                );
-                self.emit_store(reg, identifier, is_array_index, indexer, allocator);
-                allocator.free_temp(reg);
+            match self.allocation.locations[arg] {
+                Location::Register(reg) => self.emit_instruction(
+                    "move",
+                    &format!("$a{}, {}", i, reg),
+                    &format!("Move argument {} into $a{}", i, i),
+                ),
+
+                Location::Spilled(n) => self.emit_instruction(
+                    "lw",
+                    &format!("$a{}, {}($sp)", i, self.frame.spills + n * 4),
+                    &format!("Load argument {} into $a{}", i, i),
+                ),
             }
+        }
+    }
 
-            Expr::FunctionCall {
-                function_name,
-                arguments,
-                is_builtin_function,
-                builtin_function_type,
-            } => {
-                if arguments.len() > 4 {
-                    panic!("Maximum of 4 arguments allowed for {}", function_name);
-                }
+    // Syscalls leave the temps alone, there's nothing to keep
+    fn generate_builtin(
+        &mut self,
+        dest: Option<Temp>,
+        builtin: &BuiltinFunctionType,
+        args: &[Temp],
+    ) {
+        self.pass_arguments(args);
 
-                let registers = ["$a0", "$a1", "$a2", "$a3"];
-
-                for i in 0..arguments.len() {
-                    match &arguments[i].expr {
-                        Expr::Integer(n) => {
-                            self.emit_instruction(
-                                "li",
-                                &format!("{}, {}", registers[i], n),
-                                &format!("Load {} into register {}", n, registers[i]),
-                            );
-                        }
-                        Expr::Identifier(name) => {
-                            let offset = self.get_offset_with_panic(allocator, &name);
-                            self.emit_instruction(
-                                "lw",
-                                &format!("{}, {}($sp)", registers[i], offset),
-                                &format!("Load value at {}($sp) into {}", offset, registers[i]),
-                            );
-                        }
-                        _ => panic!(
-                            "Argument Expr {:?} not implemented in generate_variable_assignment",
-                            arguments[i].expr
-                        ),
-                    }
-                }
+        // sread(n) reads into a fresh n byte buffer from sbrk and hands it back
+        if let BuiltinFunctionType::StringRead = builtin {
+            self.emit_instruction(
+                "li",
+                "$v0, 9",
+                "Allocate the buffer with sbrk, the size is already in $a0",
+            );
+            self.emit_instruction("syscall", "", "");
+            self.emit_instruction("move", "$a1, $a0", "Read at most the buffer size");
+            self.emit_instruction("move", "$a0, $v0", "Read into the new buffer");
+        }
 
-                if *is_builtin_function {
-                    let syscall_number = match builtin_function_type {
-                        Some(BuiltinFunctionType::IntegerPrint) => 1,
-                        Some(BuiltinFunctionType::IntegerRead) => 5,
-                        Some(BuiltinFunctionType::StringRead) => 8,
-                        Some(BuiltinFunctionType::StringPrint) => 4,
-                        Some(BuiltinFunctionType::IntegerRandomRange) => 42,
-                        None => -1,
-                    };
-                    self.emit_instruction(
-                        "li",
-                        &format!("$v0, {}", syscall_number),
-                        "Load syscall number into $v0",
-                    );
-                    self.emit_instruction("syscall", "", "");
-                } else {
-                    self.emit_instruction(
-                        "jal",
-                        function_name,
-                        &format!("Call function {}", function_name),
-                    );
-                }
+        let syscall_number = match builtin {
+            BuiltinFunctionType::IntegerPrint => 1,
+            BuiltinFunctionType::IntegerRead => 5,
+            BuiltinFunctionType::StringRead => 8,
+            BuiltinFunctionType::StringPrint => 4,
+            BuiltinFunctionType::IntegerRandomRange => 42,
+            BuiltinFunctionType::CharPrint => 11,
+            BuiltinFunctionType::CharRead => 12,
+            BuiltinFunctionType::Allocate => 9,
+        };
 
-                let result_reg = if function_name == "irandrange" {
-                    "$a0"
-                } else {
-                    "$v0"
-                };
+        self.emit_instruction(
+            "li",
+            &format!("$v0, {}", syscall_number),
+            &format!(
+                "Load the appropriate syscall number {} into $v0",
+                syscall_number
+            ),
+        );
+        self.emit_instruction("syscall", "", "");
 
-                self.emit_instruction(
-                    "lw",
-                    &format!("{}, {}", reg, result_reg),
-                    &format!("Move result into temp register {}", reg),
-                );
+        // The random range syscall hands its result back in $a0,
+        // and sread's buffer is still sitting there
+        let result = match builtin {
+            BuiltinFunctionType::IntegerRandomRange | BuiltinFunctionType::StringRead => "$a0",
+            _ => "$v0",
+        };
 
-                self.emit_store(reg, identifier, is_array_index, indexer, allocator);
-                allocator.free_temp(reg);
+        match dest {
+            Some(dest) => {
+                let d = self.destination(dest);
+                self.emit_instruction("move", &format!("{}, {}", d, result), "");
+                self.spill(dest);
             }
 
-            Expr::BinaryOp {
+            None => self.emit(""),
+        }
+    }
+
+    fn generate_terminator(&mut self, function: &Function, block: &Block, next: usize) {
+        let label = |i: usize| function.blocks[i].label.as_str();
+
+        match &block.terminator {
+            Terminator::Jump(target) => {
+                if target.0 != next {
+                    self.emit_instruction("j", label(target.0), "");
+                    self.emit("");
+                }
+            }
+
+            // Whichever side comes next is fallen into
+            Terminator::Branch {
+                condition,
                 left,
-                operator,
                 right,
-                is_not: _,
+                then,
+                otherwise,
             } => {
-                match &**left {
-                    Expr::Identifier(name) => {
-                        let offset = self.get_offset_with_panic(allocator, name);
-                        self.emit_instruction(
-                            "lw",
-                            &format!("{}, {}($sp)", reg, offset),
-                            &format!(
-                                "Load value from {}($sp) (variable {}) into left register {}",
-                                name, offset, reg
-                            ),
-                        );
-                    }
-                    Expr::Integer(value) => {
-                        self.emit_instruction(
-                            "li",
-                            &format!("{}, {}", reg, value),
-                            &format!("Load {} into register {}", value, reg),
-                        );
-                    }
-                    _ => panic!("Unsupported left operand in binary operation"),
+                if otherwise.0 == next {
+                    self.generate_branch(*condition, *left, right, label(then.0), true);
+                } else if then.0 == next {
+                    self.generate_branch(*condition, *left, right, label(otherwise.0), false);
+                } else {
+                    self.generate_branch(*condition, *left, right, label(then.0), true);
+                    self.emit_instruction("j", label(otherwise.0), "");
                 }
 
-                let reg2 = match allocator.allocate_temp() {
-                    Some(r) => r,
-                    None => panic!("Ran out of temporary registers"),
-                };
+                self.emit("");
+            }
 
-                match &**right {
-                    Expr::Identifier(name) => {
-                        let right_r = match allocator.get_variable_register(name) {
-                            Some(r) => r,
-                            None => panic!(
-                                "Register or Offset not found for {} in generate_variable_assignment",
-                                name
-                            ),
-                        };
-                        if right_r.contains("($sp)") {
-                            let offset = self.get_offset_with_panic(allocator, name);
-                            self.emit_instruction(
-                                "lw",
-                                &format!("{}, {}($sp)", reg2, offset),
-                                &format!(
-                                    "Load value from {}($sp) (variable {}) into right register {}",
-                                    name, offset, reg2
-                                ),
-                            );
-                        } else {
-                            self.emit_instruction(
-                                "move",
-                                &format!("{}, {}", reg2, right_r),
-                                &format!(
-                                    "Load value from {} (variable {}) into right register {}",
-                                    name, right_r, reg2
-                                ),
-                            );
-                        }
-                    }
-                    Expr::Integer(value) => {
-                        self.emit_instruction(
-                            "li",
-                            &format!("{}, {}", reg2, value),
-                            &format!("Load {} into register {}", value, reg2),
-                        );
-                    }
-                    Expr::FunctionCall {
-                        function_name,
-                        arguments,
-                        is_builtin_function,
-                        builtin_function_type,
-                    } => {
-                        if arguments.len() > 4 {
-                            panic!("Maximum of 4 arguments allowed for {}", function_name);
-                        }
-
-                        let registers = ["$a0", "$a1", "$a2", "$a3"];
-
-                        for i in 0..arguments.len() {
-                            match &arguments[i].expr {
-                                Expr::Integer(n) => {
-                                    self.emit_instruction(
-                                        "li",
-                                        &format!("{}, {}", registers[i], n),
-                                        &format!("Load {} into register {}", n, registers[i]),
-                                    );
-                                }
-                                Expr::Identifier(name) => {
-                                    let offset = self.get_offset_with_panic(allocator, &name);
-                                    self.emit_instruction(
-                                        "lw",
-                                        &format!("{}, {}($sp)", registers[i], offset),
-                                        &format!(
-                                            "Load value at {}($sp) into {}",
-                                            offset, registers[i]
-                                        ),
-                                    );
-                                }
-                                _ => panic!(
-                                    "Argument Expr {:?} not implemented in generate_variable_assignment",
-                                    arguments[i].expr
-                                ),
-                            }
-                        }
-
-                        if *is_builtin_function {
-                            let syscall_number = match builtin_function_type {
-                                Some(BuiltinFunctionType::IntegerPrint) => 1,
-                                Some(BuiltinFunctionType::IntegerRead) => 5,
-                                Some(BuiltinFunctionType::StringRead) => 8,
-                                Some(BuiltinFunctionType::StringPrint) => 4,
-                                Some(BuiltinFunctionType::IntegerRandomRange) => 42,
-                                None => -1,
-                            };
-                            self.emit_instruction(
-                                "li",
-                                &format!("$v0, {}", syscall_number),
-                                "Load syscall number into $v0",
-                            );
-                            self.emit_instruction("syscall", "", "");
-                        } else {
-                            self.emit_instruction(
-                                "jal",
-                                function_name,
-                                &format!("Call function {}", function_name),
-                            );
-                        }
-
-                        self.emit_instruction(
+            Terminator::Return(value) => {
+                if let Some(value) = value {
+                    match self.allocation.locations[value] {
+                        Location::Register(reg) => self.emit_instruction(
                             "move",
-                            &format!("{}, $v0", reg2),
-                            &format!("Move return value from $v0 to {}", reg2),
-                        );
-                    }
-
-                    Expr::ArrayIndex {
-                        array_name,
-                        indexer,
-                    } => {
-                        let base_offset = self.get_offset_with_panic(allocator, array_name);
-                        let base_ptr_temp = self.get_temp_with_panic(allocator);
-                        let addr_temp = self.get_temp_with_panic(allocator);
+                            &format!("$v0, {}", reg),
+                            &format!("Move value from register {} to return register", reg),
+                        ),
 
-                        // Load base pointer
-                        self.emit_instruction(
-                            "lw",
-                            &format!("{}, {}($sp)", base_ptr_temp, base_offset),
-                            &format!("Load base pointer of {}", array_name),
-                        );
-
-                        // Compute element address
-                        match &**indexer {
-                            Expr::Identifier(name) => {
-                                let idx_offset = self.get_offset_with_panic(allocator, name);
-                                self.emit_instruction(
-                                    "lw",
-                                    &format!("{}, {}($sp)", addr_temp, idx_offset),
-                                    &format!("Load index {}", name),
-                                );
-                            }
-                            Expr::Integer(n) => {
-                                self.emit_instruction(
-                                    "li",
-                                    &format!("{}, {}", addr_temp, n),
-                                    &format!("Load index {}", n),
-                                );
-                            }
-                            _ => panic!("Unsupported indexer in ArrayIndex right operand"),
-                        }
-
-                        self.emit_instruction(
-                            "sll",
-                            &format!("{}, {}, 2", addr_temp, addr_temp),
-                            "Multiply index by 4",
-                        );
-                        self.emit_instruction(
-                            "add",
-                            &format!("{}, {}, {}", addr_temp, addr_temp, base_ptr_temp),
-                            "Compute element address",
-                        );
-
-                        // Load the value at that address into reg2
-                        self.emit_instruction(
+                        Location::Spilled(n) => self.emit_instruction(
                             "lw",
-                            &format!("{}, ({})", reg2, addr_temp),
-                            &format!("Load {}[i] into {}", array_name, reg2),
-                        );
-
-                        allocator.free_temp(base_ptr_temp);
-                        allocator.free_temp(addr_temp);
+                            &format!("$v0, {}($sp)", self.frame.spills + n * 4),
+                            "Load the return value",
+                        ),
                     }
-
-                    _ => panic!("Unsupported right operand in binary operation"),
                 }
 
-                match operator {
-                    BinaryOperator::Add => {
-                        self.emit_instruction(
-                        "add",
-                        &format!("{}, {}, {}", reg, reg, reg2),
-                        &format!(
-                            "Add values from register {} and {} and store it back into register {}",
-                            reg, reg2, reg
-                        ),
-                    );
-                    }
-                    BinaryOperator::Subtract => {
-                        self.emit_instruction(
-                        "sub",
-                        &format!("{}, {}, {}", reg, reg, reg2),
-                        &format!(
-                            "Sub values from register {} and {} and store it back into register {}",
-                            reg, reg2, reg
-                        ),
-                    );
-                    }
-                    BinaryOperator::Multiply => {
-                        self.emit_instruction(
-                        "mulo",
-                        &format!("{}, {}, {}", reg, reg, reg2),
-                        &format!(
-                            "Multiply values from register {} and {} and store it back into register {}",
-                            reg, reg2, reg
-                        ),
-                    );
-                    }
-                    _ => panic!("Unsupported binary operator: {:?}", operator),
+                if function.has_frame {
+                    self.generate_epilogue();
                 }
 
-                self.emit_store(reg, identifier, is_array_index, indexer, allocator);
-                allocator.free_temp(reg2);
-                allocator.free_temp(reg);
+                self.emit_instruction("jr", "$ra\n", "Jump back to the function caller's address");
             }
 
-            Expr::BitwiseShift {
-                identifier: shift_identifier,
-                shift_type,
-            } => {
-                let name = match &**shift_identifier {
-                    Expr::Identifier(s) => s,
-                    _ => panic!(
-                        "{:?} not implemented for BitwiseShift in generate_variable_assignment",
-                        &**shift_identifier
-                    ),
-                };
-
-                let shift_instruction = match shift_type {
-                    BitwiseShiftType::LeftShift => "sll",
-                    BitwiseShiftType::RightShift => "srl",
-                };
-
-                let offset = self.get_offset_with_panic(allocator, name);
-                self.emit_instruction(
-                    "lw",
-                    &format!("{}, {}($sp)", reg, offset),
-                    &format!(
-                        "Load value from {}($sp) (variable {}) into left register {}",
-                        name, offset, reg
-                    ),
-                );
-                self.emit_instruction(
-                    shift_instruction,
-                    &format!("{}, {}, {}", reg, reg, 1),
-                    &format!("Bitwise Shift {}", name),
-                );
-                self.emit_instruction(
-                    "sw",
-                    &format!("{}, {}($sp)", reg, offset),
-                    &format!("Store value from register {} into {}($sp)", reg, offset),
-                );
+            Terminator::OutOfBounds { line } => {
+                self.emit_instruction("li", &format!("$a0, {}", line), "Line to report");
+                self.emit_instruction("j", "_bounds_error", "");
                 self.emit("");
             }
 
-            _ => {
-                CompileError::TypeError {
-                    message: "Unsupported expression in variable assignment".to_string(),
-                    line: self.line,
-                };
-            }
+            Terminator::Unreachable => {}
         }
     }
 
-    fn generate_condition_and_branch(
-        &mut self,
-        condition: &Expr,
-        branch_label: &String,
-        allocator: &mut Allocator,
-        negate: bool,
-    ) {
-        if let Expr::BinaryOp {
-            left,
-            operator,
-            right,
-            is_not,
-        } = condition
-        {
-            let effective_is_not = if negate { !*is_not } else { *is_not };
+    fn generate_epilogue(&mut self) {
+        self.emit_instruction(
+            "lw",
+            &format!("$ra, {}($sp)", self.frame.ra),
+            &format!(
+                "Load stored return address from {}($sp) back into $ra",
+                self.frame.ra
+            ),
+        );
 
-            if **right == Expr::Empty && *operator == BinaryOperator::Equal {
-                let left_reg = match allocator.allocate_temp() {
-                    Some(reg) => reg,
-                    None => panic!("Out of temp registers"),
-                };
+        for (reg, offset) in self.frame.saved.clone() {
+            self.emit_instruction(
+                "lw",
+                &format!("{}, {}($sp)", reg, offset),
+                &format!("Restore callee-saved register {}", reg),
+            );
+        }
 
-                match &**left {
-                    Expr::Identifier(name) => {
-                        let location = allocator.get_variable_location(name);
-                        if location == VariableLocation::Stack {
-                            let offset = allocator.get_stack_variable_offset(name).unwrap();
-                            self.emit_instruction(
-                                "lw",
-                                &format!("{}, {}($sp)", left_reg, offset),
-                                &format!("Load boolean {} from {}($sp)", name, offset),
-                            );
-                        } else {
-                            let reg = allocator.get_argument_register(name).unwrap();
-                            self.emit_instruction(
-                                "move",
-                                &format!("{}, {}", left_reg, reg),
-                                &format!("Move boolean {} from {}", name, reg),
-                            );
-                        }
-                    }
+        self.emit_instruction(
+            "addi",
+            &format!("$sp, $sp, {}", self.frame.size),
+            &format!("Deallocate {} bytes of stack space", self.frame.size),
+        );
+    }
 
-                    Expr::BoolLiteral(b) => {
-                        let n = if *b { 1 } else { 0 };
+    // Branches to target when the condition is when. An unsigned compare
+    // has no branch of its own, so it's worked out in a scratch register first
+    fn generate_branch(
+        &mut self,
+        condition: Condition,
+        left: Temp,
+        right: &Operand,
+        target: &str,
+        when: bool,
+    ) {
+        let l = self.source(left, 0);
+        let r = self.register(right, 1);
 
-                        self.emit_instruction(
-                            "li",
-                            &format!("{}, {}", left_reg, n),
-                            "Load boolean",
-                        );
-                    }
-                    _ => panic!(
-                        "Unhandled left expression in boolean condition: {:?}",
-                        &**left
-                    ),
-                }
+        let instruction = match (condition, when) {
+            (Condition::Less, true) | (Condition::GreaterEqual, false) => "blt",
+            (Condition::LessEqual, true) | (Condition::Greater, false) => "ble",
+            (Condition::Greater, true) | (Condition::LessEqual, false) => "bgt",
+            (Condition::GreaterEqual, true) | (Condition::Less, false) => "bge",
+            (Condition::Equal, true) | (Condition::NotEqual, false) => "beq",
+            (Condition::NotEqual, true) | (Condition::Equal, false) => "bne",
 
-                let branch_type = if effective_is_not { "beq" } else { "bne" };
+            (Condition::Below, _) => {
+                let scratch = SCRATCH[1];
 
+                self.emit_instruction("sltu", &format!("{}, {}, {}", scratch, l, r), "");
                 self.emit_instruction(
-                    branch_type,
-                    &format!("{}, $zero, {}", left_reg, branch_label),
-                    &format!("Branch to {} based on boolean test", branch_label),
+                    if when { "bne" } else { "beq" },
+                    &format!("{}, $zero, {}", scratch, target),
+                    "",
                 );
-
-                self.emit("");
-                allocator.free_temp(left_reg);
                 return;
             }
+        };
 
-            let branch_type = match (operator, effective_is_not) {
-                (BinaryOperator::LessThan, true) => "bge",
-                (BinaryOperator::LessThan, false) => "blt",
-                (BinaryOperator::LessEqual, true) => "bgt",
-                (BinaryOperator::LessEqual, false) => "ble",
-                (BinaryOperator::GreaterThan, true) => "ble",
-                (BinaryOperator::GreaterThan, false) => "bgt",
-                (BinaryOperator::GreaterEqual, true) => "blt",
-                (BinaryOperator::GreaterEqual, false) => "bge",
-                (BinaryOperator::Equal, true) => "bne",
-                (BinaryOperator::Equal, false) => "beq",
-                (BinaryOperator::NotEqual, true) => "beq",
-                (BinaryOperator::NotEqual, false) => "bne",
-                _ => panic!(
-                    "{:?} not implemented in generate_condition_and_branch",
-                    operator
-                ),
-            };
-
-            let left_reg = match allocator.allocate_temp() {
-                Some(reg) => reg,
-                None => {
-                    panic!("Out of temp registers in generate_condition_and_branch")
-                }
-            };
-
-            let right_reg = match allocator.allocate_temp() {
-                Some(reg) => reg,
-                None => {
-                    panic!("Out of temp registers in generate_condition_and_branch")
-                }
-            };
-
-            match &**left {
-                Expr::Identifier(name) => {
-                    let location = allocator.get_variable_location(name);
-                    if location == VariableLocation::Stack {
-                        let offset = match allocator.get_stack_variable_offset(name) {
-                            Some(n) => n,
-                            None => {
-                                panic!("Variable {} doesn't have offset", name);
-                            }
-                        };
-
-                        self.emit_instruction(
-                            "lw",
-                            &format!("{}, {}($sp)", left_reg, offset),
-                            &format!(
-                                "Load value from {}($sp) into left register {}",
-                                offset, left_reg
-                            ),
-                        );
-                    } else {
-                        let reg = match allocator.get_argument_register(name) {
-                            Some(r) => r,
-                            None => {
-                                panic!("Argument register not found for {}", name);
-                            }
-                        };
-
-                        self.emit_instruction(
-                            "move",
-                            &format!("{}, {}", left_reg, reg),
-                            &format!(
-                                "Move value from register {} into left register {}",
-                                reg, left_reg
-                            ),
-                        );
-                    }
-                }
-
-                Expr::Integer(n) => {
-                    self.emit_instruction(
-                        "li",
-                        &format!("{}, {}", left_reg, n),
-                        &format!("Store {} in left register {}", n, left_reg),
-                    );
-                }
-
-                __ => {
-                    panic!(
-                        "Unhandled left-side expression in condition in generate_condition_and_branch: {:?}",
-                        left
-                    );
-                }
-            }
-
-            match &**right {
-                Expr::Identifier(name) => {
-                    let location = allocator.get_variable_location(name);
-                    if location == VariableLocation::Stack {
-                        let offset = match allocator.get_stack_variable_offset(name) {
-                            Some(n) => n,
-                            None => {
-                                panic!("Variable {} doesn't have offset", name);
-                            }
-                        };
-
-                        self.emit_instruction(
-                            "lw",
-                            &format!("{}, {}($sp)", right_reg, offset),
-                            &format!(
-                                "Load value from {}($sp) into right register {}",
-                                offset, right_reg
-                            ),
-                        );
-                    } else {
-                        let reg = match allocator.get_argument_register(name) {
-                            Some(r) => r,
-                            None => {
-                                panic!("Argument register not found for {}", name);
-                            }
-                        };
-
-                        self.emit_instruction(
-                            "move",
-                            &format!("{}, {}", right_reg, reg),
-                            &format!(
-                                "Move value from register {} into right register {}",
-                                reg, right_reg
-                            ),
-                        );
-                    }
-                }
+        self.emit_instruction(instruction, &format!("{}, {}, {}", l, r, target), "");
+    }
 
-                Expr::Integer(n) => {
-                    self.emit_instruction(
-                        "li",
-                        &format!("{}, {}", right_reg, n),
-                        &format!("Load {} into right register {}", n, right_reg),
-                    );
-                }
+    // The register temp is in, loading it into a scratch register first
+    // if it didn't get one
+    fn source(&mut self, temp: Temp, scratch: usize) -> String {
+        match self.allocation.locations[&temp] {
+            Location::Register(reg) => reg.to_string(),
 
-                _ => {
-                    panic!(
-                        "Unhandled right-side expression in condition in generate_condition_and_branch: {:?}",
-                        right
-                    );
-                }
+            Location::Spilled(n) => {
+                let reg = SCRATCH[scratch];
+                self.emit_instruction(
+                    "lw",
+                    &format!("{}, {}($sp)", reg, self.frame.spills + n * 4),
+                    "",
+                );
+                reg.to_string()
             }
-
-            self.emit_instruction(
-                branch_type,
-                &format!("{}, {}, {}", left_reg, right_reg, branch_label),
-                &format!("Compare values at left register {} and right register {} and branch to {} if needed", left_reg, right_reg, branch_label)
-            );
-
-            self.emit("");
-            allocator.free_temp(left_reg);
-            allocator.free_temp(right_reg);
         }
     }
 
-    fn generate_while(
-        &mut self,
-        body_label: &String,
-        end_label: &String,
-        condition: &Expr,
-        body: &Vec<Statement>,
-        allocator: &mut Allocator,
-    ) {
-        self.emit_instruction(
-            "j",
-            end_label,
-            &format!("Jump to while loop end at label {}", end_label),
-        );
-
-        self.emit("");
-
-        self.emit_label(body_label);
+    // Like source, but a constant is built in the scratch register
+    // unless it's 0, which $zero already is
+    fn register(&mut self, operand: &Operand, scratch: usize) -> String {
+        match operand {
+            Operand::Temp(temp) => self.source(*temp, scratch),
+            Operand::Const(0) => "$zero".to_string(),
 
-        for stmt in body {
-            self.generate_statement(stmt, allocator);
+            Operand::Const(n) => {
+                let reg = SCRATCH[scratch];
+                self.emit_instruction("li", &format!("{}, {}", reg, n), "");
+                reg.to_string()
+            }
         }
-
-        self.emit_label(end_label);
-
-        self.generate_condition_and_branch(condition, body_label, allocator, false);
     }
 
-    fn generate_for(
-        &mut self,
-        init: &Box<Statement>,
-        body_label: &String,
-        end_label: &String,
-        condition: &Expr,
-        var_change: &Box<Statement>,
-        body: &Vec<Statement>,
-        allocator: &mut Allocator,
-    ) {
-        if let Statement::VariableDeclaration {
-            var_type,
-            identifier,
-            operation,
-        } = &**init
-        {
-            self.generate_variable_declaration(var_type, identifier, operation, allocator);
-        }
-
-        self.emit_instruction(
-            "j",
-            end_label,
-            &format!("Jump to for loop end at label {}", end_label),
-        );
-
-        self.emit("");
-        self.emit_label(body_label);
-
-        for stmt in body {
-            self.generate_statement(stmt, allocator);
+    // Where an instruction should write temp. One that didn't get a
+    // register is written to scratch first and stored by spill afterwards
+    fn destination(&self, temp: Temp) -> String {
+        match self.allocation.locations[&temp] {
+            Location::Register(reg) => reg.to_string(),
+            Location::Spilled(_) => SCRATCH[0].to_string(),
         }
+    }
 
-        if let Statement::VariableAssignment {
-            identifier,
-            operation,
-            is_dereference,
-            is_array_index,
-            indexer,
-        } = &**var_change
-        {
-            self.generate_variable_assignment(
-                identifier,
-                operation,
-                is_dereference,
-                is_array_index,
-                indexer,
-                allocator,
+    fn spill(&mut self, temp: Temp) {
+        if let Location::Spilled(n) = self.allocation.locations[&temp] {
+            self.emit_instruction(
+                "sw",
+                &format!("{}, {}($sp)", SCRATCH[0], self.frame.spills + n * 4),
+                "",
             );
         }
-
-        self.emit_label(end_label);
-
-        self.generate_condition_and_branch(condition, body_label, allocator, false);
     }
 
-    fn generate_if(
-        &mut self,
-        label: &String,
-        condition: &Expr,
-        body: &Vec<Statement>,
-        allocator: &mut Allocator,
-    ) {
-        self.generate_condition_and_branch(condition, label, allocator, false);
-
-        for stmt in body {
-            self.generate_statement(stmt, allocator);
+    // base + offset as a lw/sw operand
+    fn address(&mut self, base: &Base, offset: i32) -> String {
+        match base {
+            Base::Slot(slot) => format!("{}($sp)", self.frame.slots[slot.0] as i32 + offset),
+            Base::Label(label) => label_plus(label, offset),
+            Base::Temp(temp) => format!("{}({})", offset, self.source(*temp, 1)),
         }
-
-        self.emit_label(label);
     }
 
-    // fn generate_builtin_function(
-    //     &mut self,
-    //     function_type: &BuiltinFunctionType,
-    //     arguments: &Vec<Argument>,
-    //     allocator: &mut Allocator,
-    // ) {
-    //     let syscall_number = match function_type {
-    //         BuiltinFunctionType::IntegerPrint => 1,
-    //         BuiltinFunctionType::StringPrint => 4,
-    //         BuiltinFunctionType::IntegerRead => 5,
-    //         BuiltinFunctionType::StringRead => 8,
-    //     };
-
-    //     // Looping through the arguments here is kinda useless because
-    //     // We're only using a0 I think it's fine for now
-    //     for arg in arguments {
-    //         match &arg.expr {
-    //             Expr::Identifier(name) => {
-    //                 let offset = self.get_offset_with_panic(allocator, &name);
-    //                 self.emit_instruction(
-    //                     "lw",
-    //                     &format!("$a0, {}($sp)", offset),
-    //                     &format!("Load argument {} into $a0 from {}($sp)", name, offset),
-    //                 );
-    //             }
-
-    //             Expr::Integer(n) => {
-    //                 self.emit_instruction(
-    //                     "li",
-    //                     &format!("$a0, {}", n),
-    //                     &format!("Load {} into argument register $a0", n),
-    //                 );
-    //             }
-
-    //             Expr::StringLiteral(s) => {
-    //                 let label = match self.get_data_label_for_string(s) {
-    //                     Some(l) => l,
-    //                     None => {
-    //                         panic!("Label not found for {}", s);
-    //                     }
-    //                 };
-
-    //                 self.emit_instruction("la", &format!("$a0, {}", label), &format!("Load the address of the string stored at {} into argument register $a0", label));
-    //             }
-
-    //             _ => {
-    //                 panic!(
-    //                     "expr {:?} not implemented in generate_builtin_function",
-    //                     arg.expr
-    //                 );
-    //             }
-    //         }
-    //     }
-
-    //     self.emit_instruction(
-    //         "li",
-    //         &format!("$v0, {}", syscall_number),
-    //         &format!(
-    //             "Load the appropriate syscall number {} into $v0",
-    //             syscall_number
-    //         ),
-    //     );
-    //     self.emit_instruction("syscall", "", "");
-    //     self.emit("");
-    // }
+    // Shared by every bounds check, prints the line in $a0 and exits with 1
+    fn generate_bounds_error(&mut self) {
+        self.emit_label("_bounds_error");
+        self.emit_instruction("move", "$t0, $a0", "Keep the line number");
+        self.emit_instruction("la", "$a0, _bounds_message", "");
+        self.emit_instruction("li", "$v0, 4", "");
+        self.emit_instruction("syscall", "", "");
+        self.emit_instruction("move", "$a0, $t0", "");
+        self.emit_instruction("li", "$v0, 1", "");
+        self.emit_instruction("syscall", "", "");
+        self.emit_instruction("li", "$a0, 10", "Newline");
+        self.emit_instruction("li", "$v0, 11", "");
+        self.emit_instruction("syscall", "", "");
+        self.emit_instruction("li", "$a0, 1", "Exit code");
+        self.emit_instruction("li", "$v0, 17", "Exit with a code");
+        self.emit_instruction("syscall", "", "");
+    }
 
     fn generate_data_label(&mut self, statement: &Statement) {
-        if let Statement::DataDeclaration {
+        if let StatementKind::DataDeclaration {
             label,
             storage_type,
-            value,
-        } = statement
+            values,
+        } = &statement.kind
         {
-            match storage_type {
-                DataStorageType::Asciiz => {
-                    self.emit(&format!("\t{}: .asciiz \"{}\"", label, value));
-                }
+            if *storage_type == DataStorageType::Asciiz
+                && let [value @ DataValue::String(text)] = values.as_slice()
+                && !value.is_quotable()
+            {
+                let mut bytes: Vec<DataValue> =
+                    text.bytes().map(|b| DataValue::Integer(b as i32)).collect();
+                bytes.push(DataValue::Integer(0));
+
+                let bytes_declaration = Statement::new(
+                    StatementKind::DataDeclaration {
+                        label: label.clone(),
+                        storage_type: DataStorageType::Byte,
+                        values: bytes,
+                    },
+                    statement.span.clone(),
+                );
+
+                return self.generate_data_label(&bytes_declaration);
             }
-        }
-    }
 
-    fn get_data_label_for_string(&self, value: &String) -> Option<String> {
-        for stmt in &self.program.segments.data.body {
-            if let Statement::DataDeclaration {
-                label,
-                storage_type: DataStorageType::Asciiz,
-                value: val,
-            } = stmt
-            {
-                if val == value {
-                    return Some(label.clone());
-                }
+            let directive = match storage_type {
+                DataStorageType::Asciiz => ".asciiz".to_string(),
+                DataStorageType::Word => ".word".to_string(),
+                DataStorageType::Byte => ".byte".to_string(),
+                DataStorageType::Space(n) => format!(".space {}", n),
+                DataStorageType::Align(n) => format!(".align {}", n),
+            };
+
+            let mut line = match label {
+                Some(label) => format!("\t{}: {}", label, directive),
+                None => format!("\t{}", directive),
+            };
+
+            if !values.is_empty() {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                line.push(' ');
+                line.push_str(&values.join(", "));
             }
-        }
 
-        None
+            self.emit(&line);
+        }
     }
+}
 
-    // Needs to be changed in the future
-    fn get_offset_with_panic(&self, allocator: &Allocator, name: &String) -> usize {
-        match allocator.get_stack_variable_offset(name) {
-            Some(n) => n,
-            None => {
-                panic!("Variable {} not found in stack", name);
+// The blocks a terminator has to jump to, given which one comes next.
+// Those need a label, the next one is just fallen into
+fn jumps(terminator: &Terminator, next: usize) -> Vec<usize> {
+    match terminator {
+        Terminator::Jump(target) if target.0 != next => vec![target.0],
+
+        Terminator::Branch {
+            then, otherwise, ..
+        } => {
+            if otherwise.0 == next {
+                vec![then.0]
+            } else if then.0 == next {
+                vec![otherwise.0]
+            } else {
+                vec![then.0, otherwise.0]
             }
         }
+
+        _ => Vec::new(),
     }
+}
 
-    fn get_temp_with_panic(&self, allocator: &mut Allocator) -> Register {
-        let t = allocator.allocate_temp();
-        match t {
-            Some(r) => {
-                return r;
-            }
+// The version of op that takes n as an immediate, if n fits in one.
+// Anything else gets n built in a register first
+fn immediate_form(op: BinaryOp, n: i32) -> Option<&'static str> {
+    let signed = (-32768..=32767).contains(&n);
+    let unsigned = (0..=65535).contains(&n);
+
+    match op {
+        BinaryOp::Add if signed => Some("addi"),
+        BinaryOp::Subtract if signed => Some("subi"),
+        BinaryOp::BitwiseAnd if unsigned => Some("andi"),
+        BinaryOp::BitwiseOr if unsigned => Some("ori"),
+        BinaryOp::BitwiseXor if unsigned => Some("xori"),
+        BinaryOp::ShiftLeft => Some("sll"),
+        BinaryOp::ShiftRight => Some("sra"),
+        BinaryOp::Compare(Condition::Less) if signed => Some("slti"),
+        _ => None,
+    }
+}
 
-            None => panic!("Ran out of temp registers"),
-        }
+fn label_plus(label: &str, offset: i32) -> String {
+    if offset == 0 {
+        label.to_string()
+    } else {
+        format!("{}+{}", label, offset)
     }
 }
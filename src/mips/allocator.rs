@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 use std::fmt::{self};
 
-use crate::parser::ast::{Expr, Statement};
+use crate::ir::{Function, Inst, Temp};
 
-#[derive(PartialEq, Clone, Debug, Copy)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Copy)]
 pub enum Register {
     // Zero,
     // AT,
@@ -39,10 +39,21 @@ pub enum Register {
     // RA,
 }
 
-#[derive(PartialEq, Debug)]
-pub enum VariableLocation {
-    Stack,
-    ArgumentRegister,
+impl Register {
+    // $s registers have to survive calls, so whoever uses one saves it
+    pub fn is_callee_saved(&self) -> bool {
+        matches!(
+            self,
+            Register::S0
+                | Register::S1
+                | Register::S2
+                | Register::S3
+                | Register::S4
+                | Register::S5
+                | Register::S6
+                | Register::S7
+        )
+    }
 }
 
 impl fmt::Display for Register {
@@ -85,192 +96,195 @@ impl fmt::Display for Register {
     }
 }
 
-pub struct Allocator {
-    used_registers: Vec<Register>,
-    argument_registers: HashMap<String, Register>,
-    stack_variables: HashMap<String, usize>,
-    stack_size: usize,
-    next_offset: usize,
-}
+// Handed out lowest first. $t8 and $t9 are kept back for the backend,
+// temps that didn't get a register are loaded into them when needed
+const TEMPORARIES: [Register; 8] = [
+    Register::T0,
+    Register::T1,
+    Register::T2,
+    Register::T3,
+    Register::T4,
+    Register::T5,
+    Register::T6,
+    Register::T7,
+];
+
+const SAVED: [Register; 8] = [
+    Register::S0,
+    Register::S1,
+    Register::S2,
+    Register::S3,
+    Register::S4,
+    Register::S5,
+    Register::S6,
+    Register::S7,
+];
+
+pub const SCRATCH: [Register; 2] = [Register::T8, Register::T9];
 
-impl Allocator {
-    pub fn new() -> Self {
-        Allocator {
-            used_registers: Vec::new(),
-            argument_registers: HashMap::new(),
-            stack_variables: HashMap::new(),
-            stack_size: 0,
-            next_offset: 0,
-        }
-    }
+#[derive(PartialEq, Clone, Debug, Copy)]
+pub enum Location {
+    Register(Register),
 
-    pub fn allocate_temp(&mut self) -> Option<Register> {
-        let temp_registers = vec![
-            Register::T0,
-            Register::T1,
-            Register::T2,
-            Register::T3,
-            Register::T4,
-            Register::T5,
-            Register::T6,
-            Register::T7,
-            Register::T8,
-            Register::T9,
-            Register::S0,
-            Register::S1,
-            Register::S2,
-            Register::S3,
-            Register::S4,
-            Register::S5,
-            Register::S6,
-            Register::S7,
-        ];
-
-        for reg in temp_registers {
-            if !self.used_registers.contains(&reg) {
-                self.used_registers.push(reg.clone());
-                return Some(reg);
-            }
-        }
+    // The nth word of the frame set aside for these
+    Spilled(usize),
+}
 
-        None
-    }
+#[derive(Default)]
+pub struct Allocation {
+    pub locations: HashMap<Temp, Location>,
+    pub spills: usize,
 
-    pub fn free_temp(&mut self, reg: Register) {
-        if let Some(pos) = self.used_registers.iter().position(|x| *x == reg) {
-            self.used_registers.remove(pos);
-        }
-    }
+    // Every $s register handed out, the function has to restore these
+    // before returning
+    pub saved: Vec<Register>,
 
-    pub fn calculate_needed_stack_space(
-        &mut self,
-        body: &Vec<Statement>,
-        num_params: usize,
-    ) -> usize {
-        self.stack_size += 4 * (num_params + 1);
+    // $t registers holding something still needed after the call at
+    // (block, instruction), the caller keeps them in its frame meanwhile
+    pub live_across: HashMap<(usize, usize), Vec<Register>>,
+}
 
-        self.calculate_needed_stack_space_helper(body);
+// A temp from the first instruction that mentions it to the last
+struct Interval {
+    temp: Temp,
+    start: usize,
+    end: usize,
+    crosses_call: bool,
+}
 
-        self.stack_size
-    }
+// Linear scan. Instructions are numbered in layout order and each temp
+// gets a register for its whole interval, see ir::Function for why that's
+// enough. Anything live across a call goes in an $s register if there's
+// one free, the callee has to leave those alone anyway. When every
+// register is taken, whichever interval ends last goes to the frame
+pub fn allocate(function: &Function) -> Allocation {
+    let mut starts = vec![usize::MAX; function.temps];
+    let mut ends = vec![0; function.temps];
+    let mut calls = Vec::new();
+    let mut position = 0;
+
+    let mut mention = |temp: Temp, position: usize| {
+        starts[temp.0] = starts[temp.0].min(position);
+        ends[temp.0] = ends[temp.0].max(position);
+    };
+
+    for (b, block) in function.blocks.iter().enumerate() {
+        for (i, inst) in block.instructions.iter().enumerate() {
+            for temp in inst.uses().into_iter().chain(inst.dest()) {
+                mention(temp, position);
+            }
 
-    fn calculate_needed_stack_space_helper(&mut self, statements: &[Statement]) {
-        for stmt in statements {
-            match stmt {
-                Statement::VariableDeclaration { operation, .. } => {
-                    match operation {
-                        Expr::BinaryOp { left, .. } => {
-                            match &**left {
-                                Expr::ArrayInitializer { size, .. } => {
-                                    // size * 4 for elements, the variable's own slot
-                                    // is added by the += 4 below, giving (size + 1) * 4 total
-                                    self.stack_size += size * 4;
-                                }
-                                _ => {}
-                            }
-                        }
-                        _ => {}
-                    }
-                    self.stack_size += 4;
-                }
-
-                Statement::While { body, .. } | Statement::If { body, .. } => {
-                    self.calculate_needed_stack_space_helper(body);
-                }
-
-                Statement::For { body, .. } => {
-                    self.stack_size += 4;
-                    self.calculate_needed_stack_space_helper(body);
-                }
-
-                _ => {}
+            if let Inst::Call { .. } = inst {
+                calls.push((position, (b, i)));
             }
-        }
-    }
 
-    // pub fn add_argument(&mut self, name: &str) {
-    //     let arg_registers = vec![Register::A0, Register::A1, Register::A2, Register::A3];
+            position += 1;
+        }
 
-    //     let mut reg_to_use = Register::Zero;
+        for temp in block.terminator.uses() {
+            mention(temp, position);
+        }
 
-    //     for arg_reg in arg_registers {
-    //         if !self.used_registers.contains(&arg_reg) {
-    //             reg_to_use = arg_reg;
-    //             self.used_registers.push(arg_reg.clone());
-    //             break;
-    //         }
-    //     }
+        position += 1;
+    }
 
-    //     if reg_to_use == Register::Zero {
-    //         panic!("Out of Argument registers");
-    //     }
+    // A call's own arguments and result don't count, only what's
+    // there before it and still needed after
+    let mut intervals: Vec<Interval> = (0..function.temps)
+        .filter(|&t| starts[t] != usize::MAX)
+        .map(|t| Interval {
+            temp: Temp(t),
+            start: starts[t],
+            end: ends[t],
+            crosses_call: calls
+                .iter()
+                .any(|(call, _)| starts[t] < *call && *call < ends[t]),
+        })
+        .collect();
+
+    intervals.sort_by_key(|interval| interval.start);
+
+    let mut locations = HashMap::new();
+    let mut spills = 0;
+
+    // (end, temp, register) of every interval holding a register right now
+    let mut active: Vec<(usize, Temp, Register)> = Vec::new();
+
+    for interval in &intervals {
+        // An interval ending where this one starts is done with its register,
+        // the instruction reads it before writing the new one
+        active.retain(|(end, ..)| *end > interval.start);
+
+        let (preferred, other) = if interval.crosses_call {
+            (SAVED, TEMPORARIES)
+        } else {
+            (TEMPORARIES, SAVED)
+        };
 
-    //     self.argument_registers.insert(name.to_string(), reg_to_use);
-    // }
+        let free = preferred
+            .iter()
+            .chain(other.iter())
+            .find(|reg| !active.iter().any(|(_, _, taken)| taken == *reg));
 
-    pub fn get_argument_register(&self, name: &str) -> Option<String> {
-        let reg = match self.argument_registers.get(name) {
-            Some(r) => r,
-            None => {
-                panic!("{} not found in argument registers", name);
-            }
-        };
+        if let Some(reg) = free {
+            locations.insert(interval.temp, Location::Register(*reg));
+            active.push((interval.end, interval.temp, *reg));
+            continue;
+        }
 
-        Some(reg.to_string())
-    }
+        let furthest = active
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (end, ..))| *end)
+            .map(|(i, (end, ..))| (i, *end));
 
-    pub fn add_stack_variable(&mut self, name: &str) {
-        let offset = self.next_offset; // use running byte position
-        self.next_offset += 4;
-        self.stack_variables.insert(name.to_string(), offset);
-    }
+        match furthest {
+            Some((i, end)) if end > interval.end => {
+                let (_, victim, reg) = active[i];
 
-    pub fn reserve_array_elements(&mut self, num_elements: usize) {
-        self.next_offset += num_elements * 4;
-    }
+                locations.insert(victim, Location::Spilled(spills));
+                locations.insert(interval.temp, Location::Register(reg));
+                active[i] = (interval.end, interval.temp, reg);
+            }
 
-    pub fn get_stack_size(&self) -> &usize {
-        &self.stack_size
-    }
+            _ => {
+                locations.insert(interval.temp, Location::Spilled(spills));
+            }
+        }
 
-    pub fn get_stack_variable_offset(&self, name: &str) -> Option<usize> {
-        self.stack_variables.get(name).cloned()
+        spills += 1;
     }
 
-    pub fn get_variable_location(&self, name: &str) -> VariableLocation {
-        if self.stack_variables.contains_key(name) {
-            return VariableLocation::Stack;
-        } else {
-            return VariableLocation::ArgumentRegister;
-        }
+    let mut saved: Vec<Register> = locations
+        .values()
+        .filter_map(|location| match location {
+            Location::Register(reg) if reg.is_callee_saved() => Some(*reg),
+            _ => None,
+        })
+        .collect();
+
+    saved.sort_by_key(|reg| reg.to_string());
+    saved.dedup();
+
+    let mut live_across = HashMap::new();
+
+    for (call, at) in calls {
+        let live: Vec<Register> = intervals
+            .iter()
+            .filter(|interval| interval.start < call && call < interval.end)
+            .filter_map(|interval| match locations[&interval.temp] {
+                Location::Register(reg) if !reg.is_callee_saved() => Some(reg),
+                _ => None,
+            })
+            .collect();
+
+        live_across.insert(at, live);
     }
 
-    // pub fn get_stack_variables(&self) -> &HashMap<String, usize> {
-    //     &self.stack_variables
-    // }
-
-    // pub fn get_argument_variables(&self) -> &HashMap<String, Register> {
-    //     &self.argument_registers
-    // }
-
-    // I should refactor mips.rs to use this more
-    pub fn get_variable_register(&self, name: &str) -> Option<String> {
-        if self.get_variable_location(name) == VariableLocation::Stack {
-            let offset = match self.get_stack_variable_offset(name) {
-                Some(n) => n,
-                None => {
-                    return None;
-                }
-            };
-            return Some(format!("{}($sp)", offset));
-        } else {
-            let reg = match self.get_argument_register(name) {
-                Some(r) => r,
-                None => {
-                    return None;
-                }
-            };
-            return Some(reg);
-        }
+    Allocation {
+        locations,
+        spills,
+        saved,
+        live_across,
     }
 }
@@ -1,76 +1,99 @@
 pub(crate) mod ast;
 
-use std::collections::HashMap;
+use std::collections::HashSet;
 
 use crate::error::{CompileError, Result};
-use crate::lexer::Token;
+use crate::lexer::{Span, SpannedToken, Token};
 use crate::parser::ast::*;
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<SpannedToken>,
     current: usize,
-    line: usize,
 
     num_while: usize,
     num_for: usize,
     num_if: usize,
 
-    function_param_types: HashMap<String, Vec<Type>>,
+    // Struct names are types, found up front so one can be
+    // used before (or inside) its own declaration
+    struct_names: HashSet<String>,
+
+    // Everything that went wrong, parsing carries on after an error
+    // so the user sees as many as possible in one go
+    errors: Vec<CompileError>,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        let mut function_param_types: HashMap<String, Vec<Type>> = HashMap::new();
-
-        function_param_types.insert("iprint".to_string(), vec![Type::Int32]);
-        function_param_types.insert("sprint".to_string(), vec![Type::String]);
-        function_param_types.insert("iread".to_string(), vec![]);
-        function_param_types.insert("sread".to_string(), vec![]);
-        function_param_types.insert("irandrange".to_string(), vec![Type::Int32, Type::Int32]);
+    pub fn new(tokens: Vec<SpannedToken>) -> Self {
+        let struct_names = tokens
+            .windows(2)
+            .filter_map(|pair| match (&pair[0].token, &pair[1].token) {
+                (Token::Struct, Token::Identifier(name)) => Some(name.clone()),
+                _ => None,
+            })
+            .collect();
 
         Parser {
             tokens,
             current: 0,
-            line: 2,
             num_while: 0,
             num_for: 0,
             num_if: 0,
-            function_param_types,
+            struct_names,
+            errors: Vec::new(),
         }
     }
 
-    pub fn parse(&mut self) -> Result<Program> {
-        let data_body: Vec<Statement>;
+    pub fn parse(&mut self) -> std::result::Result<Program, Vec<CompileError>> {
         let mut text_body: Vec<Statement> = Vec::new();
 
-        text_body.push(Statement::Function {
-            name: "_start".to_string(),
-            params: Vec::new(),
-            return_type: Type::Void,
-            body: {
-                vec![
-                    Statement::Instruction {
-                        opcode: "jal".to_string(),
-                        operands: vec!["main".to_string()],
-                    },
-                    Statement::Instruction {
-                        opcode: "li".to_string(),
-                        operands: vec!["$v0, 10".to_string()],
-                    },
-                    Statement::Instruction {
-                        opcode: "syscall\n".to_string(),
-                        operands: vec![],
-                    },
-                ]
+        let instruction = |opcode: &str, operands: Vec<String>| {
+            Statement::new(
+                StatementKind::Instruction {
+                    opcode: opcode.to_string(),
+                    operands,
+                },
+                Span::default(),
+            )
+        };
+
+        text_body.push(Statement::new(
+            StatementKind::Function {
+                name: "_start".to_string(),
+                params: Vec::new(),
+                return_type: Type::Void,
+                body: {
+                    vec![
+                        instruction("jal", vec!["main".to_string()]),
+                        instruction("li", vec!["$v0, 10".to_string()]),
+                        instruction("syscall\n", vec![]),
+                    ]
+                },
+                use_stack: false,
             },
-            use_stack: false,
-        });
+            Span::default(),
+        ));
 
         while !self.is_at_end() {
-            text_body.push(self.parse_statement()?);
+            match self.parse_statement() {
+                Ok(stmt) => text_body.push(stmt),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.synchronize();
+
+                    // A stray } up here has no block to close
+                    if matches!(self.peek(), Token::RightBrace) {
+                        self.advance();
+                    }
+                }
+            }
+        }
+
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
         }
 
-        data_body = self.populate_data_segment(&text_body);
+        let data_body: Vec<Statement> = self.populate_data_segment(&text_body);
 
         Ok(Program {
             segments: Segments {
@@ -81,6 +104,7 @@ impl Parser {
     }
 
     fn parse_function(&mut self) -> Result<Statement> {
+        let start = self.peek_span();
         let return_type = self.parse_type()?;
 
         let name = self.parse_identifier()?;
@@ -88,60 +112,139 @@ impl Parser {
         self.expect(Token::LeftParen, "parse_function")?;
         let params = self.parse_parameters()?;
 
-        let mut param_types: Vec<Type> = Vec::new();
-
-        for param in &params {
-            let p = param.clone();
-            param_types.push(p.param_type);
-        }
-
-        self.function_param_types.insert(name.clone(), param_types);
-
         self.expect(Token::RightParen, "parse_function")?;
 
         self.expect(Token::LeftBrace, "parse_function")?;
         let mut body = self.parse_block()?;
 
+        // The implicit return belongs to the closing brace
         if return_type == Type::Void {
-            body.push(Statement::Return { value: Expr::Empty })
+            let span = self.peek_span();
+
+            body.push(Statement::new(
+                StatementKind::Return {
+                    value: Expr::new(ExprKind::Empty, span.clone()),
+                },
+                span,
+            ));
         }
 
         self.expect(Token::RightBrace, "parse_function")?;
 
-        Ok(Statement::Function {
-            name,
-            params,
-            return_type,
-            body,
-            use_stack: true,
-        })
+        Ok(Statement::new(
+            StatementKind::Function {
+                name,
+                params,
+                return_type,
+                body,
+                use_stack: true,
+            },
+            self.span_from(&start),
+        ))
     }
 
     fn parse_type(&mut self) -> Result<Type> {
-        let typ = match self.peek() {
+        let mut typ = match self.peek() {
             Token::Void => Type::Void,
-            Token::Int32 => {
-                if self.peek_ahead(1) == Some(&Token::Ampersand) {
-                    self.advance();
-                    Type::Int32Pointer
-                } else {
-                    Type::Int32
-                }
-            }
+            Token::Int32 => Type::Int32,
             Token::Bool => Type::Bool,
             Token::String => Type::String,
+            Token::Char => Type::Char,
+            Token::Identifier(name) if self.struct_names.contains(name) => {
+                Type::Struct(name.clone())
+            }
 
             _ => {
                 return Err(CompileError::ParseError {
                     message: format!("Expected type, found {:?}", self.peek()),
-                    line: self.line,
+                    span: self.peek_span(),
                 });
             }
         };
         self.advance();
+
+        // Every & is another level of pointer, && comes out of the lexer as one token
+        loop {
+            typ = match self.peek() {
+                Token::Ampersand => Type::Pointer(Box::new(typ)),
+                Token::AmpersandAmpersand => Type::Pointer(Box::new(Type::Pointer(Box::new(typ)))),
+                _ => break,
+            };
+            self.advance();
+        }
+
         Ok(typ)
     }
 
+    // struct Name { type field; ... } with an optional ; after the brace
+    fn parse_struct(&mut self) -> Result<Statement> {
+        let start = self.peek_span();
+        self.advance();
+
+        let name = self.parse_identifier()?;
+        self.expect(Token::LeftBrace, "parse_struct")?;
+
+        let mut fields = Vec::new();
+
+        while !matches!(self.peek(), Token::RightBrace | Token::Eof) {
+            let field_start = self.peek_span();
+            let field_type = self.parse_type()?;
+            let field_name = self.parse_identifier()?;
+
+            let dimensions = self.parse_dimensions()?;
+            let field_type = Type::array_of(field_type, &dimensions);
+
+            fields.push(StructField {
+                name: field_name,
+                field_type,
+                span: self.span_from(&field_start),
+            });
+
+            self.expect(Token::Semicolon, "parse_struct")?;
+        }
+
+        self.expect(Token::RightBrace, "parse_struct")?;
+
+        if matches!(self.peek(), Token::Semicolon) {
+            self.advance();
+        }
+
+        Ok(Statement::new(
+            StatementKind::Struct { name, fields },
+            self.span_from(&start),
+        ))
+    }
+
+    // Any number of [n] after a name, the sizes have to be known up front
+    fn parse_dimensions(&mut self) -> Result<Vec<usize>> {
+        let mut dimensions = Vec::new();
+
+        while matches!(self.peek(), Token::LeftBracket) {
+            self.advance();
+
+            match self.peek() {
+                Token::Integer(n) if *n > 0 => {
+                    dimensions.push(*n as usize);
+                    self.advance();
+                }
+
+                _ => {
+                    return Err(CompileError::ParseError {
+                        message: format!(
+                            "Array size must be a positive integer, found {:?}",
+                            self.peek()
+                        ),
+                        span: self.peek_span(),
+                    });
+                }
+            }
+
+            self.expect(Token::RightBracket, "parse_dimensions")?;
+        }
+
+        Ok(dimensions)
+    }
+
     fn parse_identifier(&mut self) -> Result<String> {
         match self.peek() {
             Token::Identifier(name) => {
@@ -152,7 +255,7 @@ impl Parser {
 
             _ => Err(CompileError::ParseError {
                 message: format!("Expected identifier, found {:?}", self.peek()),
-                line: self.line,
+                span: self.peek_span(),
             }),
         }
     }
@@ -165,11 +268,29 @@ impl Parser {
         }
 
         loop {
-            let typ = self.parse_type()?;
+            let start = self.peek_span();
+            let mut typ = self.parse_type()?;
             let name = self.parse_identifier()?;
+
+            // An array parameter is really a pointer to its first element, so
+            // like C the first size can be left out and is ignored anyway
+            if matches!(self.peek(), Token::LeftBracket) {
+                if matches!(self.peek_ahead(1), Some(Token::RightBracket)) {
+                    self.advance();
+                    self.advance();
+
+                    let dimensions = self.parse_dimensions()?;
+                    typ = Type::pointer_to(Type::array_of(typ, &dimensions));
+                } else {
+                    let dimensions = self.parse_dimensions()?;
+                    typ = Type::pointer_to(Type::array_of(typ, &dimensions[1..]));
+                }
+            }
+
             params.push(Parameter {
                 name,
                 param_type: typ,
+                span: self.span_from(&start),
             });
 
             if !matches!(self.peek(), Token::Comma) {
@@ -182,47 +303,107 @@ impl Parser {
     }
 
     fn parse_return(&mut self) -> Result<Statement> {
+        let start = self.peek_span();
         self.advance();
-        let e = self.parse_expression()?;
+
+        let e = if matches!(self.peek(), Token::Semicolon) {
+            Expr::new(ExprKind::Empty, self.peek_span())
+        } else {
+            self.parse_expression()?
+        };
+
         self.expect(Token::Semicolon, "parse_return")?;
-        Ok(Statement::Return { value: e })
+        Ok(Statement::new(
+            StatementKind::Return { value: e },
+            self.span_from(&start),
+        ))
     }
 
     fn parse_block(&mut self) -> Result<Vec<Statement>> {
         let mut statements = Vec::new();
 
         while !matches!(self.peek(), Token::RightBrace | Token::Eof) {
-            statements.push(self.parse_statement()?);
+            match self.parse_statement() {
+                Ok(stmt) => statements.push(stmt),
+                Err(e) => {
+                    self.errors.push(e);
+                    self.synchronize();
+                }
+            }
         }
 
         Ok(statements)
     }
 
+    // Panic mode recovery, skips to the end of the broken statement. That's
+    // just past the next ;, right before a } so the block can close, or past
+    // a whole { } block (and any else after it) so a broken if or while
+    // doesn't eat the function's brace
+    fn synchronize(&mut self) {
+        loop {
+            match self.peek() {
+                Token::Eof | Token::RightBrace => return,
+
+                Token::Semicolon => {
+                    self.advance();
+                    return;
+                }
+
+                Token::LeftBrace => {
+                    self.skip_braces();
+
+                    // The else belongs to the if that was just skipped
+                    if !matches!(self.peek(), Token::Else) {
+                        return;
+                    }
+
+                    self.advance();
+                }
+
+                _ => self.advance(),
+            }
+        }
+    }
+
+    // Expects to be on the {, stops after the matching }
+    fn skip_braces(&mut self) {
+        let mut depth = 0;
+
+        loop {
+            match self.peek() {
+                Token::Eof => return,
+                Token::LeftBrace => depth += 1,
+                Token::RightBrace => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        self.advance();
+                        return;
+                    }
+                }
+                _ => {}
+            }
+
+            self.advance();
+        }
+    }
+
     fn parse_statement(&mut self) -> Result<Statement> {
         match self.peek() {
             Token::Return => self.parse_return(),
             Token::If => self.parse_if(),
             Token::While => self.parse_while(),
             Token::For => self.parse_for(),
+            Token::Struct => self.parse_struct(),
 
-            Token::Int32 | Token::String | Token::Bool | Token::Void => {
-                let is_function = match self.peek_ahead(1) {
-                    Some(Token::Ampersand) => matches!(self.peek_ahead(3), Some(Token::LeftParen)),
-                    _ => matches!(self.peek_ahead(2), Some(Token::LeftParen)),
-                };
-
-                if is_function {
-                    self.parse_function()
-                } else {
-                    self.parse_variable_declaration()
-                }
+            Token::Int32 | Token::String | Token::Bool | Token::Char | Token::Void => {
+                self.parse_declaration()
             }
 
+            Token::Identifier(name) if self.struct_names.contains(name) => self.parse_declaration(),
+
             Token::Identifier(name) => {
-                let is_function = match self.peek_ahead(1) {
-                    Some(Token::LeftParen) => true,
-                    _ => false,
-                };
+                let is_function = matches!(self.peek_ahead(1), Some(Token::LeftParen));
 
                 if is_function {
                     self.parse_function_call(name.clone())
@@ -235,370 +416,461 @@ impl Parser {
         }
     }
 
+    // Starts on a type, either a function or a variable follows
+    fn parse_declaration(&mut self) -> Result<Statement> {
+        // Skip the &s of a pointer type to get to what follows the name
+        let mut ahead = 1;
+        while matches!(
+            self.peek_ahead(ahead),
+            Some(Token::Ampersand | Token::AmpersandAmpersand)
+        ) {
+            ahead += 1;
+        }
+
+        let is_function = matches!(self.peek_ahead(ahead + 1), Some(Token::LeftParen));
+
+        if is_function {
+            self.parse_function()
+        } else {
+            self.parse_variable_declaration()
+        }
+    }
+
     fn parse_variable_declaration(&mut self) -> Result<Statement> {
-        let var_type = self.parse_type()?;
+        let start = self.peek_span();
+        let element_type = self.parse_type()?;
         let identifier = self.parse_identifier()?;
 
-        let res_statement: Statement;
+        let dimensions = self.parse_dimensions()?;
+        let var_type = Type::array_of(element_type, &dimensions);
 
-        if matches!(self.peek(), Token::Equal) {
+        let operation = if matches!(self.peek(), Token::Equal) {
             self.advance();
+            self.parse_expression()?
+        } else {
+            Expr::new(ExprKind::Empty, self.peek_span())
+        };
 
-            let mut is_not = false;
-            if matches!(self.peek(), Token::Not) {
-                is_not = true;
-                self.advance();
-            }
+        self.expect(Token::Semicolon, "parse_variable_declaration")?;
 
-            let left = self.parse_expression()?;
+        Ok(Statement::new(
+            StatementKind::VariableDeclaration {
+                var_type,
+                identifier,
+                operation,
+            },
+            self.span_from(&start),
+        ))
+    }
 
-            let operator = match self.peek() {
-                Token::Plus => BinaryOperator::Add,
-                Token::Minus => BinaryOperator::Subtract,
-                Token::Star => BinaryOperator::Multiply,
-                _ => BinaryOperator::Empty,
-            };
+    fn parse_expression(&mut self) -> Result<Expr> {
+        self.parse_binary(0)
+    }
 
-            if operator != BinaryOperator::Empty {
-                self.advance();
+    // Precedence climbing, every binary operator is left associative
+    // so the right side only takes operators that bind tighter
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expr> {
+        let mut left = self.parse_unary()?;
+
+        while let Some((precedence, operator)) = Self::binary_operator(self.peek()) {
+            if precedence < min_precedence {
+                break;
             }
 
-            let mut right = Expr::Empty;
-            if operator != BinaryOperator::Empty {
-                right = self.parse_expression()?;
+            // A shift with nothing after it is the old `x<<;` statement
+            if matches!(
+                operator,
+                BinaryOperator::ShiftLeft | BinaryOperator::ShiftRight
+            ) && matches!(
+                self.peek_ahead(1),
+                Some(Token::Semicolon | Token::RightParen)
+            ) {
+                break;
             }
 
-            res_statement = Statement::VariableDeclaration {
-                var_type,
-                identifier,
-                operation: Expr::BinaryOp {
+            self.advance();
+
+            let right = self.parse_binary(precedence + 1)?;
+
+            let span = left.span.to(&right.span);
+
+            left = Expr::new(
+                ExprKind::BinaryOp {
                     left: Box::new(left),
                     operator,
                     right: Box::new(right),
-                    is_not,
                 },
-            }
-        } else {
-            res_statement = Statement::VariableDeclaration {
-                var_type,
-                identifier,
-                operation: Expr::BinaryOp {
-                    left: Box::new(Expr::Empty),
-                    operator: BinaryOperator::Empty,
-                    right: Box::new(Expr::Empty),
-                    is_not: false,
-                },
-            }
+                span,
+            );
         }
 
-        self.expect(Token::Semicolon, "parse_variable_declaration")?;
+        Ok(left)
+    }
 
-        Ok(res_statement)
+    // Same ordering as C, higher binds tighter
+    fn binary_operator(token: &Token) -> Option<(u8, BinaryOperator)> {
+        let op = match token {
+            Token::PipePipe => (1, BinaryOperator::LogicalOr),
+            Token::AmpersandAmpersand => (2, BinaryOperator::LogicalAnd),
+            Token::Pipe => (3, BinaryOperator::BitwiseOr),
+            Token::Caret => (4, BinaryOperator::BitwiseXor),
+            Token::Ampersand => (5, BinaryOperator::BitwiseAnd),
+            Token::EqualEqual => (6, BinaryOperator::Equal),
+            Token::NotEqual => (6, BinaryOperator::NotEqual),
+            Token::LessThan => (7, BinaryOperator::LessThan),
+            Token::LessThanEqual => (7, BinaryOperator::LessEqual),
+            Token::GreaterThan => (7, BinaryOperator::GreaterThan),
+            Token::GreaterThanEqual => (7, BinaryOperator::GreaterEqual),
+            Token::LeftShift => (8, BinaryOperator::ShiftLeft),
+            Token::RightShift => (8, BinaryOperator::ShiftRight),
+            Token::Plus => (9, BinaryOperator::Add),
+            Token::Minus => (9, BinaryOperator::Subtract),
+            Token::Star => (10, BinaryOperator::Multiply),
+            Token::Slash => (10, BinaryOperator::Divide),
+            Token::Percent => (10, BinaryOperator::Modulo),
+            _ => return None,
+        };
+
+        Some(op)
     }
 
-    fn parse_expression(&mut self) -> Result<Expr> {
-        match self.peek() {
+    fn parse_unary(&mut self) -> Result<Expr> {
+        let start = self.peek_span();
+
+        let operator = match self.peek() {
+            Token::Minus => UnaryOperator::Negate,
+            Token::Not => UnaryOperator::Not,
+            Token::Tilde => UnaryOperator::BitwiseNot,
+
+            // In operand position & can only be address-of, parse_binary
+            // treats it as bitwise and when it follows an operand
             Token::Ampersand => {
                 self.advance();
                 let name = self.parse_identifier()?;
-                Ok(Expr::IdentifierReference(name))
+                return Ok(Expr::new(
+                    ExprKind::IdentifierReference(name),
+                    self.span_from(&start),
+                ));
             }
 
             Token::Star => {
                 self.advance();
                 let name = self.parse_identifier()?;
-                Ok(Expr::IdentifierDereference(name))
+                return Ok(Expr::new(
+                    ExprKind::IdentifierDereference(name),
+                    self.span_from(&start),
+                ));
             }
 
-            Token::Integer(n) => {
-                let n = *n;
-                self.advance();
-                Ok(Expr::Integer(n))
-            }
+            _ => return self.parse_postfix(),
+        };
 
-            Token::Identifier(name) => {
-                let n = name.clone();
+        self.advance();
+        let operand = self.parse_unary()?;
+        let span = start.to(&operand.span);
 
-                self.advance();
+        // Fold literals so -5 is still a plain integer
+        if operator == UnaryOperator::Negate
+            && let ExprKind::Integer(n) = operand.kind
+        {
+            return Ok(Expr::new(ExprKind::Integer(n.wrapping_neg()), span));
+        }
 
-                if matches!(self.peek(), Token::LeftParen) {
+        Ok(Expr::new(
+            ExprKind::UnaryOp {
+                operator,
+                operand: Box::new(operand),
+            },
+            span,
+        ))
+    }
+
+    // Indexing and field accesses chain left to right, a[i].b->c is ((a[i]).b)->c
+    fn parse_postfix(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            let kind = match self.peek() {
+                Token::LeftBracket => {
                     self.advance();
 
-                    let is_builtin_function = matches!(
-                        n.as_str(),
-                        "iprint" | "sprint" | "iread" | "sread" | "irandrange"
-                    );
+                    let indexer = Box::new(self.parse_expression()?);
+                    self.expect(Token::RightBracket, "parse_postfix")?;
 
-                    let builtin_function_type = match n.as_str() {
-                        "iprint" => Some(BuiltinFunctionType::IntegerPrint),
-                        "sprint" => Some(BuiltinFunctionType::StringPrint),
-                        "iread" => Some(BuiltinFunctionType::IntegerRead),
-                        "sread" => Some(BuiltinFunctionType::StringRead),
-                        "irandrange" => Some(BuiltinFunctionType::IntegerRandomRange),
-                        _ => None,
-                    };
+                    ExprKind::ArrayIndex {
+                        array: Box::new(expr),
+                        indexer,
+                    }
+                }
 
-                    let mut arguments: Vec<Argument> = Vec::new();
+                Token::Dot | Token::Arrow => {
+                    let through_pointer = matches!(self.peek(), Token::Arrow);
+                    self.advance();
 
-                    let typ_v: Vec<Type> = match self.function_param_types.get(&n) {
-                        Some(vec) => vec.clone(),
-                        None => {
-                            return Err(CompileError::ParseError {
-                                message: format!("Call to undeclared function '{}'", n),
-                                line: self.line,
-                            });
-                        }
-                    };
+                    ExprKind::FieldAccess {
+                        base: Box::new(expr),
+                        field: self.parse_identifier()?,
+                        through_pointer,
+                    }
+                }
 
-                    let mut i = 0;
-                    if self.peek() != &Token::RightParen {
-                        loop {
-                            let expr = self.parse_expression()?;
+                _ => break,
+            };
 
-                            let typ = match typ_v.get(i) {
-                                Some(t) => t,
-                                None => panic!("not this either"),
-                            };
+            let span = match &kind {
+                ExprKind::ArrayIndex { array, .. } => self.span_from(&array.span),
+                ExprKind::FieldAccess { base, .. } => self.span_from(&base.span),
+                _ => unreachable!(),
+            };
 
-                            arguments.push(Argument {
-                                expr,
-                                typ: typ.clone(),
-                            });
+            expr = Expr::new(kind, span);
+        }
 
-                            if !matches!(self.peek(), Token::Comma) {
-                                break;
-                            }
-                            self.advance();
-                            i += 1;
-                        }
-                    }
+        Ok(expr)
+    }
 
-                    self.expect(Token::RightParen, "parse_expression (function call)")?;
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let start = self.peek_span();
 
-                    let call_expr = Expr::FunctionCall {
-                        function_name: n,
-                        arguments,
-                        is_builtin_function,
-                        builtin_function_type,
-                    };
-                    return Ok(call_expr);
-                } else if matches!(self.peek(), Token::LeftBracket) {
-                    self.advance();
+        match self.peek() {
+            Token::Integer(n) => {
+                let n = *n;
+                self.advance();
+                Ok(Expr::new(ExprKind::Integer(n), start))
+            }
 
-                    let array_name = n;
-                    let indexer = Box::new(self.parse_expression()?);
+            Token::Identifier(name) => {
+                let n = name.clone();
 
-                    self.expect(Token::RightBracket, "parse_expression")?;
+                self.advance();
 
-                    return Ok(Expr::ArrayIndex {
-                        array_name,
-                        indexer,
-                    });
+                if matches!(self.peek(), Token::LeftParen) {
+                    return self.parse_call(n, start);
                 }
 
-                Ok(Expr::Identifier(n))
+                Ok(Expr::new(ExprKind::Identifier(n), start))
+            }
+
+            Token::LeftParen => {
+                self.advance();
+                let mut expr = self.parse_expression()?;
+                self.expect(Token::RightParen, "parse_primary")?;
+
+                // Keep the parentheses so errors underline the whole thing
+                expr.span = self.span_from(&start);
+                Ok(expr)
             }
 
             Token::LeftBrace => {
                 self.advance();
 
-                let mut exprs: Vec<Box<Expr>> = Vec::new();
+                let mut exprs: Vec<Expr> = Vec::new();
                 let mut len = 0;
 
                 while self.peek() != &Token::RightBrace {
                     let expr = self.parse_expression()?;
 
-                    exprs.push(Box::new(expr));
+                    exprs.push(expr);
                     len += 1;
 
                     if self.peek() == &Token::RightBrace {
                         break;
                     }
 
-                    self.expect(Token::Comma, "parse_expression")?;
+                    self.expect(Token::Comma, "parse_primary")?;
                 }
 
-                self.expect(Token::RightBrace, "parse_expression")?;
+                self.expect(Token::RightBrace, "parse_primary")?;
 
                 if self.peek() == &Token::LeftBracket {
                     self.advance();
 
-                    if let Expr::Integer(n) = self.parse_expression()? {
+                    let size = self.parse_expression()?;
+
+                    if let ExprKind::Integer(n) = size.kind {
                         if n < 0 {
                             return Err(CompileError::ParseError {
                                 message: format!("{} must be >= 0 in array init", n),
-                                line: self.line,
+                                span: size.span,
                             });
                         }
 
                         len = n as usize;
                     }
 
-                    self.expect(Token::RightBracket, "parse_expression")?;
+                    self.expect(Token::RightBracket, "parse_primary")?;
                 }
 
-                Ok(Expr::ArrayInitializer {
-                    body: exprs,
-                    size: len,
-                })
+                Ok(Expr::new(
+                    ExprKind::ArrayInitializer {
+                        body: exprs,
+                        size: len,
+                    },
+                    self.span_from(&start),
+                ))
             }
 
             Token::StringLiteral(str) => {
                 let str = str.clone();
                 self.advance();
-                Ok(Expr::StringLiteral(str))
+                Ok(Expr::new(ExprKind::StringLiteral(str), start))
             }
 
             Token::BoolLiteral(b) => {
                 let bool = *b;
                 self.advance();
-                Ok(Expr::BoolLiteral(bool))
+                Ok(Expr::new(ExprKind::BoolLiteral(bool), start))
+            }
+
+            Token::CharLiteral(c) => {
+                let c = *c;
+                self.advance();
+                Ok(Expr::new(ExprKind::CharLiteral(c), start))
             }
 
             _ => Err(CompileError::ParseError {
                 message: format!("Expected expression, found {:?}", self.peek()),
-                line: self.line,
+                span: start,
             }),
         }
     }
 
-    fn parse_unary(&mut self) -> Result<Statement> {
-        self.backtrack();
+    // Expects the function name to already be consumed, start is its span
+    fn parse_call(&mut self, function_name: String, start: Span) -> Result<Expr> {
+        self.expect(Token::LeftParen, "parse_call")?;
+
+        let is_builtin_function = matches!(
+            function_name.as_str(),
+            "iprint"
+                | "sprint"
+                | "iread"
+                | "sread"
+                | "irandrange"
+                | "cprint"
+                | "cread"
+                | "alloc"
+        );
 
-        let identifier = self.parse_expression()?;
-
-        let name = if let Expr::Identifier(n) = &identifier {
-            n.clone()
-        } else {
-            return Err(CompileError::ParseError {
-                message: "Left side of unary must be an identifier".to_string(),
-                line: self.line,
-            });
+        let builtin_function_type = match function_name.as_str() {
+            "iprint" => Some(BuiltinFunctionType::IntegerPrint),
+            "sprint" => Some(BuiltinFunctionType::StringPrint),
+            "iread" => Some(BuiltinFunctionType::IntegerRead),
+            "sread" => Some(BuiltinFunctionType::StringRead),
+            "irandrange" => Some(BuiltinFunctionType::IntegerRandomRange),
+            "cprint" => Some(BuiltinFunctionType::CharPrint),
+            "cread" => Some(BuiltinFunctionType::CharRead),
+            "alloc" => Some(BuiltinFunctionType::Allocate),
+            _ => None,
         };
 
-        let operation = match self.peek() {
-            Token::PlusPlus => Expr::BinaryOp {
-                left: Box::new(identifier),
-                operator: BinaryOperator::Add,
-                right: Box::new(Expr::Integer(1)),
-                is_not: false,
-            },
+        // Whether the function exists and takes these arguments
+        // is left to the semantic pass
+        let mut arguments: Vec<Expr> = Vec::new();
 
-            Token::MinusMinus => Expr::BinaryOp {
-                left: Box::new(identifier),
-                operator: BinaryOperator::Subtract,
-                right: Box::new(Expr::Integer(1)),
-                is_not: false,
+        if self.peek() != &Token::RightParen {
+            loop {
+                arguments.push(self.parse_expression()?);
+
+                if !matches!(self.peek(), Token::Comma) {
+                    break;
+                }
+                self.advance();
+            }
+        }
+
+        self.expect(Token::RightParen, "parse_call")?;
+
+        Ok(Expr::new(
+            ExprKind::FunctionCall {
+                function_name,
+                arguments,
+                is_builtin_function,
+                builtin_function_type,
             },
+            self.span_from(&start),
+        ))
+    }
 
-            _ => Expr::Empty,
+    fn parse_increment(&mut self, target: Expr) -> Result<Statement> {
+        let operator = match self.peek() {
+            Token::PlusPlus => BinaryOperator::Add,
+            _ => BinaryOperator::Subtract,
         };
 
         self.advance();
 
-        // This will bite me later
-        if self.peek_ahead(1) != Some(&Token::RightParen) {
-            self.expect(Token::Semicolon, "parse_unary")?;
-        }
+        let operation = self.make_step(target.clone(), operator);
 
-        Ok(Statement::VariableAssignment {
-            identifier: name,
-            operation,
-            is_dereference: false,
-            is_array_index: false,
-            indexer: Expr::Empty,
-        })
+        self.make_assignment(target, operation)
     }
 
-    fn parse_shift(&mut self) -> Result<Statement> {
-        self.backtrack();
-
-        let identifier = self.parse_expression()?;
-
-        let name = if let Expr::Identifier(n) = &identifier {
-            n.clone()
-        } else {
-            return Err(CompileError::ParseError {
-                message: "Left side of shift must be an identifier".to_string(),
-                line: self.line,
-            });
+    // `x<<;` and `x>>;` shift a variable in place by one
+    fn parse_shift(&mut self, target: Expr) -> Result<Statement> {
+        let operator = match self.peek() {
+            Token::LeftShift => BinaryOperator::ShiftLeft,
+            _ => BinaryOperator::ShiftRight,
         };
 
-        let operation = match self.peek() {
-            Token::LeftShift => Expr::BitwiseShift {
-                identifier: Box::new(identifier),
-                shift_type: BitwiseShiftType::LeftShift,
-            },
-            Token::RightShift => Expr::BitwiseShift {
-                identifier: Box::new(identifier),
-                shift_type: BitwiseShiftType::RightShift,
-            },
+        self.advance();
 
-            _ => Expr::Empty,
-        };
+        let operation = self.make_step(target.clone(), operator);
 
-        self.advance();
+        self.make_assignment(target, operation)
+    }
 
-        self.expect(Token::Semicolon, "parse_shift")?;
+    // target <operator> 1, spanning the whole `x++` style statement
+    fn make_step(&self, target: Expr, operator: BinaryOperator) -> Expr {
+        let span = self.span_from(&target.span);
 
-        Ok(Statement::VariableAssignment {
-            identifier: name,
-            operation,
-            is_dereference: false,
-            is_array_index: false,
-            indexer: Expr::Empty,
-        })
+        Expr::new(
+            ExprKind::BinaryOp {
+                left: Box::new(target),
+                operator,
+                right: Box::new(Expr::new(ExprKind::Integer(1), span.clone())),
+            },
+            span,
+        )
     }
 
-    fn parse_assignment(&mut self, identifier: Expr) -> Result<Statement> {
+    fn parse_assignment(&mut self, target: Expr) -> Result<Statement> {
         self.advance();
 
-        let mut is_not = false;
-        if matches!(self.peek(), Token::Not) {
-            is_not = true;
-            self.advance();
-        }
+        let value = self.parse_expression()?;
 
-        let left = self.parse_expression()?;
+        self.make_assignment(target, value)
+    }
 
-        let value = match self.peek() {
-            Token::Plus | Token::Minus | Token::Star => {
-                let operator = match self.peek() {
-                    Token::Plus => BinaryOperator::Add,
-                    Token::Minus => BinaryOperator::Subtract,
-                    Token::Star => BinaryOperator::Multiply,
-                    _ => unreachable!(),
-                };
-                self.advance();
+    fn make_assignment(&self, target: Expr, operation: Expr) -> Result<Statement> {
+        let mut is_dereference = false;
+        let mut is_array_index = false;
 
-                let right = self.parse_expression()?;
+        let span = target.span.to(&operation.span);
 
-                Expr::BinaryOp {
-                    left: Box::new(left),
-                    operator,
-                    right: Box::new(right),
-                    is_not,
-                }
-            }
-            _ => left,
+        // Only name[i] has a shortcut, anything deeper is stored through its address
+        let is_indirect = match &target.kind {
+            ExprKind::FieldAccess { .. } => true,
+            ExprKind::ArrayIndex { array, .. } => !matches!(array.kind, ExprKind::Identifier(_)),
+            _ => false,
         };
 
-        self.expect(Token::Semicolon, "parse_assignment")?;
-
-        let mut is_dereference = false;
-        let mut is_array_index = false;
-
-        let mut indexer = Expr::Empty;
+        if is_indirect {
+            return Ok(Statement::new(
+                StatementKind::IndirectAssignment { target, operation },
+                span,
+            ));
+        }
+        let mut indexer = Expr::new(ExprKind::Empty, target.span.clone());
 
-        let name = if let Expr::Identifier(name) = identifier {
+        let name = if let ExprKind::Identifier(name) = target.kind {
             name
-        } else if let Expr::IdentifierDereference(name) = identifier {
+        } else if let ExprKind::IdentifierDereference(name) = target.kind {
             is_dereference = true;
             name
-        } else if let Expr::ArrayIndex {
-            array_name,
+        } else if let ExprKind::ArrayIndex {
+            array,
             indexer: array_indexer,
-        } = identifier
+        } = target.kind
+            && let ExprKind::Identifier(array_name) = array.kind
         {
             is_array_index = true;
             indexer = *array_indexer;
@@ -606,79 +878,53 @@ impl Parser {
         } else {
             return Err(CompileError::ParseError {
                 message: "Left side of assignment must be an identifier".to_string(),
-                line: self.line,
+                span: target.span,
             });
         };
 
-        Ok(Statement::VariableAssignment {
-            identifier: name,
-            operation: value,
-            is_dereference,
-            is_array_index,
-            indexer,
-        })
+        Ok(Statement::new(
+            StatementKind::VariableAssignment {
+                identifier: name,
+                operation,
+                is_dereference,
+                is_array_index,
+                indexer,
+            },
+            span,
+        ))
     }
 
     fn parse_expression_statement(&mut self) -> Result<Statement> {
-        let expr = self.parse_expression()?;
-
-        if matches!(self.peek(), Token::Equal) {
-            return self.parse_assignment(expr);
-        } else if matches!(self.peek(), Token::PlusPlus | Token::MinusMinus) {
-            return self.parse_unary();
-        } else if matches!(self.peek(), Token::LeftShift | Token::RightShift) {
-            return self.parse_shift();
-        }
-
+        let start = self.peek_span();
+        let mut statement = self.parse_simple_statement()?;
         self.expect(Token::Semicolon, "parse_expression_statement")?;
-        Ok(Statement::ExprStatement(expr))
-    }
 
-    fn parse_conditional(&mut self, default_is_not: bool) -> Result<Expr> {
-        let mut is_not = default_is_not;
-        if matches!(self.peek(), Token::Not) {
-            is_not = !is_not;
-            self.advance();
-        }
+        statement.span = self.span_from(&start);
+        Ok(statement)
+    }
 
-        let left = self.parse_expression()?;
+    // Assignments, increments, shifts and bare expressions without the
+    // trailing semicolon, shared with the increment part of a for loop
+    fn parse_simple_statement(&mut self) -> Result<Statement> {
+        let expr = self.parse_expression()?;
 
-        if matches!(self.peek(), Token::RightParen) {
-            return Ok(Expr::BinaryOp {
-                left: Box::new(left),
-                operator: BinaryOperator::Equal,
-                right: Box::new(Expr::Empty),
-                is_not,
-            });
+        match self.peek() {
+            Token::Equal => self.parse_assignment(expr),
+            Token::PlusPlus | Token::MinusMinus => self.parse_increment(expr),
+            Token::LeftShift | Token::RightShift => self.parse_shift(expr),
+            _ => {
+                let span = expr.span.clone();
+                Ok(Statement::new(StatementKind::ExprStatement(expr), span))
+            }
         }
-
-        let operator = match self.peek() {
-            Token::LessThan => BinaryOperator::LessThan,
-            Token::GreaterThan => BinaryOperator::GreaterThan,
-            Token::LessThanEqual => BinaryOperator::LessEqual,
-            Token::GreaterThanEqual => BinaryOperator::GreaterEqual,
-            Token::EqualEqual => BinaryOperator::Equal,
-            Token::NotEqual => BinaryOperator::NotEqual,
-            _ => panic!("{:?} not implemented in parse_conditional", self.peek()),
-        };
-
-        self.advance();
-
-        let right = self.parse_expression()?;
-
-        Ok(Expr::BinaryOp {
-            left: Box::new(left),
-            operator,
-            right: Box::new(right),
-            is_not,
-        })
     }
 
     fn parse_while(&mut self) -> Result<Statement> {
+        let start = self.peek_span();
         self.advance();
 
         self.expect(Token::LeftParen, "parse_while")?;
-        let condition = self.parse_conditional(false)?;
+        let condition = self.parse_expression()?;
 
         self.expect(Token::RightParen, "parse_while")?;
 
@@ -690,24 +936,28 @@ impl Parser {
         let end_label = format!("while_{}_end", self.num_while);
         self.num_while += 1;
 
-        Ok(Statement::While {
-            body_label,
-            end_label,
-            condition,
-            body,
-        })
+        Ok(Statement::new(
+            StatementKind::While {
+                body_label,
+                end_label,
+                condition,
+                body,
+            },
+            self.span_from(&start),
+        ))
     }
 
     fn parse_for(&mut self) -> Result<Statement> {
+        let start = self.peek_span();
         self.advance();
         self.expect(Token::LeftParen, "parse_for")?;
 
         let init = self.parse_variable_declaration()?;
-        let condition = self.parse_conditional(false)?;
+        let condition = self.parse_expression()?;
 
         self.expect(Token::Semicolon, "parse_for")?;
 
-        let var_change = self.parse_for_increment()?;
+        let var_change = self.parse_simple_statement()?;
 
         self.expect(Token::RightParen, "parse_for")?;
 
@@ -719,115 +969,25 @@ impl Parser {
         let end_label = format!("for_{}_end", self.num_for);
         self.num_for += 1;
 
-        Ok(Statement::For {
-            init: Box::new(init),
-            body_label,
-            end_label,
-            condition,
-            body,
-            var_change: Box::new(var_change),
-        })
-    }
-
-    fn parse_for_increment(&mut self) -> Result<Statement> {
-        let expr = self.parse_expression()?;
-
-        if matches!(self.peek(), Token::Equal) {
-            self.advance();
-
-            let mut is_not = false;
-            if matches!(self.peek(), Token::Not) {
-                is_not = true;
-                self.advance();
-            }
-
-            let left = self.parse_expression()?;
-
-            let value = match self.peek() {
-                Token::Plus | Token::Minus => {
-                    let operator = match self.peek() {
-                        Token::Plus => BinaryOperator::Add,
-                        Token::Minus => BinaryOperator::Subtract,
-                        _ => unreachable!(),
-                    };
-                    self.advance();
-
-                    let right = self.parse_expression()?;
-
-                    Expr::BinaryOp {
-                        left: Box::new(left),
-                        operator,
-                        right: Box::new(right),
-                        is_not,
-                    }
-                }
-                _ => left,
-            };
-
-            let name = if let Expr::Identifier(name) = expr {
-                name
-            } else {
-                return Err(CompileError::ParseError {
-                    message: "Left side of assignment must be an identifier".to_string(),
-                    line: self.line,
-                });
-            };
-
-            return Ok(Statement::VariableAssignment {
-                identifier: name,
-                operation: value,
-                is_dereference: false,
-                is_array_index: false,
-                indexer: Expr::Empty,
-            });
-        } else if matches!(self.peek(), Token::PlusPlus | Token::MinusMinus) {
-            // Unary operation without semicolon
-            let name = if let Expr::Identifier(n) = &expr {
-                n.clone()
-            } else {
-                return Err(CompileError::ParseError {
-                    message: "Left side of unary must be an identifier".to_string(),
-                    line: self.line,
-                });
-            };
-
-            let operation = match self.peek() {
-                Token::PlusPlus => Expr::BinaryOp {
-                    left: Box::new(expr),
-                    operator: BinaryOperator::Add,
-                    right: Box::new(Expr::Integer(1)),
-                    is_not: false,
-                },
-
-                Token::MinusMinus => Expr::BinaryOp {
-                    left: Box::new(expr),
-                    operator: BinaryOperator::Subtract,
-                    right: Box::new(Expr::Integer(1)),
-                    is_not: false,
-                },
-
-                _ => Expr::Empty,
-            };
-
-            self.advance();
-
-            return Ok(Statement::VariableAssignment {
-                identifier: name,
-                operation,
-                is_dereference: false,
-                is_array_index: false,
-                indexer: Expr::Empty,
-            });
-        }
-
-        Ok(Statement::ExprStatement(expr))
+        Ok(Statement::new(
+            StatementKind::For {
+                init: Box::new(init),
+                body_label,
+                end_label,
+                condition,
+                body,
+                var_change: Box::new(var_change),
+            },
+            self.span_from(&start),
+        ))
     }
 
     fn parse_if(&mut self) -> Result<Statement> {
+        let start = self.peek_span();
         self.advance();
 
         self.expect(Token::LeftParen, "parse_if")?;
-        let condition = self.parse_conditional(true)?;
+        let condition = self.parse_expression()?;
 
         self.expect(Token::RightParen, "parse_if")?;
 
@@ -838,138 +998,311 @@ impl Parser {
         let label = format!("if_{}", self.num_if);
         self.num_if += 1;
 
-        Ok(Statement::If {
-            label,
-            condition,
-            body,
-        })
+        let else_body = if matches!(self.peek(), Token::Else) {
+            self.advance();
+
+            if matches!(self.peek(), Token::If) {
+                Some(vec![self.parse_if()?])
+            } else {
+                self.expect(Token::LeftBrace, "parse_if (else)")?;
+                let else_body = self.parse_block()?;
+                self.expect(Token::RightBrace, "parse_if (else)")?;
+
+                Some(else_body)
+            }
+        } else {
+            None
+        };
+
+        Ok(Statement::new(
+            StatementKind::If {
+                label,
+                condition,
+                body,
+                else_body,
+            },
+            self.span_from(&start),
+        ))
     }
 
     fn parse_function_call(&mut self, function_name: String) -> Result<Statement> {
+        let start = self.peek_span();
         self.advance();
-        self.expect(Token::LeftParen, "parse_function_call")?;
-
-        let is_builtin_function = match function_name.as_str() {
-            "iprint" => true,
-            "sprint" => true,
-            "iread" => true,
-            "sread" => true,
-            "irandrange" => true,
-            _ => false,
-        };
 
-        let builtin_function_type = match function_name.as_str() {
-            "iprint" => Some(BuiltinFunctionType::IntegerPrint),
-            "sprint" => Some(BuiltinFunctionType::StringPrint),
-            "iread" => Some(BuiltinFunctionType::IntegerRead),
-            "sread" => Some(BuiltinFunctionType::StringRead),
-            "irandrange" => Some(BuiltinFunctionType::IntegerRandomRange),
-            _ => None,
-        };
+        let call = self.parse_call(function_name, start.clone())?;
 
-        let mut arguments: Vec<Argument> = Vec::new();
+        self.expect(Token::Semicolon, "parse_function_call")?;
 
-        let typ_v: Vec<Type> = match self.function_param_types.get(&function_name) {
-            Some(vec) => vec.clone(),
-            None => {
-                return Err(CompileError::ParseError {
-                    message: format!("Call to undeclared function '{}'", function_name),
-                    line: self.line,
-                });
-            }
-        };
+        match call.kind {
+            ExprKind::FunctionCall {
+                function_name,
+                arguments,
+                is_builtin_function,
+                builtin_function_type,
+            } => Ok(Statement::new(
+                StatementKind::FunctionCall {
+                    function_name,
+                    arguments,
+                    is_builtin_function,
+                    builtin_function_type,
+                },
+                self.span_from(&start),
+            )),
 
-        let mut i = 0;
-        if self.peek() != &Token::RightParen {
-            loop {
-                let expr = self.parse_expression()?;
+            _ => unreachable!(),
+        }
+    }
 
-                let typ = match typ_v.get(i) {
-                    Some(t) => t,
-                    None => panic!("not this either"),
-                };
+    fn populate_data_segment(&mut self, text: &[Statement]) -> Vec<Statement> {
+        let mut strings = Vec::new();
 
-                arguments.push(Argument {
-                    expr,
-                    typ: typ.clone(),
-                });
+        for stmt in text {
+            match &stmt.kind {
+                StatementKind::Function { body, .. } => {
+                    self.collect_string_literals(body, &mut strings);
+                }
 
-                if !matches!(self.peek(), Token::Comma) {
-                    break;
+                StatementKind::VariableDeclaration { .. } => {
+                    self.collect_string_literals(std::slice::from_ref(stmt), &mut strings);
                 }
-                self.advance();
-                i += 1;
+
+                _ => {}
             }
         }
 
-        self.expect(Token::RightParen, "parse_function_call")?;
-        self.expect(Token::Semicolon, "parse_function_call")?;
+        let mut seen = std::collections::HashSet::new();
+        strings.retain(|s| seen.insert(s.clone()));
 
-        Ok(Statement::FunctionCall {
-            function_name,
-            arguments,
-            is_builtin_function,
-            builtin_function_type,
-        })
-    }
+        let string_label = |value: &String| {
+            let i = strings.iter().position(|s| s == value).unwrap_or(0);
+            format!("str_{}", i)
+        };
 
-    fn populate_data_segment(&mut self, text: &[Statement]) -> Vec<Statement> {
-        let mut strings = Vec::new();
+        // Globals go first so the words in them stay aligned
+        let mut data = Vec::new();
+
+        let mut declare = |label: Option<String>, storage_type, values| {
+            data.push(Statement::new(
+                StatementKind::DataDeclaration {
+                    label,
+                    storage_type,
+                    values,
+                },
+                Span::default(),
+            ));
+        };
 
         for stmt in text {
-            if let Statement::Function { body, .. } = stmt {
-                self.collect_string_literals(body, &mut strings);
+            if let StatementKind::VariableDeclaration {
+                var_type,
+                identifier,
+                operation,
+            } = &stmt.kind
+            {
+                let label = global_label(identifier);
+
+                // Laid out in place and zeroed, .space doesn't align itself
+                // so a char global before it could leave it off a word
+                if let Type::Array(..) = var_type {
+                    declare(None, DataStorageType::Align(2), Vec::new());
+                    declare(
+                        Some(label),
+                        DataStorageType::Space(Self::data_size(var_type).next_multiple_of(4)),
+                        Vec::new(),
+                    );
+                    continue;
+                }
+
+                let value = match &operation.kind {
+                    ExprKind::Integer(n) => DataValue::Integer(*n),
+                    ExprKind::BoolLiteral(b) => DataValue::Integer(*b as i32),
+                    ExprKind::StringLiteral(s) => DataValue::Label(string_label(s)),
+                    ExprKind::CharLiteral(c) => DataValue::Integer(*c as i32),
+
+                    // Same layout as a local array, the variable holds a
+                    // pointer to the elements which follow it
+                    ExprKind::ArrayInitializer { body, size } => {
+                        let elements_label = format!("{}_data", label);
+
+                        declare(
+                            Some(label),
+                            DataStorageType::Word,
+                            vec![DataValue::Label(elements_label.clone())],
+                        );
+
+                        if body.is_empty() || *size == 0 {
+                            declare(
+                                Some(elements_label),
+                                DataStorageType::Space(size * 4),
+                                Vec::new(),
+                            );
+                        } else {
+                            let values = (0..*size)
+                                .map(|i| match body.get(i).map(|e| &e.kind) {
+                                    Some(ExprKind::Integer(n)) => DataValue::Integer(*n),
+                                    _ => DataValue::Integer(0),
+                                })
+                                .collect();
+
+                            declare(Some(elements_label), DataStorageType::Word, values);
+                        }
+                        continue;
+                    }
+
+                    _ => DataValue::Integer(0),
+                };
+
+                // .word aligns itself so a char in between doesn't upset anything
+                let storage_type = if *var_type == Type::Char {
+                    DataStorageType::Byte
+                } else {
+                    DataStorageType::Word
+                };
+
+                declare(Some(label), storage_type, vec![value]);
             }
         }
 
-        let mut seen = std::collections::HashSet::new();
-        strings.retain(|s| seen.insert(s.clone()));
+        for (i, value) in strings.iter().enumerate() {
+            declare(
+                Some(format!("str_{}", i)),
+                DataStorageType::Asciiz,
+                vec![DataValue::String(value.clone())],
+            );
+        }
 
-        strings
-            .into_iter()
-            .enumerate()
-            .map(|(i, value)| Statement::DataDeclaration {
-                label: format!("str_{}", i),
-                storage_type: DataStorageType::Asciiz,
-                value,
-            })
-            .collect()
+        data
+    }
+
+    // Bytes a global takes up, the semantic pass keeps structs out of the data segment
+    fn data_size(typ: &Type) -> usize {
+        match typ {
+            Type::Char => 1,
+            Type::Array(element, n) => n * Self::data_size(element),
+            _ => 4,
+        }
     }
 
     fn collect_string_literals(&self, statements: &[Statement], strings: &mut Vec<String>) {
         for stmt in statements {
-            match stmt {
-                Statement::VariableDeclaration {
-                    var_type: Type::String,
-                    operation: Expr::BinaryOp { left, .. },
-                    ..
+            match &stmt.kind {
+                StatementKind::VariableDeclaration { operation, .. } => {
+                    self.collect_expr_string_literals(operation, strings);
+                }
+
+                StatementKind::VariableAssignment {
+                    operation, indexer, ..
                 } => {
-                    if let Expr::StringLiteral(value) = &**left {
-                        strings.push(value.clone());
-                    }
+                    self.collect_expr_string_literals(operation, strings);
+                    self.collect_expr_string_literals(indexer, strings);
                 }
 
-                Statement::FunctionCall { arguments, .. } => {
+                StatementKind::FunctionCall { arguments, .. } => {
                     for arg in arguments {
-                        if let Expr::StringLiteral(s) = &arg.expr {
-                            strings.push(s.clone());
-                        }
+                        self.collect_expr_string_literals(arg, strings);
                     }
                 }
 
-                Statement::While { body, .. }
-                | Statement::If { body, .. }
-                | Statement::For { body, .. } => {
+                StatementKind::While {
+                    condition, body, ..
+                } => {
+                    self.collect_expr_string_literals(condition, strings);
                     self.collect_string_literals(body, strings);
                 }
 
+                StatementKind::For {
+                    init,
+                    condition,
+                    var_change,
+                    body,
+                    ..
+                } => {
+                    self.collect_string_literals(std::slice::from_ref(&**init), strings);
+                    self.collect_expr_string_literals(condition, strings);
+                    self.collect_string_literals(std::slice::from_ref(&**var_change), strings);
+                    self.collect_string_literals(body, strings);
+                }
+
+                StatementKind::If {
+                    condition,
+                    body,
+                    else_body,
+                    ..
+                } => {
+                    self.collect_expr_string_literals(condition, strings);
+                    self.collect_string_literals(body, strings);
+
+                    if let Some(else_body) = else_body {
+                        self.collect_string_literals(else_body, strings);
+                    }
+                }
+
+                StatementKind::Return { value } | StatementKind::ExprStatement(value) => {
+                    self.collect_expr_string_literals(value, strings);
+                }
+
+                StatementKind::IndirectAssignment { target, operation } => {
+                    self.collect_expr_string_literals(target, strings);
+                    self.collect_expr_string_literals(operation, strings);
+                }
+
                 _ => {}
             }
         }
     }
 
+    fn collect_expr_string_literals(&self, expr: &Expr, strings: &mut Vec<String>) {
+        match &expr.kind {
+            ExprKind::StringLiteral(value) => {
+                strings.push(value.clone());
+            }
+
+            ExprKind::BinaryOp { left, right, .. } => {
+                self.collect_expr_string_literals(left, strings);
+                self.collect_expr_string_literals(right, strings);
+            }
+
+            ExprKind::UnaryOp { operand, .. } => {
+                self.collect_expr_string_literals(operand, strings);
+            }
+
+            ExprKind::ArrayInitializer { body, .. } => {
+                for element in body {
+                    self.collect_expr_string_literals(element, strings);
+                }
+            }
+
+            ExprKind::ArrayIndex { array, indexer } => {
+                self.collect_expr_string_literals(array, strings);
+                self.collect_expr_string_literals(indexer, strings);
+            }
+
+            ExprKind::FieldAccess { base, .. } => {
+                self.collect_expr_string_literals(base, strings);
+            }
+
+            ExprKind::FunctionCall { arguments, .. } => {
+                for arg in arguments {
+                    self.collect_expr_string_literals(arg, strings);
+                }
+            }
+
+            _ => {}
+        }
+    }
+
     fn peek(&self) -> &Token {
-        &self.tokens[self.current]
+        &self.tokens[self.current].token
+    }
+
+    fn peek_span(&self) -> Span {
+        self.tokens[self.current].span.clone()
+    }
+
+    // From start up to the end of the last consumed token
+    fn span_from(&self, start: &Span) -> Span {
+        let previous = &self.tokens[self.current.saturating_sub(1)].span;
+        start.to(previous)
     }
 
     fn advance(&mut self) {
@@ -984,22 +1317,11 @@ impl Parser {
     //     }
     // }
 
-    fn backtrack(&mut self) {
-        if self.current > 0 {
-            self.current -= 1;
-        }
-    }
-
     fn is_at_end(&self) -> bool {
         matches!(self.peek(), Token::Eof)
     }
 
     fn expect(&mut self, expected: Token, caller: &str) -> Result<()> {
-        // while self.peek() == &Token::NewLine {
-        //     self.line += 1;
-        //     self.advance();
-        // }
-
         if self.peek() == &expected {
             self.advance();
             Ok(())
@@ -1011,13 +1333,13 @@ impl Parser {
                     self.peek(),
                     caller
                 ),
-                line: self.line,
+                span: self.peek_span(),
             })
         }
     }
 
     fn peek_ahead(&self, n: i32) -> Option<&Token> {
         let change = self.current as i32 + n;
-        self.tokens.get(change as usize)
+        self.tokens.get(change as usize).map(|t| &t.token)
     }
 }
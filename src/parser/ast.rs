@@ -1,16 +1,143 @@
+use std::fmt;
+
+use crate::lexer::Span;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Type {
     Void,
     Int32,
-    Int32Pointer,
     Bool,
     String,
+    Char,
+
+    // Written T&, void& is what alloc hands back and converts to any other pointer
+    Pointer(Box<Type>),
+
+    // A struct declared somewhere in the file, by name
+    Struct(String),
+
+    // Written T name[n], laid out in place. int32 grid[3][4] is an
+    // Array of 3 Arrays of 4 int32s. As a value it decays to a T&
+    Array(Box<Type>, usize),
+}
+
+impl Type {
+    pub fn pointer_to(typ: Type) -> Type {
+        Type::Pointer(Box::new(typ))
+    }
+
+    // Wraps typ in one array per dimension, the first dimension outermost
+    pub fn array_of(typ: Type, dimensions: &[usize]) -> Type {
+        dimensions
+            .iter()
+            .rev()
+            .fold(typ, |element, n| Type::Array(Box::new(element), *n))
+    }
+
+    // The element type of an array however many dimensions deep, anything else is itself
+    pub fn innermost(&self) -> &Type {
+        match self {
+            Type::Array(element, _) => element.innermost(),
+            _ => self,
+        }
+    }
+}
+
+// Written the way it appears in source, for error messages
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Type::Void => "void",
+            Type::Int32 => "int32",
+            Type::Bool => "bool",
+            Type::String => "string",
+            Type::Char => "char",
+            Type::Pointer(pointee) => return write!(f, "{}&", pointee),
+            Type::Struct(name) => name.as_str(),
+
+            // The dimensions come after the element type, outermost first
+            Type::Array(..) => {
+                let mut element = self;
+                let mut dimensions = String::new();
+
+                while let Type::Array(inner, n) = element {
+                    dimensions.push_str(&format!("[{}]", n));
+                    element = inner;
+                }
+
+                return write!(f, "{}{}", element, dimensions);
+            }
+        };
+        write!(f, "{}", s)
+    }
 }
 
+// One assembler directive, for anything that needs laying out in .data
 #[derive(Debug, Clone, PartialEq)]
 pub enum DataStorageType {
-    // I'll think about adding more later
     Asciiz,
+    Word,
+    Byte,
+
+    // Number of zeroed bytes, takes no values
+    Space(usize),
+
+    // Pads to the next multiple of 2^n bytes, takes no values
+    Align(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataValue {
+    Integer(i32),
+
+    // The address of another label
+    Label(String),
+
+    String(String),
+}
+
+// Written the way the assembler expects it
+impl fmt::Display for DataValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataValue::Integer(n) => write!(f, "{}", n),
+            DataValue::Label(label) => write!(f, "{}", label),
+            DataValue::String(s) => {
+                write!(f, "\"")?;
+
+                for ch in s.chars() {
+                    match ch {
+                        '\n' => write!(f, "\\n")?,
+                        '\t' => write!(f, "\\t")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '"' => write!(f, "\\\"")?,
+                        _ => write!(f, "{}", ch)?,
+                    }
+                }
+
+                write!(f, "\"")
+            }
+        }
+    }
+}
+
+impl DataValue {
+    // MARS and SPIM only agree on printable ASCII and the escapes
+    // above, anything else has to be written out byte by byte
+    pub fn is_quotable(&self) -> bool {
+        match self {
+            DataValue::String(s) => s
+                .chars()
+                .all(|ch| ch == '\n' || ch == '\t' || (' '..='~').contains(&ch)),
+            _ => true,
+        }
+    }
+}
+
+// Globals live in the data segment under a prefixed label so they
+// can't clash with function names or instructions
+pub fn global_label(name: &str) -> String {
+    format!("global_{}", name)
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -20,12 +147,16 @@ pub enum BuiltinFunctionType {
     IntegerRead,
     StringRead,
     IntegerRandomRange,
+    CharPrint,
+    CharRead,
+    Allocate,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum BitwiseShiftType {
-    LeftShift,
-    RightShift,
+pub enum UnaryOperator {
+    Negate,
+    Not,
+    BitwiseNot,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -39,44 +170,72 @@ pub enum BinaryOperator {
     Add,
     Subtract,
     Multiply,
-    Empty,
+    Divide,
+    Modulo,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+    LogicalAnd,
+    LogicalOr,
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Expr {
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind, span: Span) -> Self {
+        Expr { kind, span }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind {
     Integer(i32),
     Identifier(String),
     IdentifierReference(String),
     IdentifierDereference(String),
     StringLiteral(String),
     BoolLiteral(bool),
+    CharLiteral(char),
     Empty,
 
     BinaryOp {
         left: Box<Expr>,
         operator: BinaryOperator,
         right: Box<Expr>,
-        is_not: bool
+    },
+
+    UnaryOp {
+        operator: UnaryOperator,
+        operand: Box<Expr>,
     },
 
     ArrayInitializer {
-        body: Vec<Box<Expr>>,
+        body: Vec<Expr>,
         size: usize,
     },
 
+    // array is anything that can be indexed, a[i][j] indexes a[i]
     ArrayIndex {
-        array_name: String,
+        array: Box<Expr>,
         indexer: Box<Expr>,
     },
 
-    BitwiseShift {
-        identifier: Box<Expr>,
-        shift_type: BitwiseShiftType,
+    // base.field, or base->field when through_pointer is set
+    FieldAccess {
+        base: Box<Expr>,
+        field: String,
+        through_pointer: bool,
     },
 
     FunctionCall {
         function_name: String,
-        arguments: Vec<Argument>,
+        arguments: Vec<Expr>,
         is_builtin_function: bool,
         builtin_function_type: Option<BuiltinFunctionType>,
     },
@@ -97,20 +256,37 @@ pub struct Segments {
 pub struct Parameter {
     pub name: String,
     pub param_type: Type,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructField {
+    pub name: String,
+    pub field_type: Type,
+    pub span: Span,
 }
 
+// Statements made up by the compiler (like _start) get a default span
 #[derive(Debug, Clone, PartialEq)]
-pub struct Argument {
-    pub expr: Expr,
-    pub typ: Type,
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Statement { kind, span }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Statement {
+#[allow(clippy::enum_variant_names)]
+pub enum StatementKind {
     DataDeclaration {
-        label: String,
+        // Directives like .align don't need one
+        label: Option<String>,
         storage_type: DataStorageType,
-        value: String,
+        values: Vec<DataValue>,
     },
 
     Instruction {
@@ -144,7 +320,7 @@ pub enum Statement {
 
     FunctionCall {
         function_name: String,
-        arguments: Vec<Argument>,
+        arguments: Vec<Expr>,
         is_builtin_function: bool,
         builtin_function_type: Option<BuiltinFunctionType>,
     },
@@ -169,6 +345,9 @@ pub enum Statement {
         label: String,
         condition: Expr,
         body: Vec<Statement>,
+
+        // An `else if` is stored as a single nested If
+        else_body: Option<Vec<Statement>>,
     },
 
     Return {
@@ -177,6 +356,18 @@ pub enum Statement {
 
     ExprStatement(Expr),
 
+    Struct {
+        name: String,
+        fields: Vec<StructField>,
+    },
+
+    // Stores through whatever address target works out to. Used for field
+    // accesses and for indexing anything that isn't a plain variable
+    IndirectAssignment {
+        target: Expr,
+        operation: Expr,
+    },
+
     // NewLine,
 }
 
@@ -0,0 +1,939 @@
+mod symbol_table;
+
+use std::collections::{HashMap, HashSet};
+
+use crate::error::CompileError;
+use crate::lexer::Span;
+use crate::parser::ast::*;
+use crate::semantic::symbol_table::{Symbol, SymbolTable};
+
+struct FunctionSignature {
+    params: Vec<Type>,
+    return_type: Type,
+}
+
+// Runs between parsing and code generation. Checks that every name is
+// declared exactly once in its scope and that the types line up, so the
+// generator can assume the program makes sense
+pub struct Analyzer {
+    symbols: SymbolTable,
+    functions: HashMap<String, FunctionSignature>,
+    structs: HashMap<String, Vec<StructField>>,
+
+    // Return type of the function being checked
+    return_type: Type,
+
+    errors: Vec<CompileError>,
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        let mut functions = HashMap::new();
+
+        let builtins = [
+            ("iprint", vec![Type::Int32], Type::Void),
+            ("sprint", vec![Type::String], Type::Void),
+            ("iread", vec![], Type::Int32),
+            ("sread", vec![Type::Int32], Type::String),
+            ("irandrange", vec![Type::Int32, Type::Int32], Type::Int32),
+            ("cprint", vec![Type::Char], Type::Void),
+            ("cread", vec![], Type::Char),
+            ("alloc", vec![Type::Int32], Type::pointer_to(Type::Void)),
+        ];
+
+        for (name, params, return_type) in builtins {
+            functions.insert(
+                name.to_string(),
+                FunctionSignature {
+                    params,
+                    return_type,
+                },
+            );
+        }
+
+        Analyzer {
+            symbols: SymbolTable::new(),
+            functions,
+            structs: HashMap::new(),
+            return_type: Type::Void,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn analyze(&mut self, program: &Program) -> Result<(), Vec<CompileError>> {
+        let text = &program.segments.text.body;
+
+        for stmt in text {
+            if let StatementKind::Struct { name, fields } = &stmt.kind {
+                self.declare_struct(name, fields, &stmt.span);
+            }
+        }
+
+        // A struct holding itself by value would never end
+        for stmt in text {
+            if let StatementKind::Struct { name, .. } = &stmt.kind
+                && self.contains_struct(name, name, &mut HashSet::new())
+            {
+                self.type_error(
+                    format!(
+                        "Struct '{}' contains itself, make the field a {}& instead",
+                        name, name
+                    ),
+                    &stmt.span,
+                );
+            }
+        }
+
+        // Signatures first so a function can be called above its definition
+        for stmt in text {
+            if let StatementKind::Function {
+                name,
+                params,
+                return_type,
+                ..
+            } = &stmt.kind
+            {
+                if self.functions.contains_key(name) {
+                    self.semantic_error(
+                        format!("Function '{}' is declared more than once", name),
+                        &stmt.span,
+                    );
+                    continue;
+                }
+
+                self.functions.insert(
+                    name.clone(),
+                    FunctionSignature {
+                        params: params.iter().map(|p| p.param_type.clone()).collect(),
+                        return_type: return_type.clone(),
+                    },
+                );
+            }
+        }
+
+        // Globals, like in C they're only visible below their declaration
+        self.symbols.push_scope();
+
+        for stmt in text {
+            match &stmt.kind {
+                StatementKind::Function {
+                    name,
+                    params,
+                    return_type,
+                    body,
+                    ..
+                } => self.check_function(name, params, return_type, body, &stmt.span),
+
+                StatementKind::Struct { .. } => {}
+
+                StatementKind::VariableDeclaration {
+                    var_type,
+                    identifier,
+                    operation,
+                } => {
+                    if let Type::Struct(name) = var_type.innermost() {
+                        self.type_error(
+                            format!(
+                                "Global '{}' can't be a struct, alloc a {} and keep a {}& instead",
+                                identifier, name, name
+                            ),
+                            &stmt.span,
+                        );
+                    }
+
+                    // They're laid out in the data segment before anything runs
+                    if !Self::is_constant(operation) {
+                        self.semantic_error(
+                            format!(
+                                "Global '{}' must be initialised with a constant",
+                                identifier
+                            ),
+                            &operation.span,
+                        );
+                    }
+
+                    self.check_statement(stmt);
+                }
+
+                _ => self.semantic_error(
+                    "Only functions, structs and variables can be declared at the top level"
+                        .to_string(),
+                    &stmt.span,
+                ),
+            }
+        }
+
+        self.symbols.pop_scope();
+
+        if !self.functions.contains_key("main") {
+            self.errors.push(CompileError::GenericError {
+                message: "No main function found".to_string(),
+            });
+        }
+
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    fn declare_struct(&mut self, name: &str, fields: &[StructField], span: &Span) {
+        if self.structs.contains_key(name) {
+            self.semantic_error(
+                format!("Struct '{}' is declared more than once", name),
+                span,
+            );
+            return;
+        }
+
+        let mut seen = HashSet::new();
+
+        for field in fields {
+            if !seen.insert(&field.name) {
+                self.semantic_error(
+                    format!("'{}' already has a field named '{}'", name, field.name),
+                    &field.span,
+                );
+            }
+
+            if field.field_type.innermost() == &Type::Void {
+                self.type_error(format!("Field '{}' can't be void", field.name), &field.span);
+            }
+        }
+
+        if fields.is_empty() {
+            self.type_error(format!("Struct '{}' has no fields", name), span);
+        }
+
+        self.structs.insert(name.to_string(), fields.to_vec());
+    }
+
+    // Whether name holds target by value somewhere down its fields
+    fn contains_struct(&self, name: &str, target: &str, visited: &mut HashSet<String>) -> bool {
+        if !visited.insert(name.to_string()) {
+            return false;
+        }
+
+        self.structs.get(name).into_iter().flatten().any(|field| {
+            matches!(field.field_type.innermost(), Type::Struct(inner)
+                if inner == target || self.contains_struct(inner, target, visited))
+        })
+    }
+
+    fn is_constant(expr: &Expr) -> bool {
+        match &expr.kind {
+            ExprKind::Integer(_)
+            | ExprKind::BoolLiteral(_)
+            | ExprKind::StringLiteral(_)
+            | ExprKind::CharLiteral(_)
+            | ExprKind::Empty => true,
+
+            ExprKind::ArrayInitializer { body, .. } => body
+                .iter()
+                .all(|element| matches!(element.kind, ExprKind::Integer(_))),
+
+            _ => false,
+        }
+    }
+
+    // The value of an integer expression made only of literals, wrapping
+    // the same way the generated code does. None if anything in it needs
+    // running, or it divides by zero itself (that gets its own error)
+    fn fold(expr: &Expr) -> Option<i32> {
+        match &expr.kind {
+            ExprKind::Integer(n) => Some(*n),
+
+            ExprKind::UnaryOp { operator, operand } => {
+                let n = Self::fold(operand)?;
+
+                match operator {
+                    UnaryOperator::Negate => Some(n.wrapping_neg()),
+                    UnaryOperator::BitwiseNot => Some(!n),
+                    UnaryOperator::Not => None,
+                }
+            }
+
+            ExprKind::BinaryOp {
+                left,
+                operator,
+                right,
+            } => {
+                let l = Self::fold(left)?;
+                let r = Self::fold(right)?;
+
+                match operator {
+                    BinaryOperator::Add => Some(l.wrapping_add(r)),
+                    BinaryOperator::Subtract => Some(l.wrapping_sub(r)),
+                    BinaryOperator::Multiply => Some(l.wrapping_mul(r)),
+                    BinaryOperator::Divide if r != 0 => Some(l.wrapping_div(r)),
+                    BinaryOperator::Modulo if r != 0 => Some(l.wrapping_rem(r)),
+                    BinaryOperator::BitwiseAnd => Some(l & r),
+                    BinaryOperator::BitwiseOr => Some(l | r),
+                    BinaryOperator::BitwiseXor => Some(l ^ r),
+                    BinaryOperator::ShiftLeft => Some(l.wrapping_shl(r as u32)),
+                    BinaryOperator::ShiftRight => Some(l.wrapping_shr(r as u32)),
+                    _ => None,
+                }
+            }
+
+            _ => None,
+        }
+    }
+
+    fn check_function(
+        &mut self,
+        name: &str,
+        params: &[Parameter],
+        return_type: &Type,
+        body: &[Statement],
+        span: &Span,
+    ) {
+        self.return_type = return_type.clone();
+
+        // Parameters share a scope with the top of the body, like C
+        self.symbols.push_scope();
+
+        for param in params {
+            if let Type::Struct(name) = &param.param_type {
+                self.type_error(
+                    format!("Structs can't be passed by value, use {}& instead", name),
+                    &param.span,
+                );
+            }
+
+            self.declare(&param.name, &param.param_type, &param.span);
+        }
+
+        if let Type::Struct(name) = return_type {
+            self.type_error(
+                format!("Structs can't be returned by value, use {}& instead", name),
+                span,
+            );
+        }
+
+        self.check_block(body);
+
+        // Falling off the end would hand back whatever was left in $v0
+        if *return_type != Type::Void && !Self::always_returns(body) {
+            self.semantic_error(
+                format!(
+                    "Function '{}' can get to the end without returning a {}",
+                    name, return_type
+                ),
+                span,
+            );
+        }
+
+        self.symbols.pop_scope();
+    }
+
+    // Whether running statements always ends in a return. There's no break,
+    // so a loop on a constant true only ever leaves through one
+    fn always_returns(statements: &[Statement]) -> bool {
+        statements.iter().any(|stmt| match &stmt.kind {
+            StatementKind::Return { .. } => true,
+
+            StatementKind::If {
+                body,
+                else_body: Some(else_body),
+                ..
+            } => Self::always_returns(body) && Self::always_returns(else_body),
+
+            StatementKind::While { condition, .. } | StatementKind::For { condition, .. } => {
+                condition.kind == ExprKind::BoolLiteral(true)
+            }
+
+            _ => false,
+        })
+    }
+
+    fn check_block(&mut self, statements: &[Statement]) {
+        for stmt in statements {
+            self.check_statement(stmt);
+        }
+    }
+
+    fn check_scoped_block(&mut self, statements: &[Statement]) {
+        self.symbols.push_scope();
+        self.check_block(statements);
+        self.symbols.pop_scope();
+    }
+
+    fn check_statement(&mut self, statement: &Statement) {
+        let span = &statement.span;
+
+        match &statement.kind {
+            StatementKind::VariableDeclaration {
+                var_type,
+                identifier,
+                operation,
+            } => {
+                if var_type.innermost() == &Type::Void {
+                    self.type_error(format!("Variable '{}' can't be void", identifier), span);
+                }
+
+                // Checked before declaring so `int32 x = x;` is an error
+                if let ExprKind::ArrayInitializer { body, .. } = &operation.kind {
+                    if *var_type != Type::pointer_to(Type::Int32) {
+                        self.type_error(
+                            format!(
+                                "Array '{}' must be declared as {}, not {}",
+                                identifier,
+                                Type::pointer_to(Type::Int32),
+                                var_type
+                            ),
+                            span,
+                        );
+                    }
+
+                    for element in body {
+                        let typ = self.type_of(element);
+                        self.expect_type(&Type::Int32, typ, &element.span);
+                    }
+                } else if operation.kind != ExprKind::Empty {
+                    let typ = self.type_of(operation);
+                    self.expect_type(var_type, typ, &operation.span);
+                }
+
+                self.declare(identifier, var_type, span);
+            }
+
+            StatementKind::VariableAssignment {
+                identifier,
+                operation,
+                is_dereference,
+                is_array_index,
+                indexer,
+            } => {
+                let value = self.type_of(operation);
+
+                let target = match self.lookup(identifier, span) {
+                    Some(typ) if *is_array_index => {
+                        let index = self.type_of(indexer);
+                        self.expect_type(&Type::Int32, index, &indexer.span);
+
+                        self.element_type(&format!("'{}'", identifier), &typ, span)
+                    }
+
+                    Some(typ) if *is_dereference => {
+                        self.pointee(&format!("'{}'", identifier), &typ, span)
+                    }
+
+                    typ => typ,
+                };
+
+                if let Some(target) = target {
+                    self.expect_type(&target, value, &operation.span);
+                }
+            }
+
+            StatementKind::FunctionCall {
+                function_name,
+                arguments,
+                ..
+            } => {
+                self.check_call(function_name, arguments, span);
+            }
+
+            StatementKind::While {
+                condition, body, ..
+            } => {
+                self.check_condition(condition);
+                self.check_scoped_block(body);
+            }
+
+            // The loop variable is only visible inside the loop
+            StatementKind::For {
+                init,
+                condition,
+                var_change,
+                body,
+                ..
+            } => {
+                self.symbols.push_scope();
+
+                self.check_statement(init);
+                self.check_condition(condition);
+                self.check_block(body);
+                self.check_statement(var_change);
+
+                self.symbols.pop_scope();
+            }
+
+            StatementKind::If {
+                condition,
+                body,
+                else_body,
+                ..
+            } => {
+                self.check_condition(condition);
+                self.check_scoped_block(body);
+
+                if let Some(else_body) = else_body {
+                    self.check_scoped_block(else_body);
+                }
+            }
+
+            StatementKind::Return { value } => {
+                let return_type = self.return_type.clone();
+
+                if value.kind == ExprKind::Empty {
+                    if return_type != Type::Void {
+                        self.type_error(
+                            format!("Expected a return value of type {}", return_type),
+                            span,
+                        );
+                    }
+                } else if return_type == Type::Void {
+                    self.type_error(
+                        "A void function can't return a value".to_string(),
+                        &value.span,
+                    );
+                } else {
+                    let typ = self.type_of(value);
+                    self.expect_type(&return_type, typ, &value.span);
+                }
+            }
+
+            StatementKind::ExprStatement(expr) => {
+                self.type_of(expr);
+            }
+
+            StatementKind::IndirectAssignment { target, operation } => {
+                let value = self.type_of(operation);
+
+                if let Some(target) = self.type_of(target) {
+                    self.expect_type(&target, value, &operation.span);
+                }
+            }
+
+            StatementKind::Struct { name, .. } => {
+                self.semantic_error(
+                    format!("Struct '{}' must be declared at the top level", name),
+                    span,
+                );
+            }
+
+            StatementKind::Function { name, .. } => {
+                self.semantic_error(
+                    format!(
+                        "Function '{}' can't be declared inside another function",
+                        name
+                    ),
+                    span,
+                );
+            }
+
+            StatementKind::Instruction { .. } | StatementKind::DataDeclaration { .. } => {}
+        }
+    }
+
+    // None means the expression was already reported, so the caller
+    // shouldn't pile another error on top
+    fn type_of(&mut self, expr: &Expr) -> Option<Type> {
+        let span = &expr.span;
+
+        match &expr.kind {
+            ExprKind::Integer(_) => Some(Type::Int32),
+            ExprKind::BoolLiteral(_) => Some(Type::Bool),
+            ExprKind::CharLiteral(_) => Some(Type::Char),
+            ExprKind::StringLiteral(_) => Some(Type::String),
+            ExprKind::Empty => Some(Type::Void),
+
+            ExprKind::Identifier(name) => self.lookup(name, span),
+
+            ExprKind::IdentifierReference(name) => {
+                let typ = self.lookup(name, span)?;
+                Some(Type::pointer_to(typ))
+            }
+
+            ExprKind::IdentifierDereference(name) => {
+                let typ = self.lookup(name, span)?;
+                self.pointee(&format!("'{}'", name), &typ, span)
+            }
+
+            ExprKind::ArrayIndex { array, indexer } => {
+                let index = self.type_of(indexer);
+                self.expect_type(&Type::Int32, index, &indexer.span);
+
+                let typ = self.type_of(array)?;
+                let subject = match &array.kind {
+                    ExprKind::Identifier(name) => format!("'{}'", name),
+                    _ => "This".to_string(),
+                };
+
+                self.element_type(&subject, &typ, span)
+            }
+
+            ExprKind::FieldAccess {
+                base,
+                field,
+                through_pointer,
+            } => {
+                let typ = self.type_of(base)?;
+                self.field_type(&typ, field, *through_pointer, span)
+            }
+
+            ExprKind::FunctionCall {
+                function_name,
+                arguments,
+                ..
+            } => self.check_call(function_name, arguments, span),
+
+            ExprKind::UnaryOp { operator, operand } => match operator {
+                UnaryOperator::Not => {
+                    self.check_condition(operand);
+                    Some(Type::Bool)
+                }
+
+                UnaryOperator::Negate | UnaryOperator::BitwiseNot => {
+                    let typ = self.type_of(operand);
+                    self.expect_type(&Type::Int32, typ, &operand.span);
+                    Some(Type::Int32)
+                }
+            },
+
+            ExprKind::BinaryOp {
+                left,
+                operator,
+                right,
+            } => self.check_binary(left, operator, right),
+
+            ExprKind::ArrayInitializer { .. } => {
+                self.type_error(
+                    "Array initializers are only allowed in declarations".to_string(),
+                    span,
+                );
+                None
+            }
+        }
+    }
+
+    fn check_binary(
+        &mut self,
+        left: &Expr,
+        operator: &BinaryOperator,
+        right: &Expr,
+    ) -> Option<Type> {
+        match operator {
+            BinaryOperator::LogicalAnd | BinaryOperator::LogicalOr => {
+                self.check_condition(left);
+                self.check_condition(right);
+                Some(Type::Bool)
+            }
+
+            // Any two values of the same type can be compared, strings by address
+            BinaryOperator::Equal | BinaryOperator::NotEqual => {
+                let left_type = self.type_of(left);
+                let right_type = self.type_of(right);
+
+                if let Some(Type::Void) = left_type {
+                    self.type_error("Can't compare a void value".to_string(), &left.span);
+                } else if let Some(Type::Struct(_)) = left_type {
+                    self.type_error(
+                        "Structs can't be compared, compare their fields or addresses".to_string(),
+                        &left.span,
+                    );
+                } else if let Some(Type::Array(..)) = left_type {
+                    self.type_error(
+                        "Arrays can't be compared, compare their elements instead".to_string(),
+                        &left.span,
+                    );
+                } else if let Some(left_type) = left_type {
+                    self.expect_type(&left_type, right_type, &right.span);
+                }
+
+                Some(Type::Bool)
+            }
+
+            BinaryOperator::LessThan
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::LessEqual
+            | BinaryOperator::GreaterEqual => {
+                let left_type = self.type_of(left);
+                self.expect_type(&Type::Int32, left_type, &left.span);
+
+                let right_type = self.type_of(right);
+                self.expect_type(&Type::Int32, right_type, &right.span);
+
+                Some(Type::Bool)
+            }
+
+            // p + i and i + p step through memory a whole element at a time
+            BinaryOperator::Add | BinaryOperator::Subtract => {
+                let left_type = self.type_of(left);
+                let right_type = self.type_of(right);
+
+                let (pointer, pointer_type, offset, offset_type) = match (&left_type, &right_type) {
+                    (Some(typ @ Type::Pointer(_)), _) => (left, typ.clone(), right, right_type),
+
+                    (_, Some(typ @ Type::Pointer(_))) if *operator == BinaryOperator::Add => {
+                        (right, typ.clone(), left, left_type)
+                    }
+
+                    _ => {
+                        self.expect_type(&Type::Int32, left_type, &left.span);
+                        self.expect_type(&Type::Int32, right_type, &right.span);
+                        return Some(Type::Int32);
+                    }
+                };
+
+                if pointer_type == Type::pointer_to(Type::Void) {
+                    self.type_error(
+                        format!("Can't do arithmetic on {}", pointer_type),
+                        &pointer.span,
+                    );
+                    return None;
+                }
+
+                self.expect_type(&Type::Int32, offset_type, &offset.span);
+                Some(pointer_type)
+            }
+
+            BinaryOperator::Multiply
+            | BinaryOperator::Divide
+            | BinaryOperator::Modulo
+            | BinaryOperator::BitwiseAnd
+            | BinaryOperator::BitwiseOr
+            | BinaryOperator::BitwiseXor
+            | BinaryOperator::ShiftLeft
+            | BinaryOperator::ShiftRight => {
+                let left_type = self.type_of(left);
+                self.expect_type(&Type::Int32, left_type, &left.span);
+
+                let right_type = self.type_of(right);
+                self.expect_type(&Type::Int32, right_type, &right.span);
+
+                if matches!(operator, BinaryOperator::Divide | BinaryOperator::Modulo)
+                    && Self::fold(right) == Some(0)
+                {
+                    self.type_error("Division by constant zero".to_string(), &right.span);
+                }
+
+                Some(Type::Int32)
+            }
+        }
+    }
+
+    fn check_call(&mut self, function_name: &str, arguments: &[Expr], span: &Span) -> Option<Type> {
+        let arg_types: Vec<Option<Type>> = arguments.iter().map(|arg| self.type_of(arg)).collect();
+
+        let (params, return_type) = match self.functions.get(function_name) {
+            Some(f) => (f.params.clone(), f.return_type.clone()),
+            None => {
+                self.semantic_error(
+                    format!("Call to undeclared function '{}'", function_name),
+                    span,
+                );
+                return None;
+            }
+        };
+
+        if params.len() != arguments.len() {
+            self.type_error(
+                format!(
+                    "'{}' expects {} argument{}, found {}",
+                    function_name,
+                    params.len(),
+                    if params.len() == 1 { "" } else { "s" },
+                    arguments.len()
+                ),
+                span,
+            );
+        } else {
+            for ((param, typ), arg) in params.iter().zip(arg_types).zip(arguments) {
+                self.expect_type(param, typ, &arg.span);
+            }
+        }
+
+        Some(return_type)
+    }
+
+    // Conditions are C style, any int32, char or pointer works as well as a bool
+    fn check_condition(&mut self, condition: &Expr) {
+        match self.type_of(condition) {
+            Some(Type::Bool | Type::Int32 | Type::Char | Type::Pointer(_)) | None => {}
+
+            Some(typ) => self.type_error(
+                format!(
+                    "Condition must be bool, int32, char or a pointer, found {}",
+                    typ
+                ),
+                &condition.span,
+            ),
+        }
+    }
+
+    // The type *name ends up with. subject is what the error calls it, like 'p'
+    fn pointee(&mut self, subject: &str, typ: &Type, span: &Span) -> Option<Type> {
+        match typ {
+            Type::Pointer(pointee) if **pointee != Type::Void => Some((**pointee).clone()),
+
+            Type::Pointer(_) => {
+                self.type_error(
+                    format!(
+                        "{} is {}, store it in a pointer to something first",
+                        subject, typ
+                    ),
+                    span,
+                );
+                None
+            }
+
+            _ => {
+                self.type_error(
+                    format!("{} is {}, only pointers can be dereferenced", subject, typ),
+                    span,
+                );
+                None
+            }
+        }
+    }
+
+    // The type a[i] ends up with, strings index to their characters
+    fn element_type(&mut self, subject: &str, typ: &Type, span: &Span) -> Option<Type> {
+        match typ {
+            Type::String => Some(Type::Char),
+            Type::Array(element, _) => Some((**element).clone()),
+            Type::Pointer(_) => self.pointee(subject, typ, span),
+
+            _ => {
+                self.type_error(
+                    format!(
+                        "{} is {}, only pointers, arrays and strings can be indexed",
+                        subject, typ
+                    ),
+                    span,
+                );
+                None
+            }
+        }
+    }
+
+    // The type of base.field, or base->field when through_pointer is set
+    fn field_type(
+        &mut self,
+        typ: &Type,
+        field: &str,
+        through_pointer: bool,
+        span: &Span,
+    ) -> Option<Type> {
+        let (target, is_pointer) = match typ {
+            Type::Pointer(pointee) => (&**pointee, true),
+            _ => (typ, false),
+        };
+
+        let Type::Struct(name) = target else {
+            self.type_error(format!("{} has no fields", typ), span);
+            return None;
+        };
+
+        if is_pointer != through_pointer {
+            let message = if is_pointer {
+                format!("{} is a pointer, use -> instead of .", typ)
+            } else {
+                format!("{} isn't a pointer, use . instead of ->", typ)
+            };
+
+            self.type_error(message, span);
+            return None;
+        }
+
+        let found = self
+            .structs
+            .get(name)
+            .and_then(|fields| fields.iter().find(|f| f.name == field))
+            .map(|f| f.field_type.clone());
+
+        if found.is_none() {
+            self.type_error(format!("'{}' has no field '{}'", name, field), span);
+        }
+
+        found
+    }
+
+    // A char converts to and from int32 like in C, so 'a' + 1 and
+    // cprint(65) work. void& converts to and from any pointer so alloc
+    // can be used for anything, and an array is a pointer to its first
+    // element everywhere except its declaration. Nothing else converts
+    fn expect_type(&mut self, expected: &Type, found: Option<Type>, span: &Span) {
+        if let Type::Array(..) = expected {
+            self.type_error(
+                "Arrays can't be assigned to, assign their elements instead".to_string(),
+                span,
+            );
+            return;
+        }
+
+        let found = found.map(|typ| match typ {
+            Type::Array(element, _) => Type::Pointer(element),
+            typ => typ,
+        });
+
+        let is_integer = |typ: &Type| matches!(typ, Type::Int32 | Type::Char);
+        let void_pointer = Type::pointer_to(Type::Void);
+
+        let converts = |a: &Type, b: &Type| {
+            (is_integer(a) && is_integer(b))
+                || (matches!((a, b), (Type::Pointer(_), Type::Pointer(_)))
+                    && (*a == void_pointer || *b == void_pointer))
+        };
+
+        if let Some(found) = found
+            && found != *expected
+            && !converts(&found, expected)
+        {
+            self.type_error(format!("Expected {}, found {}", expected, found), span);
+        } else if let Type::Struct(name) = expected {
+            self.type_error(
+                format!("Structs can't be copied, use a {}& instead", name),
+                span,
+            );
+        }
+    }
+
+    fn declare(&mut self, name: &str, typ: &Type, span: &Span) {
+        let symbol = Symbol {
+            typ: typ.clone(),
+            span: span.clone(),
+        };
+
+        if let Err(existing) = self.symbols.declare(name, symbol) {
+            self.semantic_error(
+                format!("'{}' is already declared at {}", name, existing.span),
+                span,
+            );
+        }
+    }
+
+    fn lookup(&mut self, name: &str, span: &Span) -> Option<Type> {
+        match self.symbols.lookup(name) {
+            Some(symbol) => Some(symbol.typ.clone()),
+            None => {
+                self.semantic_error(format!("Use of undeclared variable '{}'", name), span);
+                None
+            }
+        }
+    }
+
+    fn type_error(&mut self, message: String, span: &Span) {
+        self.errors.push(CompileError::TypeError {
+            message,
+            span: span.clone(),
+        });
+    }
+
+    fn semantic_error(&mut self, message: String, span: &Span) {
+        self.errors.push(CompileError::SemanticError {
+            message,
+            span: span.clone(),
+        });
+    }
+}
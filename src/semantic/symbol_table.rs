@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use crate::lexer::Span;
+use crate::parser::ast::Type;
+
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub typ: Type,
+
+    // Where it was declared, for duplicate declaration errors
+    pub span: Span,
+}
+
+// One map per block, the innermost scope is last
+pub struct SymbolTable {
+    scopes: Vec<HashMap<String, Symbol>>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        SymbolTable { scopes: Vec::new() }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    // Hands back the existing symbol if the name is already taken in this scope,
+    // shadowing something from an outer scope is fine
+    pub fn declare(&mut self, name: &str, symbol: Symbol) -> Result<(), Symbol> {
+        let scope = match self.scopes.last_mut() {
+            Some(s) => s,
+            None => panic!("Declared {} outside of any scope", name),
+        };
+
+        if let Some(existing) = scope.get(name) {
+            return Err(existing.clone());
+        }
+
+        scope.insert(name.to_string(), symbol);
+        Ok(())
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<&Symbol> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+}
@@ -0,0 +1,393 @@
+mod assembler;
+pub(crate) mod console;
+pub(crate) mod memory;
+
+use std::fmt;
+use std::io::{BufRead, Write};
+
+use crate::rng::Rng;
+use assembler::{A0, A1, AluOp, Assembly, Condition, GP, Instruction, Operand, RA, SP, V0};
+use memory::{Memory, STACK_TOP, TEXT_BASE};
+
+// Something that went wrong assembling or running, line is the line of
+// the assembly it happened on when there is one
+#[derive(Debug)]
+pub struct SimError {
+    pub message: String,
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for SimError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{} (line {} of the assembly)", self.message, line),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for SimError {}
+
+// Runs the assembly MipsGenerator produces without needing MARS. Only
+// knows the instructions and syscalls ecc actually uses, plus a few
+// neighbours so hand written assembly isn't hopeless
+pub struct Simulator {
+    assembly: Assembly,
+    memory: Memory,
+    registers: [u32; 32],
+    hi: u32,
+    lo: u32,
+
+    // An index into assembly.text, not an address
+    pc: usize,
+
+    rng: Rng,
+}
+
+impl Simulator {
+    pub fn new(source: &str, rng: Rng) -> Result<Self, SimError> {
+        let assembly = assembler::assemble(source)?;
+        let memory = Memory::new(&assembly.data);
+
+        let mut registers = [0; 32];
+        registers[GP] = 0x1000_8000;
+        registers[SP] = STACK_TOP;
+
+        Ok(Simulator {
+            assembly,
+            memory,
+            registers,
+            hi: 0,
+            lo: 0,
+            pc: 0,
+            rng,
+        })
+    }
+
+    // Runs until the program exits and hands back its exit code. Falling
+    // off the end of .text counts as exiting with 0, same as MARS. With
+    // max_steps set an infinite loop is an error instead of a hang
+    pub fn run(
+        &mut self,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+        max_steps: Option<u64>,
+    ) -> Result<i32, SimError> {
+        let result = self.execute(input, output, max_steps);
+
+        // Whatever got printed before an error is still worth seeing
+        output.flush().map_err(|e| SimError {
+            message: format!("Failed to write output: {}", e),
+            line: None,
+        })?;
+
+        result
+    }
+
+    fn execute(
+        &mut self,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+        max_steps: Option<u64>,
+    ) -> Result<i32, SimError> {
+        let mut steps: u64 = 0;
+
+        while self.pc < self.assembly.text.len() {
+            let line = self.assembly.lines[self.pc];
+
+            if max_steps.is_some_and(|max| steps >= max) {
+                return Err(SimError {
+                    message: format!("Gave up after {} instructions", steps),
+                    line: Some(line),
+                });
+            }
+
+            steps += 1;
+
+            let exit = self.step(input, output).map_err(|message| SimError {
+                message,
+                line: Some(line),
+            })?;
+
+            if let Some(code) = exit {
+                return Ok(code);
+            }
+        }
+
+        Ok(0)
+    }
+
+    // Some(code) once the program asks to exit
+    fn step(
+        &mut self,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<Option<i32>, String> {
+        let instruction = self.assembly.text[self.pc];
+        self.pc += 1;
+
+        match instruction {
+            Instruction::Alu { op, rd, rs, rt } => {
+                let value = alu(op, self.registers[rs], self.operand(rt))?;
+                self.set(rd, value);
+            }
+
+            Instruction::Load {
+                size,
+                signed,
+                rt,
+                base,
+                offset,
+            } => {
+                let address = self.registers[base].wrapping_add(offset as u32);
+                let value = self.memory.load(address, size)?;
+                let bits = size * 8;
+
+                let value = if signed && bits < 32 {
+                    ((value << (32 - bits)) as i32 >> (32 - bits)) as u32
+                } else {
+                    value
+                };
+
+                self.set(rt, value);
+            }
+
+            Instruction::Store {
+                size,
+                rt,
+                base,
+                offset,
+            } => {
+                let address = self.registers[base].wrapping_add(offset as u32);
+                self.memory.store(address, size, self.registers[rt])?;
+            }
+
+            Instruction::Branch {
+                condition,
+                rs,
+                rt,
+                target,
+            } => {
+                let a = self.registers[rs] as i32;
+                let b = self.operand(rt) as i32;
+
+                let taken = match condition {
+                    Condition::Equal => a == b,
+                    Condition::NotEqual => a != b,
+                    Condition::Less => a < b,
+                    Condition::LessEqual => a <= b,
+                    Condition::Greater => a > b,
+                    Condition::GreaterEqual => a >= b,
+                };
+
+                if taken {
+                    self.pc = target;
+                }
+            }
+
+            Instruction::Jump { target, link } => {
+                if link {
+                    self.set(RA, self.return_address());
+                }
+
+                self.pc = target;
+            }
+
+            Instruction::JumpRegister { rs, link } => {
+                let address = self.registers[rs];
+                let index = address.wrapping_sub(TEXT_BASE) / 4;
+
+                if address < TEXT_BASE
+                    || !address.is_multiple_of(4)
+                    || index as usize > self.assembly.text.len()
+                {
+                    return Err(format!("Jumped to 0x{:08x}, which isn't code", address));
+                }
+
+                if link {
+                    self.set(RA, self.return_address());
+                }
+
+                self.pc = index as usize;
+            }
+
+            Instruction::Multiply { rs, rt, signed } => {
+                let product = if signed {
+                    (self.registers[rs] as i32 as i64 * self.registers[rt] as i32 as i64) as u64
+                } else {
+                    self.registers[rs] as u64 * self.registers[rt] as u64
+                };
+
+                self.hi = (product >> 32) as u32;
+                self.lo = product as u32;
+            }
+
+            // Like MARS, dividing by zero doesn't trap and leaves HI and LO
+            // as they were. Code that cares checks first, which is what the
+            // three operand div and rem do
+            Instruction::Divide { rs, rt, signed } => {
+                let (a, b) = (self.registers[rs], self.registers[rt]);
+
+                if b == 0 {
+                    return Ok(None);
+                }
+
+                if signed {
+                    self.lo = (a as i32).wrapping_div(b as i32) as u32;
+                    self.hi = (a as i32).wrapping_rem(b as i32) as u32;
+                } else {
+                    self.lo = a / b;
+                    self.hi = a % b;
+                }
+            }
+
+            Instruction::MoveFromHi(rd) => self.set(rd, self.hi),
+            Instruction::MoveFromLo(rd) => self.set(rd, self.lo),
+            Instruction::Syscall => return self.syscall(input, output),
+            Instruction::Nop => {}
+        }
+
+        Ok(None)
+    }
+
+    // Same numbers and registers as MARS
+    fn syscall(
+        &mut self,
+        input: &mut dyn BufRead,
+        output: &mut dyn Write,
+    ) -> Result<Option<i32>, String> {
+        let a0 = self.registers[A0];
+        let a1 = self.registers[A1];
+        let write_error = |e: std::io::Error| format!("Failed to write output: {}", e);
+
+        match self.registers[V0] {
+            // Print integer
+            1 => write!(output, "{}", a0 as i32).map_err(write_error)?,
+
+            // Print string
+            4 => {
+                let bytes = self.memory.load_string(a0)?;
+                output.write_all(&bytes).map_err(write_error)?;
+            }
+
+            // Read integer
+            5 => {
+                output.flush().map_err(write_error)?;
+
+                let value = console::read_integer(input)?;
+                self.set(V0, value as u32);
+            }
+
+            // Read string into the a1 byte buffer at a0
+            8 => {
+                output.flush().map_err(write_error)?;
+
+                let Some(bytes) = console::read_string(input, a1 as i32)? else {
+                    return Ok(None);
+                };
+
+                for (i, byte) in bytes.iter().enumerate() {
+                    self.memory
+                        .store(a0.wrapping_add(i as u32), 1, *byte as u32)?;
+                }
+            }
+
+            // sbrk
+            9 => {
+                let address = self.memory.sbrk(a0 as i32)?;
+                self.set(V0, address);
+            }
+
+            // Exit
+            10 => return Ok(Some(0)),
+
+            // Print character
+            11 => output.write_all(&[a0 as u8]).map_err(write_error)?,
+
+            // Read character
+            12 => {
+                output.flush().map_err(write_error)?;
+
+                let byte = console::read_character(input)?;
+                self.set(V0, byte as u32);
+            }
+
+            // Exit with a code
+            17 => return Ok(Some(a0 as i32)),
+
+            // Random int in [0, a1), a0 picks the generator but there's only one
+            42 => {
+                if (a1 as i32) <= 0 {
+                    return Err(format!(
+                        "The upper bound for a random number has to be positive, found {}",
+                        a1 as i32
+                    ));
+                }
+
+                let value = self.rng.below(a1 as i32);
+                self.set(A0, value as u32);
+            }
+
+            other => return Err(format!("Unsupported syscall {}", other)),
+        }
+
+        Ok(None)
+    }
+
+    fn operand(&self, operand: Operand) -> u32 {
+        match operand {
+            Operand::Register(r) => self.registers[r],
+            Operand::Immediate(n) => n as u32,
+        }
+    }
+
+    // $zero stays zero whatever gets written to it
+    fn set(&mut self, register: usize, value: u32) {
+        if register != 0 {
+            self.registers[register] = value;
+        }
+    }
+
+    // pc has already moved past the jump
+    fn return_address(&self) -> u32 {
+        TEXT_BASE + 4 * self.pc as u32
+    }
+}
+
+fn alu(op: AluOp, a: u32, b: u32) -> Result<u32, String> {
+    let (x, y) = (a as i32, b as i32);
+    let overflow = || format!("Arithmetic overflow with {} and {}", x, y);
+
+    let value = match op {
+        AluOp::Add => x.checked_add(y).ok_or_else(overflow)? as u32,
+        AluOp::Addu => a.wrapping_add(b),
+        AluOp::Sub => x.checked_sub(y).ok_or_else(overflow)? as u32,
+        AluOp::Subu => a.wrapping_sub(b),
+        AluOp::Mul => x.wrapping_mul(y) as u32,
+        AluOp::Mulo => x.checked_mul(y).ok_or_else(overflow)? as u32,
+
+        // The pseudo instructions check for zero and break before dividing
+        AluOp::Div | AluOp::Rem if y == 0 => return Err("Division by zero".to_string()),
+        AluOp::Div => x.wrapping_div(y) as u32,
+        AluOp::Rem => x.wrapping_rem(y) as u32,
+
+        AluOp::And => a & b,
+        AluOp::Or => a | b,
+        AluOp::Xor => a ^ b,
+        AluOp::Nor => !(a | b),
+        AluOp::Slt => (x < y) as u32,
+        AluOp::Sltu => (a < b) as u32,
+        AluOp::Seq => (a == b) as u32,
+        AluOp::Sne => (a != b) as u32,
+        AluOp::Sgt => (x > y) as u32,
+        AluOp::Sge => (x >= y) as u32,
+        AluOp::Sle => (x <= y) as u32,
+
+        // Only the low five bits of the shift amount count
+        AluOp::Sll => a << (b & 31),
+        AluOp::Srl => a >> (b & 31),
+        AluOp::Sra => (x >> (b & 31)) as u32,
+    };
+
+    Ok(value)
+}
@@ -0,0 +1,780 @@
+use std::collections::HashMap;
+
+use super::SimError;
+use super::memory::{DATA_BASE, TEXT_BASE};
+
+pub const V0: usize = 2;
+pub const A0: usize = 4;
+pub const A1: usize = 5;
+pub const GP: usize = 28;
+pub const SP: usize = 29;
+pub const RA: usize = 31;
+
+const REGISTER_NAMES: [&str; 32] = [
+    "zero", "at", "v0", "v1", "a0", "a1", "a2", "a3", "t0", "t1", "t2", "t3", "t4", "t5", "t6",
+    "t7", "s0", "s1", "s2", "s3", "s4", "s5", "s6", "s7", "t8", "t9", "k0", "k1", "gp", "sp", "fp",
+    "ra",
+];
+
+// Either a register or a constant baked into the instruction. MARS lets
+// most instructions take either in the last slot and expands the
+// immediate form itself, so they share one shape here
+#[derive(Debug, Clone, Copy)]
+pub enum Operand {
+    Register(usize),
+    Immediate(i32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AluOp {
+    // Add, Sub and Mulo trap on signed overflow, like MARS
+    Add,
+    Addu,
+    Sub,
+    Subu,
+    Mul,
+    Mulo,
+    Div,
+    Rem,
+    And,
+    Or,
+    Xor,
+    Nor,
+    Slt,
+    Sltu,
+    Seq,
+    Sne,
+    Sgt,
+    Sge,
+    Sle,
+    Sll,
+    Srl,
+    Sra,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Condition {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+// One instruction as written in the source, pseudo instructions included.
+// Jump targets are indexes into the text segment rather than addresses
+#[derive(Debug, Clone, Copy)]
+pub enum Instruction {
+    Alu {
+        op: AluOp,
+        rd: usize,
+        rs: usize,
+        rt: Operand,
+    },
+
+    Load {
+        size: u32,
+        signed: bool,
+        rt: usize,
+        base: usize,
+        offset: i32,
+    },
+
+    Store {
+        size: u32,
+        rt: usize,
+        base: usize,
+        offset: i32,
+    },
+
+    Branch {
+        condition: Condition,
+        rs: usize,
+        rt: Operand,
+        target: usize,
+    },
+
+    Jump {
+        target: usize,
+        link: bool,
+    },
+
+    JumpRegister {
+        rs: usize,
+        link: bool,
+    },
+
+    // The two operand forms that leave their result in HI and LO
+    Multiply {
+        rs: usize,
+        rt: usize,
+        signed: bool,
+    },
+
+    Divide {
+        rs: usize,
+        rt: usize,
+        signed: bool,
+    },
+
+    MoveFromHi(usize),
+    MoveFromLo(usize),
+    Syscall,
+    Nop,
+}
+
+pub struct Assembly {
+    pub text: Vec<Instruction>,
+
+    // The source line each instruction came from, for runtime errors
+    pub lines: Vec<usize>,
+
+    // Everything the .data directives laid out, starting at DATA_BASE
+    pub data: Vec<u8>,
+}
+
+// An instruction seen in the first pass, parsed once every label is known
+struct Pending {
+    line: usize,
+    mnemonic: String,
+    operands: Vec<String>,
+}
+
+#[derive(PartialEq)]
+enum Segment {
+    Text,
+    Data,
+}
+
+// Two passes, the first lays out .data and finds every label, the second
+// turns the instructions into something the simulator can run
+pub fn assemble(source: &str) -> Result<Assembly, SimError> {
+    let mut labels: HashMap<String, u32> = HashMap::new();
+    let mut pending: Vec<Pending> = Vec::new();
+    let mut data: Vec<u8> = Vec::new();
+
+    // .word can name a label that hasn't been seen yet, patched in at the end
+    let mut fixups: Vec<(usize, String, usize)> = Vec::new();
+
+    // Labels on a line of their own in .data belong to whatever comes next,
+    // after any alignment that directive does
+    let mut unplaced: Vec<String> = Vec::new();
+
+    let mut segment = Segment::Text;
+
+    for (i, raw) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let error = |message: String| SimError {
+            message,
+            line: Some(line_number),
+        };
+
+        let mut line = strip_comment(raw).trim();
+
+        while let Some((label, rest)) = split_label(line) {
+            if labels.contains_key(label) || unplaced.iter().any(|l| l == label) {
+                return Err(error(format!(
+                    "Label '{}' is defined more than once",
+                    label
+                )));
+            }
+
+            match segment {
+                Segment::Text => {
+                    labels.insert(label.to_string(), TEXT_BASE + 4 * pending.len() as u32);
+                }
+                Segment::Data => unplaced.push(label.to_string()),
+            }
+
+            line = rest.trim();
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let (word, rest) = match line.split_once(char::is_whitespace) {
+            Some((word, rest)) => (word, rest.trim()),
+            None => (line, ""),
+        };
+
+        if let Some(directive) = word.strip_prefix('.') {
+            match directive {
+                "text" => segment = Segment::Text,
+                "data" => segment = Segment::Data,
+                "globl" | "global" | "extern" => {}
+
+                _ if segment == Segment::Text => {
+                    return Err(error(format!(
+                        "'.{}' only works in the .data segment",
+                        directive
+                    )));
+                }
+
+                _ => {
+                    // Labels point past any padding the directive needs
+                    let alignment = alignment_of(directive, rest).map_err(error)?;
+                    data.resize(data.len().next_multiple_of(alignment), 0);
+
+                    for label in unplaced.drain(..) {
+                        labels.insert(label, DATA_BASE + data.len() as u32);
+                    }
+
+                    lay_out_data(directive, rest, &mut data, &mut fixups, line_number)
+                        .map_err(error)?;
+                }
+            }
+
+            continue;
+        }
+
+        if segment == Segment::Data {
+            return Err(error(format!(
+                "Found instruction '{}' in the .data segment",
+                word
+            )));
+        }
+
+        pending.push(Pending {
+            line: line_number,
+            mnemonic: word.to_string(),
+            operands: split_operands(rest),
+        });
+    }
+
+    for label in unplaced {
+        labels.insert(label, DATA_BASE + data.len() as u32);
+    }
+
+    for (offset, label, line) in fixups {
+        let address = *labels.get(&label).ok_or_else(|| SimError {
+            message: format!("Undefined label '{}'", label),
+            line: Some(line),
+        })?;
+
+        data[offset..offset + 4].copy_from_slice(&address.to_le_bytes());
+    }
+
+    let mut text = Vec::new();
+    let mut lines = Vec::new();
+
+    for p in &pending {
+        let instruction = parse_instruction(&p.mnemonic, &p.operands, &labels, pending.len())
+            .map_err(|message| SimError {
+                message,
+                line: Some(p.line),
+            })?;
+
+        text.push(instruction);
+        lines.push(p.line);
+    }
+
+    Ok(Assembly { text, lines, data })
+}
+
+fn lay_out_data(
+    directive: &str,
+    rest: &str,
+    data: &mut Vec<u8>,
+    fixups: &mut Vec<(usize, String, usize)>,
+    line: usize,
+) -> Result<(), String> {
+    match directive {
+        "asciiz" | "ascii" => {
+            for value in split_operands(rest) {
+                let text = value
+                    .strip_prefix('"')
+                    .and_then(|v| v.strip_suffix('"'))
+                    .ok_or_else(|| format!("Expected a quoted string, found {}", value))?;
+
+                data.extend(unescape(text)?);
+
+                if directive == "asciiz" {
+                    data.push(0);
+                }
+            }
+        }
+
+        "word" | "half" | "byte" => {
+            let size = match directive {
+                "word" => 4,
+                "half" => 2,
+                _ => 1,
+            };
+
+            for value in split_operands(rest) {
+                let number = match parse_number(&value) {
+                    Some(n) => n,
+                    None if size == 4 && is_label(&value) => {
+                        fixups.push((data.len(), value.clone(), line));
+                        0
+                    }
+                    None => return Err(format!("Expected a number, found '{}'", value)),
+                };
+
+                data.extend(&number.to_le_bytes()[..size]);
+            }
+        }
+
+        "space" => {
+            let n = parse_number(rest)
+                .filter(|n| *n >= 0)
+                .ok_or_else(|| format!("Expected a size, found '{}'", rest))?;
+
+            data.resize(data.len() + n as usize, 0);
+        }
+
+        // alignment_of already did the work
+        "align" => {}
+
+        _ => return Err(format!("Unknown directive '.{}'", directive)),
+    }
+
+    Ok(())
+}
+
+// MARS lines .word and .half up on their own, .align n pads to 2^n
+fn alignment_of(directive: &str, rest: &str) -> Result<usize, String> {
+    match directive {
+        "word" => Ok(4),
+        "half" => Ok(2),
+        "align" => parse_number(rest)
+            .filter(|n| (0..=3).contains(n))
+            .map(|n| 1 << n)
+            .ok_or_else(|| format!("'.align' takes 0 to 3, found '{}'", rest)),
+        _ => Ok(1),
+    }
+}
+
+fn parse_instruction(
+    mnemonic: &str,
+    operands: &[String],
+    labels: &HashMap<String, u32>,
+    text_length: usize,
+) -> Result<Instruction, String> {
+    let expect = |count: usize| {
+        if operands.len() == count {
+            Ok(())
+        } else {
+            Err(format!(
+                "'{}' takes {} operands, found {}",
+                mnemonic,
+                count,
+                operands.len()
+            ))
+        }
+    };
+
+    let reg = |i: usize| register(&operands[i]);
+    let operand = |i: usize| -> Result<Operand, String> {
+        if operands[i].starts_with('$') {
+            Ok(Operand::Register(register(&operands[i])?))
+        } else {
+            Ok(Operand::Immediate(immediate(&operands[i])?))
+        }
+    };
+
+    let target = |i: usize| -> Result<usize, String> {
+        let label = &operands[i];
+        let address = *labels
+            .get(label)
+            .ok_or_else(|| format!("Undefined label '{}'", label))?;
+        let index = address.wrapping_sub(TEXT_BASE) as usize / 4;
+
+        if address < TEXT_BASE || index > text_length {
+            return Err(format!("'{}' isn't a label in the .text segment", label));
+        }
+
+        Ok(index)
+    };
+
+    let alu = |op: AluOp| -> Result<Instruction, String> {
+        expect(3)?;
+        Ok(Instruction::Alu {
+            op,
+            rd: reg(0)?,
+            rs: reg(1)?,
+            rt: operand(2)?,
+        })
+    };
+
+    let load = |size: u32, signed: bool| -> Result<Instruction, String> {
+        expect(2)?;
+        let (base, offset) = address(&operands[1], labels)?;
+        Ok(Instruction::Load {
+            size,
+            signed,
+            rt: reg(0)?,
+            base,
+            offset,
+        })
+    };
+
+    let store = |size: u32| -> Result<Instruction, String> {
+        expect(2)?;
+        let (base, offset) = address(&operands[1], labels)?;
+        Ok(Instruction::Store {
+            size,
+            rt: reg(0)?,
+            base,
+            offset,
+        })
+    };
+
+    let branch = |condition: Condition| -> Result<Instruction, String> {
+        expect(3)?;
+        Ok(Instruction::Branch {
+            condition,
+            rs: reg(0)?,
+            rt: operand(1)?,
+            target: target(2)?,
+        })
+    };
+
+    // beqz and friends compare against zero
+    let branch_zero = |condition: Condition| -> Result<Instruction, String> {
+        expect(2)?;
+        Ok(Instruction::Branch {
+            condition,
+            rs: reg(0)?,
+            rt: Operand::Register(0),
+            target: target(1)?,
+        })
+    };
+
+    match mnemonic {
+        "add" | "addi" => alu(AluOp::Add),
+        "addu" | "addiu" => alu(AluOp::Addu),
+        "sub" | "subi" => alu(AluOp::Sub),
+        "subu" | "subiu" => alu(AluOp::Subu),
+        "mul" => alu(AluOp::Mul),
+        "mulo" => alu(AluOp::Mulo),
+        "rem" => alu(AluOp::Rem),
+        "and" | "andi" => alu(AluOp::And),
+        "or" | "ori" => alu(AluOp::Or),
+        "xor" | "xori" => alu(AluOp::Xor),
+        "nor" => alu(AluOp::Nor),
+        "slt" | "slti" => alu(AluOp::Slt),
+        "sltu" | "sltiu" => alu(AluOp::Sltu),
+        "seq" => alu(AluOp::Seq),
+        "sne" => alu(AluOp::Sne),
+        "sgt" => alu(AluOp::Sgt),
+        "sge" => alu(AluOp::Sge),
+        "sle" => alu(AluOp::Sle),
+        "sll" | "sllv" => alu(AluOp::Sll),
+        "srl" | "srlv" => alu(AluOp::Srl),
+        "sra" | "srav" => alu(AluOp::Sra),
+
+        // div with three operands is the pseudo instruction for the quotient
+        "div" if operands.len() == 3 => alu(AluOp::Div),
+        "div" | "divu" | "mult" | "multu" => {
+            expect(2)?;
+            let (rs, rt) = (reg(0)?, reg(1)?);
+            let signed = !mnemonic.ends_with('u');
+
+            if mnemonic.starts_with("div") {
+                Ok(Instruction::Divide { rs, rt, signed })
+            } else {
+                Ok(Instruction::Multiply { rs, rt, signed })
+            }
+        }
+
+        "mfhi" => {
+            expect(1)?;
+            Ok(Instruction::MoveFromHi(reg(0)?))
+        }
+
+        "mflo" => {
+            expect(1)?;
+            Ok(Instruction::MoveFromLo(reg(0)?))
+        }
+
+        "li" => {
+            expect(2)?;
+            Ok(Instruction::Alu {
+                op: AluOp::Addu,
+                rd: reg(0)?,
+                rs: 0,
+                rt: Operand::Immediate(immediate(&operands[1])?),
+            })
+        }
+
+        "la" => {
+            expect(2)?;
+            let (base, offset) = address(&operands[1], labels)?;
+            Ok(Instruction::Alu {
+                op: AluOp::Addu,
+                rd: reg(0)?,
+                rs: base,
+                rt: Operand::Immediate(offset),
+            })
+        }
+
+        "move" | "not" | "neg" => {
+            expect(2)?;
+            let (rd, rs) = (reg(0)?, reg(1)?);
+
+            Ok(match mnemonic {
+                "move" => Instruction::Alu {
+                    op: AluOp::Addu,
+                    rd,
+                    rs,
+                    rt: Operand::Register(0),
+                },
+                "not" => Instruction::Alu {
+                    op: AluOp::Nor,
+                    rd,
+                    rs,
+                    rt: Operand::Register(0),
+                },
+                _ => Instruction::Alu {
+                    op: AluOp::Sub,
+                    rd,
+                    rs: 0,
+                    rt: Operand::Register(rs),
+                },
+            })
+        }
+
+        "lw" => load(4, true),
+        "lh" => load(2, true),
+        "lhu" => load(2, false),
+        "lb" => load(1, true),
+        "lbu" => load(1, false),
+        "sw" => store(4),
+        "sh" => store(2),
+        "sb" => store(1),
+
+        "beq" => branch(Condition::Equal),
+        "bne" => branch(Condition::NotEqual),
+        "blt" => branch(Condition::Less),
+        "ble" => branch(Condition::LessEqual),
+        "bgt" => branch(Condition::Greater),
+        "bge" => branch(Condition::GreaterEqual),
+        "beqz" => branch_zero(Condition::Equal),
+        "bnez" => branch_zero(Condition::NotEqual),
+        "bltz" => branch_zero(Condition::Less),
+        "blez" => branch_zero(Condition::LessEqual),
+        "bgtz" => branch_zero(Condition::Greater),
+        "bgez" => branch_zero(Condition::GreaterEqual),
+
+        "b" | "j" | "jal" => {
+            expect(1)?;
+            Ok(Instruction::Jump {
+                target: target(0)?,
+                link: mnemonic == "jal",
+            })
+        }
+
+        "jr" | "jalr" => {
+            expect(1)?;
+            Ok(Instruction::JumpRegister {
+                rs: reg(0)?,
+                link: mnemonic == "jalr",
+            })
+        }
+
+        "syscall" => {
+            expect(0)?;
+            Ok(Instruction::Syscall)
+        }
+
+        "nop" => {
+            expect(0)?;
+            Ok(Instruction::Nop)
+        }
+
+        _ => Err(format!("Unknown instruction '{}'", mnemonic)),
+    }
+}
+
+fn register(text: &str) -> Result<usize, String> {
+    let name = text
+        .strip_prefix('$')
+        .ok_or_else(|| format!("Expected a register, found '{}'", text))?;
+
+    if let Ok(n) = name.parse::<usize>()
+        && n < 32
+    {
+        return Ok(n);
+    }
+
+    REGISTER_NAMES
+        .iter()
+        .position(|r| *r == name)
+        .ok_or_else(|| format!("Unknown register '{}'", text))
+}
+
+fn immediate(text: &str) -> Result<i32, String> {
+    parse_number(text).ok_or_else(|| format!("Expected a number, found '{}'", text))
+}
+
+// Everything a load, store or la can point at: offset($reg), ($reg),
+// label, label+offset or a plain address. Hands back a base register and offset
+fn address(text: &str, labels: &HashMap<String, u32>) -> Result<(usize, i32), String> {
+    if let Some((offset, base)) = text.split_once('(') {
+        let base = base
+            .strip_suffix(')')
+            .ok_or_else(|| format!("Missing ')' in '{}'", text))?;
+        let offset = offset.trim();
+        let offset = if offset.is_empty() {
+            0
+        } else {
+            immediate(offset)?
+        };
+
+        return Ok((register(base.trim())?, offset));
+    }
+
+    if let Some(n) = parse_number(text) {
+        return Ok((0, n));
+    }
+
+    let (label, offset) = match text.split_once('+') {
+        Some((label, offset)) => (label.trim(), immediate(offset.trim())?),
+        None => (text, 0),
+    };
+
+    let address = labels
+        .get(label)
+        .ok_or_else(|| format!("Undefined label '{}'", label))?;
+
+    Ok((0, address.wrapping_add(offset as u32) as i32))
+}
+
+// Decimal, hex or a quoted character, the way MARS reads them. Hex is
+// allowed to fill all 32 bits
+fn parse_number(text: &str) -> Option<i32> {
+    let text = text.trim();
+
+    if let Some(quoted) = text.strip_prefix('\'').and_then(|t| t.strip_suffix('\'')) {
+        let bytes = unescape(quoted).ok()?;
+
+        return match bytes.as_slice() {
+            [byte] => Some(*byte as i32),
+            _ => None,
+        };
+    }
+
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()? as i64,
+        None if digits.starts_with(|c: char| c.is_ascii_digit()) => digits.parse::<i64>().ok()?,
+        None => return None,
+    };
+
+    let value = if negative { -value } else { value };
+
+    if value < i32::MIN as i64 || value > u32::MAX as i64 {
+        return None;
+    }
+
+    Some(value as i32)
+}
+
+fn is_label(text: &str) -> bool {
+    text.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_' || c == '.')
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$')
+}
+
+// "name: rest" to (name, rest), as long as the colon isn't inside a string
+fn split_label(line: &str) -> Option<(&str, &str)> {
+    let (label, rest) = line.split_once(':')?;
+
+    if is_label(label.trim()) {
+        Some((label.trim(), rest))
+    } else {
+        None
+    }
+}
+
+// Everything after a # that isn't in a string or character literal
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {}
+        }
+    }
+
+    line
+}
+
+// Splits on commas that aren't inside a string or character literal
+fn split_operands(text: &str) -> Vec<String> {
+    let mut operands = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut escaped = false;
+
+    for c in text.chars() {
+        match quote {
+            Some(_) if escaped => escaped = false,
+            Some(_) if c == '\\' => escaped = true,
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == ',' => {
+                operands.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            None => {}
+        }
+
+        current.push(c);
+    }
+
+    if !current.trim().is_empty() {
+        operands.push(current.trim().to_string());
+    }
+
+    operands
+}
+
+fn unescape(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+
+        let byte = match chars.next() {
+            Some('n') => b'\n',
+            Some('t') => b'\t',
+            Some('r') => b'\r',
+            Some('0') => 0,
+            Some('\\') => b'\\',
+            Some('"') => b'"',
+            Some('\'') => b'\'',
+            Some(other) => return Err(format!("Unknown escape '\\{}'", other)),
+            None => return Err("String ends in a lone '\\'".to_string()),
+        };
+
+        bytes.push(byte);
+    }
+
+    Ok(bytes)
+}
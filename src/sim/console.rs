@@ -0,0 +1,61 @@
+use std::io::BufRead;
+
+// How the MARS read syscalls treat stdin. The interpreter reads through
+// these too so both agree on what a program sees
+
+pub fn read_integer(input: &mut dyn BufRead) -> Result<i32, String> {
+    let line = read_line(input)?.ok_or("Tried to read an integer past the end of input")?;
+    let text = String::from_utf8_lossy(&line);
+
+    text.trim()
+        .parse::<i32>()
+        .map_err(|_| format!("'{}' isn't an integer", text.trim()))
+}
+
+// What ends up in a size byte buffer. Like MARS it takes the whole line and
+// keeps what fits, newline included if there's room, then a null. None when
+// the buffer can't even hold the null, in which case nothing is read
+pub fn read_string(input: &mut dyn BufRead, size: i32) -> Result<Option<Vec<u8>>, String> {
+    if size < 1 {
+        return Ok(None);
+    }
+
+    let mut line = read_line(input)?.unwrap_or_default();
+
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    }
+
+    line.truncate(size as usize - 1);
+
+    if line.len() < size as usize - 1 {
+        line.push(b'\n');
+    }
+
+    line.push(0);
+    Ok(Some(line))
+}
+
+pub fn read_character(input: &mut dyn BufRead) -> Result<u8, String> {
+    let buffer = input
+        .fill_buf()
+        .map_err(|e| format!("Failed to read input: {}", e))?;
+
+    let byte = *buffer
+        .first()
+        .ok_or("Tried to read a character past the end of input")?;
+
+    input.consume(1);
+    Ok(byte)
+}
+
+// One line including its newline, None at the end of input
+fn read_line(input: &mut dyn BufRead) -> Result<Option<Vec<u8>>, String> {
+    let mut line = Vec::new();
+
+    let read = input
+        .read_until(b'\n', &mut line)
+        .map_err(|e| format!("Failed to read input: {}", e))?;
+
+    if read == 0 { Ok(None) } else { Ok(Some(line)) }
+}
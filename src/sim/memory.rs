@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+// Where MARS puts things, so addresses printed by a program match
+pub const TEXT_BASE: u32 = 0x0040_0000;
+pub const DATA_BASE: u32 = 0x1001_0000;
+pub const HEAP_BASE: u32 = 0x1004_0000;
+pub const STACK_TOP: u32 = 0x7fff_effc;
+
+// Way more than any ecc program needs, anything past these is runaway
+// recursion or an sbrk loop
+const STACK_SIZE: u32 = 16 * 1024 * 1024;
+const HEAP_SIZE: u32 = 256 * 1024 * 1024;
+
+const PAGE_BITS: u32 = 12;
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+
+// Byte addressable and little endian like MARS. Only the data segment,
+// the heap handed out so far and the stack can be touched, pages are
+// made (zeroed) the first time something lands in them
+pub struct Memory {
+    pages: HashMap<u32, Box<[u8; PAGE_SIZE]>>,
+
+    // Static data ends wherever the assembler stopped, the heap grows from HEAP_BASE
+    data_end: u32,
+    heap_end: u32,
+}
+
+impl Memory {
+    pub fn new(data: &[u8]) -> Self {
+        let mut memory = Memory {
+            pages: HashMap::new(),
+            data_end: DATA_BASE + data.len() as u32,
+            heap_end: HEAP_BASE,
+        };
+
+        for (i, byte) in data.iter().enumerate() {
+            memory.set_byte(DATA_BASE + i as u32, *byte);
+        }
+
+        memory
+    }
+
+    // Hands back the old end of the heap like syscall 9. MARS keeps
+    // the heap word aligned so the next sbrk is too
+    pub fn sbrk(&mut self, bytes: i32) -> Result<u32, String> {
+        if bytes < 0 {
+            return Err(format!("sbrk can't allocate a negative amount ({})", bytes));
+        }
+
+        let start = self.heap_end;
+        let end = start as u64 + (bytes as u64).next_multiple_of(4);
+
+        if end > (HEAP_BASE + HEAP_SIZE) as u64 {
+            return Err(format!("Out of heap memory allocating {} bytes", bytes));
+        }
+
+        self.heap_end = end as u32;
+        Ok(start)
+    }
+
+    pub fn load(&self, address: u32, size: u32) -> Result<u32, String> {
+        self.check(address, size)?;
+
+        let mut value = 0;
+
+        for i in (0..size).rev() {
+            value = (value << 8) | self.byte(address + i) as u32;
+        }
+
+        Ok(value)
+    }
+
+    pub fn store(&mut self, address: u32, size: u32, value: u32) -> Result<(), String> {
+        self.check(address, size)?;
+
+        for i in 0..size {
+            self.set_byte(address + i, (value >> (i * 8)) as u8);
+        }
+
+        Ok(())
+    }
+
+    // The bytes of a null terminated string, not including the null
+    pub fn load_string(&self, address: u32) -> Result<Vec<u8>, String> {
+        let mut bytes = Vec::new();
+        let mut address = address;
+
+        loop {
+            let byte = self.load(address, 1)? as u8;
+
+            if byte == 0 {
+                return Ok(bytes);
+            }
+
+            bytes.push(byte);
+            address = address.wrapping_add(1);
+        }
+    }
+
+    fn check(&self, address: u32, size: u32) -> Result<(), String> {
+        if !address.is_multiple_of(size) {
+            return Err(format!(
+                "Address 0x{:08x} isn't aligned to a {} byte boundary",
+                address, size
+            ));
+        }
+
+        let end = address as u64 + size as u64;
+        let in_data = address >= DATA_BASE && end <= self.data_end as u64;
+        let in_heap = address >= HEAP_BASE && end <= self.heap_end as u64;
+        let in_stack = address >= STACK_TOP - STACK_SIZE && end <= STACK_TOP as u64 + 4;
+
+        if in_data || in_heap || in_stack {
+            Ok(())
+        } else if address < STACK_TOP - STACK_SIZE && address > STACK_TOP - 2 * STACK_SIZE {
+            Err(format!("Stack overflow at address 0x{:08x}", address))
+        } else {
+            Err(format!("Bad address 0x{:08x}", address))
+        }
+    }
+
+    fn byte(&self, address: u32) -> u8 {
+        match self.pages.get(&(address >> PAGE_BITS)) {
+            Some(page) => page[address as usize & (PAGE_SIZE - 1)],
+            None => 0,
+        }
+    }
+
+    fn set_byte(&mut self, address: u32, byte: u8) {
+        let page = self
+            .pages
+            .entry(address >> PAGE_BITS)
+            .or_insert_with(|| Box::new([0; PAGE_SIZE]));
+
+        page[address as usize & (PAGE_SIZE - 1)] = byte;
+    }
+}
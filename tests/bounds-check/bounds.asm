@@ -0,0 +1,151 @@
+# Assembly generated by ecc (Evil C Compiler)
+
+.data
+	.align 2
+	global_squares: .space 20
+	str_0: .asciiz "\n"
+	str_1: .asciiz "\nnot reached\n"
+	_bounds_message: .asciiz "Array index out of bounds on line "
+
+.text
+.globl _start
+
+_start:
+        jal     main
+        li      $v0, 10
+        syscall
+
+main:
+        subi    $sp, $sp, 28
+        sw      $ra, 0($sp)
+
+        sw      $zero, 4($sp)
+
+        j       for_0_end
+
+for_0_body:
+        lw      $t0, 4($sp)
+        lw      $t1, 4($sp)
+        mulo    $t0, $t0, $t1
+        la      $t1, global_squares
+        lw      $t2, 4($sp)
+        li      $t9, 5
+        sltu    $t9, $t2, $t9
+        bne     $t9, $zero, bounds_0_ok
+
+        li      $a0, 9
+        j       _bounds_error
+
+bounds_0_ok:
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        sw      $t0, 0($t1)
+
+        lw      $t0, 4($sp)
+        addi    $t0, $t0, 1
+        sw      $t0, 4($sp)
+
+for_0_end:
+        lw      $t0, 4($sp)
+        li      $t9, 5
+        blt     $t0, $t9, for_0_body
+
+        li      $t8, 4
+        sw      $t8, 12($sp)
+
+        li      $t8, 5
+        sw      $t8, 16($sp)
+
+        li      $t8, 6
+        sw      $t8, 20($sp)
+
+        addi    $t0, $sp, 12
+        sw      $t0, 8($sp)
+
+        li      $v0, 5
+        syscall
+        move    $t0, $v0
+        sw      $t0, 24($sp)
+
+        la      $t0, global_squares
+        lw      $t1, 24($sp)
+        li      $t9, 5
+        sltu    $t9, $t1, $t9
+        bne     $t9, $zero, bounds_1_ok
+
+        li      $a0, 15
+        j       _bounds_error
+
+bounds_1_ok:
+        sll     $t1, $t1, 2
+        add     $t0, $t0, $t1
+        lw      $t0, 0($t0)
+        lw      $t1, 8($sp)
+        lw      $t2, 24($sp)
+        subi    $t2, $t2, 2
+        li      $t9, 3
+        sltu    $t9, $t2, $t9
+        bne     $t9, $zero, bounds_2_ok
+
+        li      $a0, 15
+        j       _bounds_error
+
+bounds_2_ok:
+        sll     $t2, $t2, 2
+        add     $t1, $t1, $t2
+        lw      $t1, 0($t1)
+        add     $t0, $t0, $t1
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_0
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        li      $v0, 5
+        syscall
+        move    $t0, $v0
+        sw      $t0, 24($sp)
+
+        la      $t0, global_squares
+        lw      $t1, 24($sp)
+        li      $t9, 5
+        sltu    $t9, $t1, $t9
+        bne     $t9, $zero, bounds_3_ok
+
+        li      $a0, 19
+        j       _bounds_error
+
+bounds_3_ok:
+        sll     $t1, $t1, 2
+        add     $t0, $t0, $t1
+        lw      $t0, 0($t0)
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+
+        la      $t0, str_1
+        move    $a0, $t0
+        li      $v0, 4
+        syscall
+
+        lw      $ra, 0($sp)
+        addi    $sp, $sp, 28
+        jr      $ra
+
+_bounds_error:
+        move    $t0, $a0
+        la      $a0, _bounds_message
+        li      $v0, 4
+        syscall
+        move    $a0, $t0
+        li      $v0, 1
+        syscall
+        li      $a0, 10
+        li      $v0, 11
+        syscall
+        li      $a0, 1
+        li      $v0, 17
+        syscall
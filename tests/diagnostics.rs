@@ -0,0 +1,37 @@
+// How errors come out once rendered, without colour
+use ecc::compiler::Compiler;
+use ecc::diagnostic::Diagnostic;
+
+fn render(source: &str) -> String {
+    let errors = Compiler::new(source, "test.ec")
+        .get_checked_ast()
+        .expect_err("expected the program to be rejected");
+
+    Diagnostic::from(&errors[0]).render(source, false)
+}
+
+#[test]
+fn caret_lines_up_under_tab_indented_code() {
+    let rendered = render("void main() {\n\tint32 x = y;\n}\n");
+
+    let lines: Vec<&str> = rendered.lines().collect();
+    let code = lines.iter().find(|line| line.starts_with("2 |")).unwrap();
+    let caret = lines.iter().find(|line| line.contains('^')).unwrap();
+
+    assert!(!code.contains('\t'), "tab wasn't expanded: {:?}", code);
+    assert_eq!(code.find('y'), caret.find('^'), "\n{}", rendered);
+}
+
+#[test]
+fn decimal_literal_past_int32_points_at_the_number() {
+    let rendered = render("void main() {\n    iprint(4294967295);\n}\n");
+
+    assert!(rendered.contains("doesn't fit in an int32"), "{}", rendered);
+
+    let lines: Vec<&str> = rendered.lines().collect();
+    let code = lines.iter().find(|line| line.starts_with("2 |")).unwrap();
+    let caret = lines.iter().find(|line| line.contains('^')).unwrap();
+
+    assert_eq!(code.find('4'), caret.find('^'), "\n{}", rendered);
+    assert_eq!(caret.matches('^').count(), "4294967295".len(), "\n{}", rendered);
+}
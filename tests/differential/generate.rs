@@ -0,0 +1,980 @@
+// Random programs that pass the semantic pass and never do anything the
+// compiled code and the interpreter are allowed to disagree on. Indexes are
+// wrapped into range, pointers only ever point at something that outlives
+// them, nothing recurses and every loop runs a handful of times. Arithmetic
+// can still overflow or divide by zero, both sides trap on that the same way
+use ecc::rng::Rng;
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    // Printed as is, literals, variables, &x, *p, s->a and the like
+    Atom(String),
+    Unary(&'static str, Box<Expr>),
+    Binary(Box<Expr>, &'static str, Box<Expr>),
+    Call(String, Vec<Expr>),
+
+    // array[index] with index wrapped into 0..length however it works out
+    Index {
+        array: String,
+        length: usize,
+        index: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum Stmt {
+    // Nothing worth shrinking in it
+    Line(String),
+
+    // before, the expression, then after, like `int32 v0 = ` 1 `;`
+    Simple {
+        before: String,
+        expr: Expr,
+        after: String,
+    },
+
+    Assign {
+        target: Expr,
+        value: Expr,
+    },
+
+    If {
+        condition: Expr,
+        body: Vec<Stmt>,
+        else_body: Vec<Stmt>,
+    },
+
+    // setup goes before the loop and step at the end of every time round,
+    // neither can be shrunk away without the loop running forever
+    Loop {
+        setup: Option<String>,
+        header: String,
+        body: Vec<Stmt>,
+        step: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub signature: String,
+    pub body: Vec<Stmt>,
+    pub result: Option<Expr>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub structs: Vec<String>,
+    pub globals: Vec<String>,
+    pub functions: Vec<Function>,
+
+    // What both sides get as stdin, mostly numbers for iread
+    pub input: String,
+}
+
+impl Program {
+    pub fn source(&self) -> String {
+        let mut out = String::new();
+
+        for item in self.structs.iter().chain(&self.globals) {
+            out.push_str(item);
+            out.push('\n');
+        }
+
+        for function in &self.functions {
+            out.push('\n');
+            out.push_str(&function.signature);
+            out.push_str(" {\n");
+            write_block(&function.body, 1, &mut out);
+
+            if let Some(result) = &function.result {
+                out.push_str(&format!("    return {};\n", result));
+            }
+
+            out.push_str("}\n");
+        }
+
+        out
+    }
+}
+
+fn write_block(body: &[Stmt], depth: usize, out: &mut String) {
+    let indent = "    ".repeat(depth);
+
+    for stmt in body {
+        match stmt {
+            Stmt::Line(line) => out.push_str(&format!("{}{}\n", indent, line)),
+
+            Stmt::Simple {
+                before,
+                expr,
+                after,
+            } => out.push_str(&format!("{}{}{}{}\n", indent, before, expr, after)),
+
+            Stmt::Assign { target, value } => {
+                out.push_str(&format!("{}{} = {};\n", indent, target, value))
+            }
+
+            Stmt::If {
+                condition,
+                body,
+                else_body,
+            } => {
+                out.push_str(&format!("{}if ({}) {{\n", indent, condition));
+                write_block(body, depth + 1, out);
+
+                if else_body.is_empty() {
+                    out.push_str(&format!("{}}}\n", indent));
+                } else {
+                    out.push_str(&format!("{}}} else {{\n", indent));
+                    write_block(else_body, depth + 1, out);
+                    out.push_str(&format!("{}}}\n", indent));
+                }
+            }
+
+            Stmt::Loop {
+                setup,
+                header,
+                body,
+                step,
+            } => {
+                if let Some(setup) = setup {
+                    out.push_str(&format!("{}{}\n", indent, setup));
+                }
+
+                out.push_str(&format!("{}{} {{\n", indent, header));
+                write_block(body, depth + 1, out);
+
+                if let Some(step) = step {
+                    out.push_str(&format!("{}    {}\n", indent, step));
+                }
+
+                out.push_str(&format!("{}}}\n", indent));
+            }
+        }
+    }
+}
+
+impl Expr {
+    // What the semantic pass folds it to, if it's all literals
+    fn value(&self) -> Option<i32> {
+        match self {
+            Expr::Atom(text) => text.parse().ok(),
+            Expr::Unary("-", operand) => Some(operand.value()?.wrapping_neg()),
+            Expr::Unary("~", operand) => Some(!operand.value()?),
+
+            Expr::Binary(left, operator, right) => {
+                let (l, r) = (left.value()?, right.value()?);
+
+                match *operator {
+                    "+" => Some(l.wrapping_add(r)),
+                    "-" => Some(l.wrapping_sub(r)),
+                    "*" => Some(l.wrapping_mul(r)),
+                    "/" if r != 0 => Some(l.wrapping_div(r)),
+                    "%" if r != 0 => Some(l.wrapping_rem(r)),
+                    "&" => Some(l & r),
+                    "|" => Some(l | r),
+                    "^" => Some(l ^ r),
+                    "<<" => Some(l.wrapping_shl(r as u32)),
+                    ">>" => Some(l.wrapping_shr(r as u32)),
+                    _ => None,
+                }
+            }
+
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Expr::Atom(text) => write!(f, "{}", text),
+            Expr::Unary(operator, operand) => write!(f, "{}({})", operator, operand),
+            Expr::Binary(left, operator, right) => write!(f, "({} {} {})", left, operator, right),
+
+            Expr::Call(name, arguments) => {
+                let arguments: Vec<String> = arguments.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}({})", name, arguments.join(", "))
+            }
+
+            // % can come out negative, adding length first fixes that without overflowing
+            Expr::Index {
+                array,
+                length,
+                index,
+            } => write!(
+                f,
+                "{}[({} % {} + {}) % {}]",
+                array, index, length, length, length
+            ),
+        }
+    }
+}
+
+// S1 holds an S0 by value, so field accesses nest and the layouts matter
+const STRUCTS: [&str; 2] = [
+    "struct S0 {\n    int32 a;\n    char b;\n    int32 c[3];\n}\n",
+    "struct S1 {\n    S0 inner;\n    int32 d;\n    char e;\n}\n",
+];
+
+// Bytes alloc needs for each, every field is rounded up to a word
+const STRUCT_SIZES: [usize; 2] = [20, 28];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Int,
+    Char,
+    Bool,
+
+    // T name[n], laid out in place
+    Array(usize),
+
+    // int32& pointing at n ints, from {..}[n], an array or an array parameter
+    List(usize),
+
+    // int32& pointing at a single int
+    Pointer,
+
+    // Which struct, and whether the variable is a pointer to it
+    Struct(usize, bool),
+}
+
+#[derive(Debug, Clone)]
+struct Var {
+    name: String,
+    kind: Kind,
+
+    // Loop counters can be read but never written
+    mutable: bool,
+
+    // How many scopes deep it was declared, a pointer can only be pointed
+    // at something declared at its own depth or shallower
+    depth: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Signature {
+    name: String,
+    params: Vec<Kind>,
+    returns: Option<Kind>,
+}
+
+pub struct Generator {
+    rng: Rng,
+    names: usize,
+    scopes: Vec<Vec<Var>>,
+    globals: Vec<Var>,
+    functions: Vec<Signature>,
+    loops: usize,
+}
+
+const MAX_DEPTH: usize = 3;
+const MAX_LOOPS: usize = 2;
+
+impl Generator {
+    pub fn new(seed: u64) -> Self {
+        Generator {
+            rng: Rng::new(seed),
+            names: 0,
+            scopes: Vec::new(),
+            globals: Vec::new(),
+            functions: Vec::new(),
+            loops: 0,
+        }
+    }
+
+    pub fn program(&mut self) -> Program {
+        let globals = (0..self.below(5)).map(|_| self.global()).collect();
+
+        let mut functions = Vec::new();
+
+        for _ in 0..self.below(4) {
+            functions.push(self.function());
+        }
+
+        self.scopes = vec![Vec::new()];
+        let body = self.block(0, 12);
+
+        functions.push(Function {
+            signature: "int32 main()".to_string(),
+            body,
+            result: Some(Expr::Atom("0".to_string())),
+        });
+
+        Program {
+            structs: STRUCTS.iter().map(|s| s.to_string()).collect(),
+            globals,
+            functions,
+            input: self.input(),
+        }
+    }
+
+    // Plenty of lines so a program rarely reads past the end, with the odd
+    // one iread can't parse
+    fn input(&mut self) -> String {
+        let mut input = String::new();
+
+        for _ in 0..40 + self.below(40) {
+            let line = match self.below(12) {
+                0 => ["", "abc", "hi there", "-", " 7 "][self.below(5)].to_string(),
+                _ => self.int_literal(),
+            };
+
+            input.push_str(&line);
+            input.push('\n');
+        }
+
+        input
+    }
+
+    fn global(&mut self) -> String {
+        let name = self.name("g");
+
+        let (kind, line) = match self.below(5) {
+            0 => (
+                Kind::Char,
+                format!("char {} = {};", name, self.char_literal()),
+            ),
+            1 => {
+                let n = 1 + self.below(5);
+                (Kind::Array(n), format!("int32 {}[{}];", name, n))
+            }
+            2 => {
+                let n = 1 + self.below(5);
+                let elements: Vec<String> = (0..n).map(|_| self.int_literal()).collect();
+                let line = format!("int32& {} = {{{}}}[{}];", name, elements.join(", "), n);
+                (Kind::List(n), line)
+            }
+            3 => (Kind::Bool, format!("bool {} = {};", name, self.chance(2))),
+            _ => (
+                Kind::Int,
+                format!("int32 {} = {};", name, self.int_literal()),
+            ),
+        };
+
+        self.globals.push(Var {
+            name,
+            kind,
+            mutable: true,
+            depth: 0,
+        });
+
+        line
+    }
+
+    fn function(&mut self) -> Function {
+        let name = self.name("f");
+        let mut params = Vec::new();
+        let mut declarations = Vec::new();
+
+        self.scopes = vec![Vec::new()];
+
+        // Up to 8 so some go on the stack
+        for _ in 0..self.below(9) {
+            let param = self.name("p");
+
+            let (kind, declaration) = match self.below(8) {
+                0 => (Kind::Char, format!("char {}", param)),
+                1 => (Kind::Pointer, format!("int32& {}", param)),
+                2 => {
+                    let n = 1 + self.below(5);
+                    (Kind::List(n), format!("int32 {}[]", param))
+                }
+                3 => {
+                    let which = self.below(2);
+                    (Kind::Struct(which, true), format!("S{}& {}", which, param))
+                }
+                _ => (Kind::Int, format!("int32 {}", param)),
+            };
+
+            params.push(kind);
+            declarations.push(declaration);
+            self.declare(param, kind, true);
+        }
+
+        let returns = match self.below(3) {
+            0 => None,
+            1 => Some(Kind::Char),
+            _ => Some(Kind::Int),
+        };
+
+        let body = self.block(0, 6);
+        let result = returns.map(|_| self.int_expr(0));
+
+        let return_type = match returns {
+            None => "void",
+            Some(Kind::Char) => "char",
+            _ => "int32",
+        };
+
+        // Only added now so it can't call itself
+        self.functions.push(Signature {
+            name: name.clone(),
+            params,
+            returns,
+        });
+
+        Function {
+            signature: format!("{} {}({})", return_type, name, declarations.join(", ")),
+            body,
+            result,
+        }
+    }
+
+    // Somewhere between one and most statements
+    fn block(&mut self, depth: usize, most: usize) -> Vec<Stmt> {
+        let count = 1 + self.below(most);
+        self.scopes.push(Vec::new());
+
+        let body = (0..count).map(|_| self.statement(depth)).collect();
+
+        self.scopes.pop();
+        body
+    }
+
+    fn statement(&mut self, depth: usize) -> Stmt {
+        match self.below(20) {
+            0..=4 => self.declaration(),
+            5..=8 => self.assignment(),
+
+            9 if depth < MAX_DEPTH => {
+                let condition = self.bool_expr(0);
+                let body = self.block(depth + 1, 3);
+
+                let else_body = if self.chance(2) {
+                    self.block(depth + 1, 3)
+                } else {
+                    Vec::new()
+                };
+
+                Stmt::If {
+                    condition,
+                    body,
+                    else_body,
+                }
+            }
+
+            10 if depth < MAX_DEPTH && self.loops < MAX_LOOPS => self.loop_statement(depth),
+
+            11 => {
+                let callable: Vec<Signature> = self
+                    .functions
+                    .iter()
+                    .filter(|f| f.returns.is_none())
+                    .cloned()
+                    .collect();
+
+                match self.pick(&callable) {
+                    Some(function) => match self.call(&function, 1) {
+                        Some(call) => Stmt::Simple {
+                            before: String::new(),
+                            expr: call,
+                            after: ";".to_string(),
+                        },
+                        None => self.print(),
+                    },
+                    None => self.print(),
+                }
+            }
+
+            12 => {
+                let counters: Vec<Var> = self
+                    .visible()
+                    .into_iter()
+                    .filter(|v| v.kind == Kind::Int && v.mutable)
+                    .collect();
+
+                match self.pick(&counters) {
+                    Some(var) => {
+                        let step = ["++", "--", "<<", ">>"][self.below(4)];
+                        Stmt::Line(format!("{}{};", var.name, step))
+                    }
+                    None => self.print(),
+                }
+            }
+
+            _ => self.print(),
+        }
+    }
+
+    fn print(&mut self) -> Stmt {
+        match self.below(7) {
+            0 => {
+                let text = ["a", "hi", "\\n", "x y", "hi"][self.below(5)];
+                Stmt::Line(format!("sprint(\"{}\");", text))
+            }
+            1 => Stmt::Line(format!("sprint(sread({}));", 1 + self.below(8))),
+            2 => Stmt::Simple {
+                before: "cprint(".to_string(),
+                expr: self.int_expr(0),
+                after: ");".to_string(),
+            },
+            _ => Stmt::Simple {
+                before: "iprint(".to_string(),
+                expr: self.int_expr(0),
+                after: "); cprint(' ');".to_string(),
+            },
+        }
+    }
+
+    fn declaration(&mut self) -> Stmt {
+        let name = self.name("v");
+
+        let ints: Vec<Var> = self
+            .visible()
+            .into_iter()
+            .filter(|v| v.kind == Kind::Int && v.mutable)
+            .collect();
+
+        let arrays: Vec<Var> = self
+            .visible()
+            .into_iter()
+            .filter(|v| matches!(v.kind, Kind::Array(_)))
+            .collect();
+
+        let (kind, stmt) = match self.below(9) {
+            0 => {
+                let stmt = Stmt::Simple {
+                    before: format!("char {} = ", name),
+                    expr: self.int_expr(0),
+                    after: ";".to_string(),
+                };
+                (Kind::Char, stmt)
+            }
+
+            1 => {
+                let stmt = Stmt::Simple {
+                    before: format!("bool {} = ", name),
+                    expr: self.bool_expr(0),
+                    after: ";".to_string(),
+                };
+                (Kind::Bool, stmt)
+            }
+
+            2 => {
+                let n = 1 + self.below(6);
+                (
+                    Kind::Array(n),
+                    Stmt::Line(format!("int32 {}[{}];", name, n)),
+                )
+            }
+
+            3 => {
+                let n = 1 + self.below(5);
+                let elements: Vec<String> = (0..n).map(|_| self.int_expr(1).to_string()).collect();
+                let line = format!("int32& {} = {{{}}}[{}];", name, elements.join(", "), n);
+                (Kind::List(n), Stmt::Line(line))
+            }
+
+            4 if !ints.is_empty() => {
+                let target = self.pick(&ints).unwrap();
+                (
+                    Kind::Pointer,
+                    Stmt::Line(format!("int32& {} = &{};", name, target.name)),
+                )
+            }
+
+            5 if !arrays.is_empty() => {
+                let array = self.pick(&arrays).unwrap();
+                let Kind::Array(n) = array.kind else {
+                    unreachable!()
+                };
+                (
+                    Kind::List(n),
+                    Stmt::Line(format!("int32& {} = {};", name, array.name)),
+                )
+            }
+
+            6 => {
+                let which = self.below(2);
+
+                if self.chance(2) {
+                    (
+                        Kind::Struct(which, false),
+                        Stmt::Line(format!("S{} {};", which, name)),
+                    )
+                } else {
+                    let line = format!("S{}& {} = alloc({});", which, name, STRUCT_SIZES[which]);
+                    (Kind::Struct(which, true), Stmt::Line(line))
+                }
+            }
+
+            7 => (Kind::Int, Stmt::Line(format!("int32 {};", name))),
+
+            _ => {
+                let stmt = Stmt::Simple {
+                    before: format!("int32 {} = ", name),
+                    expr: self.int_expr(0),
+                    after: ";".to_string(),
+                };
+                (Kind::Int, stmt)
+            }
+        };
+
+        self.declare(name, kind, true);
+        stmt
+    }
+
+    fn assignment(&mut self) -> Stmt {
+        let vars = self.visible();
+
+        // Pointers can be moved to anything that lives at least as long
+        let pointers: Vec<Var> = vars
+            .iter()
+            .filter(|v| v.kind == Kind::Pointer)
+            .cloned()
+            .collect();
+
+        if let Some(pointer) = self.pick(&pointers)
+            && self.chance(4)
+        {
+            let targets: Vec<Var> = vars
+                .iter()
+                .filter(|v| v.kind == Kind::Int && v.mutable && v.depth <= pointer.depth)
+                .cloned()
+                .collect();
+
+            if let Some(target) = self.pick(&targets) {
+                return Stmt::Line(format!("{} = &{};", pointer.name, target.name));
+            }
+        }
+
+        let bools: Vec<Var> = vars
+            .iter()
+            .filter(|v| v.kind == Kind::Bool)
+            .cloned()
+            .collect();
+
+        if let Some(var) = self.pick(&bools)
+            && self.chance(5)
+        {
+            return Stmt::Assign {
+                target: Expr::Atom(var.name),
+                value: self.bool_expr(0),
+            };
+        }
+
+        match self.int_place(true) {
+            Some(target) => Stmt::Assign {
+                target,
+                value: self.int_expr(0),
+            },
+            None => self.declaration(),
+        }
+    }
+
+    fn loop_statement(&mut self, depth: usize) -> Stmt {
+        let times = self.below(5);
+        self.loops += 1;
+        self.scopes.push(Vec::new());
+
+        let stmt = if self.chance(2) {
+            let counter = self.name("i");
+            self.declare(counter.clone(), Kind::Int, false);
+
+            let body = self.block(depth + 1, 4);
+
+            Stmt::Loop {
+                setup: None,
+                header: format!(
+                    "for (int32 {} = 0; {} < {}; {}++)",
+                    counter, counter, times, counter
+                ),
+                body,
+                step: None,
+            }
+        } else {
+            let counter = self.name("w");
+            self.declare(counter.clone(), Kind::Int, false);
+
+            let body = self.block(depth + 1, 4);
+
+            Stmt::Loop {
+                setup: Some(format!("int32 {} = 0;", counter)),
+                header: format!("while ({} < {})", counter, times),
+                body,
+                step: Some(format!("{}++;", counter)),
+            }
+        };
+
+        // The while counter really lives in the enclosing scope, but
+        // nothing after the loop gets to use it either way
+        self.scopes.pop();
+        self.loops -= 1;
+        stmt
+    }
+
+    fn int_expr(&mut self, depth: usize) -> Expr {
+        if depth >= MAX_DEPTH {
+            return self.int_leaf();
+        }
+
+        match self.below(12) {
+            0..=3 => self.int_leaf(),
+
+            4 => {
+                let operator = ["-", "~"][self.below(2)];
+                Expr::Unary(operator, Box::new(self.int_expr(depth + 1)))
+            }
+
+            5 => {
+                let callable: Vec<Signature> = self
+                    .functions
+                    .iter()
+                    .filter(|f| f.returns.is_some())
+                    .cloned()
+                    .collect();
+
+                match self.pick(&callable) {
+                    Some(function) => self
+                        .call(&function, depth + 1)
+                        .unwrap_or_else(|| self.int_leaf()),
+                    None => self.int_leaf(),
+                }
+            }
+
+            6 => Expr::Call(
+                "irandrange".to_string(),
+                vec![
+                    Expr::Atom("0".to_string()),
+                    Expr::Atom((1 + self.below(10)).to_string()),
+                ],
+            ),
+
+            _ => {
+                let operator = [
+                    "+", "-", "*", "/", "%", "&", "|", "^", "<<", ">>", "+", "-", "*",
+                ][self.below(13)];
+
+                let left = self.int_expr(depth + 1);
+                let mut right = self.int_expr(depth + 1);
+
+                // The semantic pass refuses to divide by anything that folds to 0
+                if matches!(operator, "/" | "%") && right.value() == Some(0) {
+                    right = Expr::Atom("1".to_string());
+                }
+
+                Expr::Binary(Box::new(left), operator, Box::new(right))
+            }
+        }
+    }
+
+    fn int_leaf(&mut self) -> Expr {
+        if self.chance(12) {
+            let read = ["iread", "cread"][self.below(2)];
+            return Expr::Call(read.to_string(), Vec::new());
+        }
+
+        if self.chance(8) {
+            return Expr::Atom(self.char_literal());
+        }
+
+        if self.chance(3) {
+            return Expr::Atom(self.int_literal());
+        }
+
+        match self.int_place(false) {
+            Some(place) => place,
+            None => Expr::Atom(self.int_literal()),
+        }
+    }
+
+    fn bool_expr(&mut self, depth: usize) -> Expr {
+        let bools: Vec<Var> = self
+            .visible()
+            .into_iter()
+            .filter(|v| v.kind == Kind::Bool)
+            .collect();
+
+        match self.below(8) {
+            0 => Expr::Atom(["true", "false"][self.below(2)].to_string()),
+
+            1 if !bools.is_empty() => Expr::Atom(self.pick(&bools).unwrap().name),
+
+            2 if depth < MAX_DEPTH => Expr::Unary("!", Box::new(self.bool_expr(depth + 1))),
+
+            3 | 4 if depth < MAX_DEPTH => {
+                let operator = ["&&", "||"][self.below(2)];
+
+                Expr::Binary(
+                    Box::new(self.bool_expr(depth + 1)),
+                    operator,
+                    Box::new(self.bool_expr(depth + 1)),
+                )
+            }
+
+            _ => {
+                let operator = ["<", ">", "<=", ">=", "==", "!="][self.below(6)];
+
+                Expr::Binary(
+                    Box::new(self.int_expr(depth + 1)),
+                    operator,
+                    Box::new(self.int_expr(depth + 1)),
+                )
+            }
+        }
+    }
+
+    // Something holding an int or a char, to read or (when writable is
+    // set) assign to. None when nothing in scope fits
+    fn int_place(&mut self, writable: bool) -> Option<Expr> {
+        let vars: Vec<Var> = self
+            .visible()
+            .into_iter()
+            .filter(|v| v.kind != Kind::Bool && (v.mutable || !writable))
+            .collect();
+
+        let var = self.pick(&vars)?;
+
+        let place = match var.kind {
+            Kind::Int | Kind::Char => Expr::Atom(var.name),
+            Kind::Pointer => Expr::Atom(format!("*{}", var.name)),
+            Kind::Array(n) | Kind::List(n) => self.index(var.name, n),
+
+            Kind::Struct(which, pointer) => {
+                let access = if pointer { "->" } else { "." };
+
+                let path = if which == 1 && self.chance(2) {
+                    format!("{}{}inner.", var.name, access)
+                } else if which == 1 {
+                    let field = ["d", "e"][self.below(2)];
+                    return Some(Expr::Atom(format!("{}{}{}", var.name, access, field)));
+                } else {
+                    format!("{}{}", var.name, access)
+                };
+
+                match self.below(3) {
+                    0 => Expr::Atom(format!("{}a", path)),
+                    1 => Expr::Atom(format!("{}b", path)),
+                    _ => self.index(format!("{}c", path), 3),
+                }
+            }
+
+            Kind::Bool => unreachable!(),
+        };
+
+        Some(place)
+    }
+
+    fn index(&mut self, array: String, length: usize) -> Expr {
+        let index = if self.chance(2) {
+            Expr::Atom(self.below(length).to_string())
+        } else {
+            self.int_expr(MAX_DEPTH - 1)
+        };
+
+        Expr::Index {
+            array,
+            length,
+            index: Box::new(index),
+        }
+    }
+
+    // None if there's nothing in scope to pass for one of the parameters
+    // Arguments are depth deep, so calls in them can't nest forever
+    fn call(&mut self, function: &Signature, depth: usize) -> Option<Expr> {
+        let vars = self.visible();
+        let mut arguments = Vec::new();
+
+        for param in &function.params {
+            let argument = match param {
+                Kind::Int | Kind::Char => self.int_expr(depth),
+
+                Kind::Pointer => {
+                    let targets: Vec<&Var> = vars
+                        .iter()
+                        .filter(|v| v.kind == Kind::Int && v.mutable)
+                        .collect();
+
+                    Expr::Atom(format!("&{}", self.pick(&targets)?.name))
+                }
+
+                // Anything at least as long as the function expects
+                Kind::List(n) => {
+                    let arrays: Vec<&Var> = vars
+                        .iter()
+                        .filter(|v| matches!(v.kind, Kind::Array(m) | Kind::List(m) if m >= *n))
+                        .collect();
+
+                    Expr::Atom(self.pick(&arrays)?.name.clone())
+                }
+
+                Kind::Struct(which, _) => {
+                    let structs: Vec<&Var> = vars
+                        .iter()
+                        .filter(|v| matches!(v.kind, Kind::Struct(w, _) if w == *which))
+                        .collect();
+
+                    let var = self.pick(&structs)?;
+
+                    match var.kind {
+                        Kind::Struct(_, true) => Expr::Atom(var.name.clone()),
+                        _ => Expr::Atom(format!("&{}", var.name)),
+                    }
+                }
+
+                Kind::Bool | Kind::Array(_) => return None,
+            };
+
+            arguments.push(argument);
+        }
+
+        Some(Expr::Call(function.name.clone(), arguments))
+    }
+
+    fn declare(&mut self, name: String, kind: Kind, mutable: bool) {
+        let depth = self.scopes.len();
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Var {
+                name,
+                kind,
+                mutable,
+                depth,
+            });
+        }
+    }
+
+    fn visible(&self) -> Vec<Var> {
+        self.globals
+            .iter()
+            .chain(self.scopes.iter().flatten())
+            .cloned()
+            .collect()
+    }
+
+    fn int_literal(&mut self) -> String {
+        const EDGES: [i32; 10] = [0, 1, -1, 127, 128, 255, 256, -129, 65535, 2147483647];
+
+        match self.below(8) {
+            0 => EDGES[self.below(EDGES.len())].to_string(),
+            _ => (self.below(41) as i32 - 20).to_string(),
+        }
+    }
+
+    fn char_literal(&mut self) -> String {
+        ["'a'", "'Z'", "'0'", "' '", "'\\n'", "'~'"][self.below(6)].to_string()
+    }
+
+    fn name(&mut self, prefix: &str) -> String {
+        self.names += 1;
+        format!("{}{}", prefix, self.names)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.rng.below(n as i32) as usize
+    }
+
+    // True one time in n
+    fn chance(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    fn pick<T: Clone>(&mut self, items: &[T]) -> Option<T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(items[self.below(items.len())].clone())
+        }
+    }
+}
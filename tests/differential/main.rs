@@ -0,0 +1,216 @@
+// Differential testing. Random programs are compiled and run in the
+// simulator, then run again in the interpreter with the same stdin, and the
+// two have to print the same thing and end the same way. A program where they don't is shrunk
+// down to something small enough to read before it's reported.
+//
+// About ten seconds in a debug build, so it only runs when asked for:
+//     cargo test --release --test differential -- --ignored
+// ECC_FUZZ_CASES says how many programs to try (2000 by default) and
+// ECC_FUZZ_SEED which seed to start from, a failure names the seed that
+// made it so it can be rerun on its own with ECC_FUZZ_CASES=1
+mod generate;
+mod shrink;
+
+use ecc::compiler::Compiler;
+use ecc::interp::Interpreter;
+use ecc::rng::Rng;
+use ecc::sim::Simulator;
+
+use generate::{Generator, Program};
+
+// For irandrange, both sides get the same numbers from it
+const SEED: u64 = 0;
+
+// Generated loops only go round a few times, but calls inside them add up.
+// Whichever side runs out first makes the program inconclusive rather than wrong
+const MAX_INSTRUCTIONS: u64 = 20_000_000;
+const MAX_STEPS: u64 = 2_000_000;
+
+#[derive(Debug)]
+enum Verdict {
+    Agree,
+
+    // The semantic pass turned it down, a generator bug or a shrink that went too far
+    Invalid(String),
+
+    // Passed the semantic pass but the backend still couldn't compile it.
+    // Hasn't happened since temps started spilling to the stack, but it's
+    // not a wrong answer so it's counted rather than failed
+    Rejected,
+
+    Differ(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Ending {
+    Exited(i32),
+    Crashed(Cause),
+    GaveUp,
+}
+
+// Why a program crashed, without the wording and numbers that differ
+// between the simulator and the interpreter. Both sides have to crash
+// for the same reason to agree
+#[derive(Debug, PartialEq)]
+enum Cause {
+    DivisionByZero,
+    Overflow,
+    StackOverflow,
+    BadAddress,
+    OutOfMemory,
+    OutOfBounds,
+
+    // Anything not sorted above has to match word for word
+    Other(String),
+}
+
+impl Cause {
+    fn of(message: &str) -> Self {
+        if message.starts_with("Division by zero") {
+            Cause::DivisionByZero
+        } else if message.starts_with("Arithmetic overflow") {
+            Cause::Overflow
+        } else if message.starts_with("Stack overflow") {
+            Cause::StackOverflow
+        } else if message.starts_with("Bad address") {
+            Cause::BadAddress
+        } else if message.starts_with("Out of heap memory") {
+            Cause::OutOfMemory
+        } else if message.contains("out of bounds") {
+            Cause::OutOfBounds
+        } else {
+            Cause::Other(message.to_string())
+        }
+    }
+}
+
+fn check(program: &Program) -> Verdict {
+    let source = program.source();
+    let mut compiler = Compiler::new(&source, "fuzz.ec");
+
+    let ast = match compiler.get_checked_ast() {
+        Ok(ast) => ast,
+        Err(errors) => return Verdict::Invalid(errors[0].to_string()),
+    };
+
+    let assembly = match compiler.get_assembly(false, false) {
+        Ok(assembly) => assembly,
+        Err(_) => return Verdict::Rejected,
+    };
+
+    let mut compiled_output = Vec::new();
+    let compiled = Simulator::new(&assembly, Rng::new(SEED)).and_then(|mut sim| {
+        sim.run(
+            &mut program.input.as_bytes(),
+            &mut compiled_output,
+            Some(MAX_INSTRUCTIONS),
+        )
+    });
+
+    let compiled = match compiled {
+        Ok(code) => Ending::Exited(code),
+        Err(e) if e.message.starts_with("Gave up") => Ending::GaveUp,
+        Err(e) => Ending::Crashed(Cause::of(&e.message)),
+    };
+
+    let mut interpreted_output = Vec::new();
+    let interpreted = Interpreter::new(
+        &ast,
+        Rng::new(SEED),
+        &mut program.input.as_bytes(),
+        &mut interpreted_output,
+    )
+    .run(Some(MAX_STEPS));
+
+    let interpreted = match interpreted {
+        Ok(code) => Ending::Exited(code),
+        Err(e) if e.message.starts_with("Gave up") => Ending::GaveUp,
+        Err(e) => Ending::Crashed(Cause::of(&e.message)),
+    };
+
+    if compiled == Ending::GaveUp || interpreted == Ending::GaveUp {
+        return Verdict::Agree;
+    }
+
+    if compiled == interpreted && compiled_output == interpreted_output {
+        return Verdict::Agree;
+    }
+
+    Verdict::Differ(format!(
+        "compiled:    {:?} after printing {:?}\ninterpreted: {:?} after printing {:?}",
+        compiled,
+        String::from_utf8_lossy(&compiled_output),
+        interpreted,
+        String::from_utf8_lossy(&interpreted_output)
+    ))
+}
+
+fn env_or(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+fn generate(seed: u64) -> (Program, Verdict) {
+    let program = Generator::new(seed).program();
+    let verdict = check(&program);
+
+    if let Verdict::Invalid(error) = &verdict {
+        panic!(
+            "seed {} made a program the semantic pass rejects: {}\n\n{}",
+            seed,
+            error,
+            program.source()
+        );
+    }
+
+    (program, verdict)
+}
+
+// Cheap enough to always run, keeps the generator honest as the language changes
+#[test]
+fn generated_programs_pass_the_semantic_pass() {
+    for seed in 0..50 {
+        generate(seed);
+    }
+}
+
+#[test]
+#[ignore]
+fn compiled_code_matches_interpreter() {
+    let cases = env_or("ECC_FUZZ_CASES", 2000);
+    let first = env_or("ECC_FUZZ_SEED", 0);
+
+    let mut rejected = 0;
+
+    for seed in first..first + cases {
+        let (program, verdict) = generate(seed);
+
+        let Verdict::Differ(_) = verdict else {
+            rejected += matches!(verdict, Verdict::Rejected) as u64;
+            continue;
+        };
+
+        let smallest = shrink::shrink(program, |candidate| {
+            matches!(check(candidate), Verdict::Differ(_))
+        });
+
+        let Verdict::Differ(details) = check(&smallest) else {
+            unreachable!()
+        };
+
+        panic!(
+            "seed {} found a program the backend gets wrong, shrunk to\n\n{}\nwith input\n{}\n{}",
+            seed,
+            smallest.source(),
+            smallest.input,
+            details
+        );
+    }
+
+    eprintln!(
+        "{} of {} programs were too much for the backend to compile",
+        rejected, cases
+    );
+}
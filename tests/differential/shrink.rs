@@ -0,0 +1,264 @@
+use crate::generate::{Expr, Program, Stmt};
+
+// Greedy. Tries every one step simplification in turn and keeps the first
+// that still fails, until none do. Plenty of candidates don't even pass the
+// semantic pass (dropping a declaration that's used later, say), fails is
+// expected to say no to those
+pub fn shrink(program: Program, fails: impl Fn(&Program) -> bool) -> Program {
+    let mut current = program;
+
+    'smaller: loop {
+        for candidate in candidates(&current) {
+            if fails(&candidate) {
+                current = candidate;
+                continue 'smaller;
+            }
+        }
+
+        return current;
+    }
+}
+
+fn candidates(program: &Program) -> Vec<Program> {
+    let mut out = Vec::new();
+
+    // Whole items first, they take the most with them. main is always last
+    for i in 0..program.functions.len().saturating_sub(1) {
+        let mut candidate = program.clone();
+        candidate.functions.remove(i);
+        out.push(candidate);
+    }
+
+    for i in 0..program.globals.len() {
+        let mut candidate = program.clone();
+        candidate.globals.remove(i);
+        out.push(candidate);
+    }
+
+    for i in 0..program.structs.len() {
+        let mut candidate = program.clone();
+        candidate.structs.remove(i);
+        out.push(candidate);
+    }
+
+    for (i, function) in program.functions.iter().enumerate() {
+        for body in block_variants(&function.body) {
+            let mut candidate = program.clone();
+            candidate.functions[i].body = body;
+            out.push(candidate);
+        }
+
+        for result in function.result.iter().flat_map(expr_variants) {
+            let mut candidate = program.clone();
+            candidate.functions[i].result = Some(result);
+            out.push(candidate);
+        }
+    }
+
+    out
+}
+
+fn block_variants(body: &[Stmt]) -> Vec<Vec<Stmt>> {
+    let mut out = Vec::new();
+
+    for i in 0..body.len() {
+        let mut without = body.to_vec();
+        without.remove(i);
+        out.push(without);
+    }
+
+    // An if or a loop replaced by what's inside it
+    for (i, stmt) in body.iter().enumerate() {
+        let insides = match stmt {
+            Stmt::If {
+                body, else_body, ..
+            } => vec![body.clone(), else_body.clone()],
+
+            Stmt::Loop { setup, body, .. } => {
+                let setup = setup.iter().map(|line| Stmt::Line(line.clone()));
+                vec![setup.chain(body.iter().cloned()).collect()]
+            }
+
+            _ => Vec::new(),
+        };
+
+        for inside in insides {
+            let mut spliced = body[..i].to_vec();
+            spliced.extend(inside);
+            spliced.extend_from_slice(&body[i + 1..]);
+            out.push(spliced);
+        }
+    }
+
+    for (i, stmt) in body.iter().enumerate() {
+        for variant in stmt_variants(stmt) {
+            let mut simpler = body.to_vec();
+            simpler[i] = variant;
+            out.push(simpler);
+        }
+    }
+
+    out
+}
+
+fn stmt_variants(stmt: &Stmt) -> Vec<Stmt> {
+    match stmt {
+        Stmt::Line(_) => Vec::new(),
+
+        Stmt::Simple {
+            before,
+            expr,
+            after,
+        } => expr_variants(expr)
+            .into_iter()
+            .map(|expr| Stmt::Simple {
+                before: before.clone(),
+                expr,
+                after: after.clone(),
+            })
+            .collect(),
+
+        // Only the index of a target can change, anything else would stop it being one
+        Stmt::Assign { target, value } => {
+            let mut out: Vec<Stmt> = expr_variants(value)
+                .into_iter()
+                .map(|value| Stmt::Assign {
+                    target: target.clone(),
+                    value,
+                })
+                .collect();
+
+            if let Expr::Index {
+                array,
+                length,
+                index,
+            } = target
+            {
+                for index in expr_variants(index) {
+                    out.push(Stmt::Assign {
+                        target: Expr::Index {
+                            array: array.clone(),
+                            length: *length,
+                            index: Box::new(index),
+                        },
+                        value: value.clone(),
+                    });
+                }
+            }
+
+            out
+        }
+
+        Stmt::If {
+            condition,
+            body,
+            else_body,
+        } => {
+            let mut out = Vec::new();
+
+            for condition in expr_variants(condition) {
+                out.push(Stmt::If {
+                    condition,
+                    body: body.clone(),
+                    else_body: else_body.clone(),
+                });
+            }
+
+            for body in block_variants(body) {
+                out.push(Stmt::If {
+                    condition: condition.clone(),
+                    body,
+                    else_body: else_body.clone(),
+                });
+            }
+
+            for else_body in block_variants(else_body) {
+                out.push(Stmt::If {
+                    condition: condition.clone(),
+                    body: body.clone(),
+                    else_body,
+                });
+            }
+
+            out
+        }
+
+        Stmt::Loop {
+            setup,
+            header,
+            body,
+            step,
+        } => block_variants(body)
+            .into_iter()
+            .map(|body| Stmt::Loop {
+                setup: setup.clone(),
+                header: header.clone(),
+                body,
+                step: step.clone(),
+            })
+            .collect(),
+    }
+}
+
+// Simplest first, a constant in its place, then each side of it, then
+// something simpler inside it. Whichever of 0 and true is the wrong type
+// gets thrown out by the semantic pass
+fn expr_variants(expr: &Expr) -> Vec<Expr> {
+    let constants = ["0", "true"].map(|c| Expr::Atom(c.to_string()));
+
+    let mut out: Vec<Expr> = match expr {
+        Expr::Atom(text) if text == "0" || text == "true" => return Vec::new(),
+        _ => constants.to_vec(),
+    };
+
+    match expr {
+        Expr::Atom(_) => {}
+
+        Expr::Unary(operator, operand) => {
+            out.push(operand.as_ref().clone());
+
+            for operand in expr_variants(operand) {
+                out.push(Expr::Unary(operator, Box::new(operand)));
+            }
+        }
+
+        Expr::Binary(left, operator, right) => {
+            out.push(left.as_ref().clone());
+            out.push(right.as_ref().clone());
+
+            for left in expr_variants(left) {
+                out.push(Expr::Binary(Box::new(left), operator, right.clone()));
+            }
+
+            for right in expr_variants(right) {
+                out.push(Expr::Binary(left.clone(), operator, Box::new(right)));
+            }
+        }
+
+        Expr::Call(name, arguments) => {
+            for (i, argument) in arguments.iter().enumerate() {
+                for argument in expr_variants(argument) {
+                    let mut arguments = arguments.clone();
+                    arguments[i] = argument;
+                    out.push(Expr::Call(name.clone(), arguments));
+                }
+            }
+        }
+
+        Expr::Index {
+            array,
+            length,
+            index,
+        } => {
+            for index in expr_variants(index) {
+                out.push(Expr::Index {
+                    array: array.clone(),
+                    length: *length,
+                    index: Box::new(index),
+                });
+            }
+        }
+    }
+
+    out
+}
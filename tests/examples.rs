@@ -0,0 +1,246 @@
+// Every program in examples/ is compiled and checked against the .asm next
+// to it, then run in the simulator and the interpreter with the .in file (if
+// there is one) as stdin and checked against the .out file. After a change to the
+// generated code or to an example, ECC_BLESS=1 cargo test rewrites them
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ecc::compiler::Compiler;
+use ecc::interp::Interpreter;
+use ecc::rng::Rng;
+use ecc::sim::Simulator;
+
+// Fixed so the guessing games pick the same numbers every run
+const SEED: u64 = 0;
+
+// Every example finishes in well under this, an infinite loop doesn't
+const MAX_STEPS: u64 = 10_000_000;
+
+fn examples() -> Vec<PathBuf> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples");
+
+    let mut sources: Vec<PathBuf> = fs::read_dir(root)
+        .unwrap()
+        .flat_map(|dir| fs::read_dir(dir.unwrap().path()).unwrap())
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "ec"))
+        .collect();
+
+    sources.sort();
+    sources
+}
+
+fn blessing() -> bool {
+    std::env::var_os("ECC_BLESS").is_some_and(|v| v != "0")
+}
+
+// Rewrites expected_path when blessing, otherwise says where the two first differ
+fn check(expected_path: &Path, actual: &str, failures: &mut Vec<String>) {
+    if blessing() {
+        fs::write(expected_path, actual).unwrap();
+        return;
+    }
+
+    let Ok(expected) = fs::read_to_string(expected_path) else {
+        failures.push(format!(
+            "{} is missing, run with ECC_BLESS=1 to create it",
+            expected_path.display()
+        ));
+        return;
+    };
+
+    if expected == actual {
+        return;
+    }
+
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    let mut line = 1;
+
+    loop {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => line += 1,
+            (e, a) => {
+                failures.push(format!(
+                    "{} differs on line {}\n  expected: {}\n  found:    {}",
+                    expected_path.display(),
+                    line,
+                    e.unwrap_or("<end of file>"),
+                    a.unwrap_or("<end of file>")
+                ));
+                return;
+            }
+        }
+    }
+}
+
+fn report(failures: Vec<String>) {
+    assert!(
+        failures.is_empty(),
+        "{}\n\nif the new output is right, rerun with ECC_BLESS=1",
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn assembly_matches_golden_files() {
+    let mut failures = Vec::new();
+
+    for source_path in examples() {
+        let source = fs::read_to_string(&source_path).unwrap();
+        let output_path = Path::new(env!("CARGO_TARGET_TMPDIR"))
+            .join(source_path.with_extension("asm").file_name().unwrap());
+
+        let mut compiler = Compiler::new(&source, &source_path.display().to_string());
+
+        if let Err(errors) = compiler.compile(output_path.to_str().unwrap(), false, false) {
+            failures.push(format!(
+                "{} failed to compile: {}",
+                source_path.display(),
+                errors[0]
+            ));
+            continue;
+        }
+
+        let generated = fs::read_to_string(&output_path).unwrap();
+        check(
+            &source_path.with_extension("asm"),
+            &generated,
+            &mut failures,
+        );
+    }
+
+    report(failures);
+}
+
+#[test]
+fn output_matches_expected() {
+    let mut failures = Vec::new();
+
+    for source_path in examples() {
+        let source = fs::read_to_string(&source_path).unwrap();
+        let mut compiler = Compiler::new(&source, &source_path.display().to_string());
+
+        let assembly = match compiler.get_assembly(false, false) {
+            Ok(assembly) => assembly,
+            Err(errors) => {
+                failures.push(format!(
+                    "{} failed to compile: {}",
+                    source_path.display(),
+                    errors[0]
+                ));
+                continue;
+            }
+        };
+
+        let input = fs::read(source_path.with_extension("in")).unwrap_or_default();
+        let mut output = Vec::new();
+
+        let result = Simulator::new(&assembly, Rng::new(SEED))
+            .and_then(|mut sim| sim.run(&mut input.as_slice(), &mut output, Some(MAX_STEPS)));
+
+        match result {
+            Ok(0) => {
+                let output = String::from_utf8_lossy(&output);
+                check(&source_path.with_extension("out"), &output, &mut failures);
+            }
+
+            Ok(code) => failures.push(format!(
+                "{} exited with code {}",
+                source_path.display(),
+                code
+            )),
+
+            Err(e) => failures.push(format!("{} crashed: {}", source_path.display(), e)),
+        }
+    }
+
+    report(failures);
+}
+
+// Same .out files, blessing is left to the simulator so the two can't drift apart quietly
+#[test]
+fn interpreter_output_matches_expected() {
+    let mut failures = Vec::new();
+
+    for source_path in examples() {
+        let source = fs::read_to_string(&source_path).unwrap();
+        let mut compiler = Compiler::new(&source, &source_path.display().to_string());
+
+        let program = match compiler.get_checked_ast() {
+            Ok(program) => program,
+            Err(errors) => {
+                failures.push(format!(
+                    "{} failed to check: {}",
+                    source_path.display(),
+                    errors[0]
+                ));
+                continue;
+            }
+        };
+
+        let input = fs::read(source_path.with_extension("in")).unwrap_or_default();
+        let mut output = Vec::new();
+
+        let result = Interpreter::new(&program, Rng::new(SEED), &mut input.as_slice(), &mut output)
+            .run(Some(MAX_STEPS));
+
+        match result {
+            Ok(0) => {
+                let expected =
+                    fs::read_to_string(source_path.with_extension("out")).unwrap_or_default();
+
+                if String::from_utf8_lossy(&output) != expected {
+                    failures.push(format!(
+                        "{} printed something different in the interpreter",
+                        source_path.display()
+                    ));
+                }
+            }
+
+            Ok(code) => failures.push(format!(
+                "{} exited with code {}",
+                source_path.display(),
+                code
+            )),
+
+            Err(e) => failures.push(format!("{} crashed: {}", source_path.display(), e)),
+        }
+    }
+
+    report(failures);
+}
+
+// Not in examples/ since without --bounds-check it reads past the end of
+// an array. With it the program has to stop on the bad index, print which
+// line it was on and exit with 1
+#[test]
+fn bounds_check_stops_on_a_bad_index() {
+    let source_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/bounds-check/bounds.ec");
+    let source = fs::read_to_string(&source_path).unwrap();
+
+    let assembly = Compiler::new(&source, &source_path.display().to_string())
+        .get_assembly(false, true)
+        .unwrap_or_else(|errors| panic!("failed to compile: {}", errors[0]));
+
+    let mut failures = Vec::new();
+    check(&source_path.with_extension("asm"), &assembly, &mut failures);
+
+    let input = fs::read(source_path.with_extension("in")).unwrap();
+    let mut output = Vec::new();
+
+    let result = Simulator::new(&assembly, Rng::new(SEED))
+        .and_then(|mut sim| sim.run(&mut input.as_slice(), &mut output, Some(MAX_STEPS)));
+
+    match result {
+        Ok(1) => {
+            let output = String::from_utf8_lossy(&output);
+            check(&source_path.with_extension("out"), &output, &mut failures);
+        }
+
+        Ok(code) => failures.push(format!("exited with code {} instead of 1", code)),
+        Err(e) => failures.push(format!("crashed: {}", e)),
+    }
+
+    report(failures);
+}
@@ -0,0 +1,74 @@
+// Programs the semantic pass has to turn down, so the backend and the
+// interpreter never have to agree on what they'd do
+use ecc::compiler::Compiler;
+
+fn errors(source: &str) -> Vec<String> {
+    match Compiler::new(source, "test.ec").get_checked_ast() {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors.iter().map(|e| e.to_string()).collect(),
+    }
+}
+
+#[test]
+fn non_void_function_has_to_return_on_every_path() {
+    let missing = errors(
+        "int32 h(int32 a) { if (a > 0) { return 1; } }\n\
+         void main() { iprint(h(1)); }\n",
+    );
+
+    assert_eq!(missing.len(), 1, "{:?}", missing);
+    assert!(
+        missing[0].contains("'h' can get to the end"),
+        "{:?}",
+        missing
+    );
+
+    let covered = errors(
+        "int32 h(int32 a) { if (a > 0) { return 1; } else { return 2; } }\n\
+         int32 w(int32 a) { while (true) { if (a > 3) { return a; } a++; } }\n\
+         void v() { }\n\
+         void main() { iprint(h(1) + w(1)); v(); }\n",
+    );
+
+    assert!(covered.is_empty(), "{:?}", covered);
+}
+
+#[test]
+fn program_needs_a_main() {
+    let missing = errors("void helper() { }\n");
+
+    assert_eq!(missing.len(), 1, "{:?}", missing);
+    assert!(
+        missing[0].contains("No main function found"),
+        "{:?}",
+        missing
+    );
+}
+
+#[test]
+fn dividing_by_something_that_folds_to_zero() {
+    let zero = errors(
+        "void main() {\n\
+         int32 x = 7;\n\
+         iprint(x / -0);\n\
+         iprint(x % (1 - 1));\n\
+         iprint(x / ((2 * 3) & 8));\n\
+         }\n",
+    );
+
+    assert_eq!(zero.len(), 3, "{:?}", zero);
+    assert!(
+        zero.iter().all(|e| e.contains("Division by constant zero")),
+        "{:?}",
+        zero
+    );
+
+    let fine = errors(
+        "void main() {\n\
+         int32 x = 7;\n\
+         iprint(x / (1 - 2) + x % (x - x) + 1 / (0 - -1));\n\
+         }\n",
+    );
+
+    assert!(fine.is_empty(), "{:?}", fine);
+}
@@ -0,0 +1,47 @@
+// Hand written assembly where the simulator has to do what MARS does
+use ecc::rng::Rng;
+use ecc::sim::Simulator;
+
+fn run(assembly: &str) -> (Result<i32, String>, String) {
+    let mut output = Vec::new();
+
+    let result = Simulator::new(assembly, Rng::new(0))
+        .and_then(|mut sim| sim.run(&mut [].as_slice(), &mut output, Some(1000)))
+        .map_err(|e| e.message);
+
+    (result, String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn bare_div_by_zero_leaves_hi_and_lo_alone() {
+    let (result, output) = run(".text
+        li      $t0, 7
+        li      $t1, 0
+        li      $t2, 2
+        div     $t0, $t2
+        div     $t0, $t1
+        mflo    $a0
+        li      $v0, 1
+        syscall
+        mfhi    $a0
+        syscall
+    ");
+
+    assert_eq!(result, Ok(0));
+    assert_eq!(output, "31");
+}
+
+#[test]
+fn div_and_rem_pseudo_instructions_break_on_zero() {
+    for mnemonic in ["div", "rem"] {
+        let (result, _) = run(&format!(
+            ".text
+            li      $t0, 7
+            {}     $a0, $t0, $zero
+            ",
+            mnemonic
+        ));
+
+        assert_eq!(result, Err("Division by zero".to_string()));
+    }
+}